@@ -0,0 +1,61 @@
+//!  Delulu Travel Agent
+//!
+//!  Copyright (C) 2026  Mamy Ratsimbazafy
+//!
+//!  This program is free software: you can redistribute it and/or modify
+//!  it under the terms of the GNU Affero General Public License as published by
+//!  the Free Software Foundation, either version 3 of the License, or
+//!  (at your option) any later version.
+//!
+//!  This program is distributed in the hope that it will be useful,
+//!  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//!  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//!  GNU Affero General Public License for more details.
+//!
+//!  You should have received a copy of the GNU Affero General Public License
+//!  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Flights results parsing benchmark
+//!
+//! Parses the largest committed Google Flights fixture, a ~9 MB page.
+//!
+//! ```bash
+//! cargo bench -p delulu-travel-agent --bench parse_flights
+//! ```
+
+use std::path::Path;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use delulu_travel_agent::{FlightSearchParams, FlightSearchResult};
+
+const FIXTURE: &str = "domestic+business-lax_ord";
+
+fn load_fixture(name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures-flights-parsing")
+        .join(format!("{}.html.zst", name));
+    let compressed = std::fs::read(&path).expect("read fixture");
+    let decoder = zstd::stream::Decoder::new(compressed.as_slice()).expect("create zstd decoder");
+    std::io::read_to_string(decoder).expect("decompress fixture")
+}
+
+fn bench_parse_flights(c: &mut Criterion) {
+    let html = load_fixture(FIXTURE);
+    let params = FlightSearchParams::builder(
+        "LAX".into(),
+        "ORD".into(),
+        chrono::NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+    )
+    .build()
+    .unwrap();
+
+    let mut group = c.benchmark_group("parse_flights");
+    group.sample_size(20);
+    group.bench_function(FIXTURE, |b| {
+        b.iter(|| FlightSearchResult::from_html(&html, params.clone()).expect("parse fixture"))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse_flights);
+criterion_main!(benches);
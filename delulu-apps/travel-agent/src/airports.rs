@@ -0,0 +1,281 @@
+//!  Delulu Travel Agent
+//!
+//!  Copyright (C) 2026  Mamy Ratsimbazafy
+//!
+//!  This program is free software: you can redistribute it and/or modify
+//!  it under the terms of the GNU Affero General Public License as published by
+//!  the Free Software Foundation, either version 3 of the License, or
+//!  (at your option) any later version.
+//!
+//!  This program is distributed in the hope that it will be useful,
+//!  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//!  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//!  GNU Affero General Public License for more details.
+//!
+//!  You should have received a copy of the GNU Affero General Public License
+//!  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Airports
+//!
+//! A small embedded table of major airports and their coordinates, used for
+//! distance-based heuristics. Airports missing from the table are simply
+//! unknown: helpers never guess their position.
+
+use crate::flights_query_builder::FlightSearchParams;
+
+/// Great-circle distance beyond which nonstop service is uncommon.
+///
+/// Ultra-long-haul nonstops exist (SIN–JFK is ~15,300 km) but only on a
+/// handful of routes, so past this point a nonstop-only search coming back
+/// empty is more likely a route limitation than a parsing problem.
+pub const TYPICAL_NONSTOP_MAX_KM: f64 = 9_000.0;
+
+/// Upper bounds of the short- and medium-haul [`RouteClass`]es.
+pub const SHORT_HAUL_MAX_KM: f64 = 1_500.0;
+pub const MEDIUM_HAUL_MAX_KM: f64 = 4_000.0;
+
+const EARTH_RADIUS_KM: f64 = 6_371.0;
+
+/// IATA code, latitude, longitude (degrees).
+const AIRPORTS: &[(&str, f64, f64)] = &[
+    // North America
+    ("ATL", 33.6407, -84.4277),
+    ("BOS", 42.3656, -71.0096),
+    ("DEN", 39.8561, -104.6737),
+    ("DFW", 32.8998, -97.0403),
+    ("EWR", 40.6895, -74.1745),
+    ("IAD", 38.9531, -77.4565),
+    ("JFK", 40.6413, -73.7781),
+    ("LAX", 33.9416, -118.4085),
+    ("LGA", 40.7769, -73.8740),
+    ("MIA", 25.7959, -80.2870),
+    ("OAK", 37.7126, -122.2197),
+    ("ORD", 41.9742, -87.9073),
+    ("SEA", 47.4502, -122.3088),
+    ("SFO", 37.6213, -122.3790),
+    ("SJC", 37.3639, -121.9289),
+    ("YUL", 45.4706, -73.7408),
+    ("YVR", 49.1967, -123.1815),
+    ("YYZ", 43.6777, -79.6248),
+    ("MEX", 19.4361, -99.0719),
+    // South America
+    ("BOG", 4.7016, -74.1469),
+    ("EZE", -34.8222, -58.5358),
+    ("GRU", -23.4356, -46.4731),
+    ("LIM", -12.0219, -77.1143),
+    ("SCL", -33.3930, -70.7858),
+    // Europe
+    ("AMS", 52.3105, 4.7683),
+    ("BCN", 41.2974, 2.0833),
+    ("CDG", 49.0097, 2.5479),
+    ("FCO", 41.8003, 12.2389),
+    ("FRA", 50.0379, 8.5622),
+    ("IST", 41.2753, 28.7519),
+    ("LGW", 51.1537, -0.1821),
+    ("LHR", 51.4700, -0.4543),
+    ("LIS", 38.7742, -9.1342),
+    ("MAD", 40.4983, -3.5676),
+    ("MUC", 48.3537, 11.7750),
+    ("ORY", 48.7262, 2.3652),
+    ("ZRH", 47.4582, 8.5555),
+    // Middle East & Africa
+    ("DOH", 25.2731, 51.6081),
+    ("DXB", 25.2532, 55.3657),
+    ("CAI", 30.1219, 31.4056),
+    ("JNB", -26.1392, 28.2460),
+    ("NBO", -1.3192, 36.9278),
+    ("TNR", -18.7969, 47.4788),
+    // Asia & Oceania
+    ("BKK", 13.6900, 100.7501),
+    ("DEL", 28.5562, 77.1000),
+    ("HKG", 22.3080, 113.9185),
+    ("HND", 35.5494, 139.7798),
+    ("ICN", 37.4602, 126.4407),
+    ("KIX", 34.4320, 135.2304),
+    ("NRT", 35.7720, 140.3929),
+    ("PEK", 40.0799, 116.6031),
+    ("PVG", 31.1443, 121.8083),
+    ("SIN", 1.3644, 103.9915),
+    ("AKL", -37.0082, 174.7850),
+    ("MEL", -37.6690, 144.8410),
+    ("SYD", -33.9399, 151.1753),
+];
+
+/// Latitude and longitude of `iata`, if the airport is in the embedded table.
+pub fn airport_coordinates(iata: &str) -> Option<(f64, f64)> {
+    AIRPORTS
+        .iter()
+        .find(|(code, _, _)| code.eq_ignore_ascii_case(iata))
+        .map(|&(_, lat, lon)| (lat, lon))
+}
+
+/// Great-circle (haversine) distance between two airports in kilometers.
+pub fn great_circle_km(from: &str, to: &str) -> Option<f64> {
+    Some(haversine_km(
+        airport_coordinates(from)?,
+        airport_coordinates(to)?,
+    ))
+}
+
+/// Great-circle distance in kilometers between two `(latitude, longitude)`
+/// points.
+pub(crate) fn haversine_km((lat1, lon1): (f64, f64), (lat2, lon2): (f64, f64)) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let dphi = (lat2 - lat1).to_radians();
+    let dlambda = (lon2 - lon1).to_radians();
+    let a = (dphi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (dlambda / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+/// `iata` followed by every other known airport within `radius_km` of it,
+/// nearest first. An airport missing from the table only yields itself.
+pub fn airports_within(iata: &str, radius_km: u32) -> Vec<String> {
+    let mut nearby: Vec<(f64, &str)> = AIRPORTS
+        .iter()
+        .filter(|(code, _, _)| !code.eq_ignore_ascii_case(iata))
+        .filter_map(|&(code, _, _)| Some((great_circle_km(iata, code)?, code)))
+        .filter(|&(distance, _)| distance <= radius_km as f64)
+        .collect();
+    nearby.sort_by(|a, b| a.0.total_cmp(&b.0));
+    std::iter::once(iata.to_string())
+        .chain(nearby.into_iter().map(|(_, code)| code.to_string()))
+        .collect()
+}
+
+/// Hint for a nonstop-only search from `from` to `to` on a route where
+/// nonstop service is atypical, to be surfaced when such a search comes back
+/// empty. Callers decide whether the search was nonstop-only: a `max_stops`
+/// of 0 does not survive [`FlightSearchParamsBuilder::build`].
+///
+/// This is a distance heuristic, not schedule data: `None` does not mean a
+/// nonstop exists, and `Some` does not mean one doesn't.
+///
+/// [`FlightSearchParamsBuilder::build`]: crate::FlightSearchParamsBuilder::build
+pub fn nonstop_hint(from: &str, to: &str) -> Option<String> {
+    let distance = great_circle_km(from, to)?;
+    (distance > TYPICAL_NONSTOP_MAX_KM).then(|| {
+        format!(
+            "No nonstop service is typical on {}→{} (~{:.0} km); consider increasing max_stops",
+            from, to, distance
+        )
+    })
+}
+
+/// Distance band of a route. Without country data, distance stands in for
+/// domestic, international and long-haul.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteClass {
+    ShortHaul,
+    MediumHaul,
+    LongHaul,
+}
+
+impl RouteClass {
+    /// Class of `from`→`to`, if both airports are in the embedded table.
+    pub fn of(from: &str, to: &str) -> Option<Self> {
+        let distance = great_circle_km(from, to)?;
+        Some(if distance <= SHORT_HAUL_MAX_KM {
+            Self::ShortHaul
+        } else if distance <= MEDIUM_HAUL_MAX_KM {
+            Self::MediumHaul
+        } else {
+            Self::LongHaul
+        })
+    }
+
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::ShortHaul => "short-haul",
+            Self::MediumHaul => "medium-haul",
+            Self::LongHaul => "long-haul",
+        }
+    }
+}
+
+/// Fewest itineraries a search is expected to return, per [`RouteClass`].
+///
+/// Google lists dozens of options on most routes, so a handful usually
+/// means cards the parser skipped. A heuristic, reported as a warning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResultExpectations {
+    pub short_haul: usize,
+    pub medium_haul: usize,
+    pub long_haul: usize,
+}
+
+impl Default for ResultExpectations {
+    fn default() -> Self {
+        Self {
+            short_haul: 5,
+            medium_haul: 5,
+            long_haul: 3,
+        }
+    }
+}
+
+impl ResultExpectations {
+    pub fn minimum(&self, class: RouteClass) -> usize {
+        match class {
+            RouteClass::ShortHaul => self.short_haul,
+            RouteClass::MediumHaul => self.medium_haul,
+            RouteClass::LongHaul => self.long_haul,
+        }
+    }
+
+    /// Warning when `count` itineraries are fewer than expected on the route
+    /// searched with `params`.
+    ///
+    /// Searches narrowed by `max_stops` or airlines, and routes with an
+    /// airport missing from the table, are not checked.
+    pub fn low_results_warning(&self, params: &FlightSearchParams, count: usize) -> Option<String> {
+        if params.max_stops.is_some()
+            || params.return_max_stops.is_some()
+            || params.preferred_airlines.is_some()
+            || params.outbound_preferred_airlines.is_some()
+            || params.return_preferred_airlines.is_some()
+        {
+            return None;
+        }
+        let class = RouteClass::of(&params.from_airport, &params.to_airport)?;
+        let minimum = self.minimum(class);
+        (count < minimum).then(|| {
+            format!(
+                "Only {} itinerary(ies) for {}→{}, a {} route where at least {} are expected; some results may not have been parsed",
+                count,
+                params.from_airport,
+                params.to_airport,
+                class.as_str_name(),
+                minimum
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_great_circle_km() {
+        let d = great_circle_km("MAD", "NRT").unwrap();
+        assert!((10_500.0..11_000.0).contains(&d), "MAD-NRT: {d}");
+        let d = great_circle_km("sfo", "lax").unwrap();
+        assert!((500.0..600.0).contains(&d), "SFO-LAX: {d}");
+        assert_eq!(great_circle_km("SFO", "ZZZ"), None);
+    }
+
+    #[test]
+    fn test_route_class() {
+        assert_eq!(RouteClass::of("SFO", "LAX"), Some(RouteClass::ShortHaul));
+        assert_eq!(RouteClass::of("LAX", "ORD"), Some(RouteClass::MediumHaul));
+        assert_eq!(RouteClass::of("JFK", "CDG"), Some(RouteClass::LongHaul));
+        assert_eq!(RouteClass::of("JFK", "ZZZ"), None);
+    }
+
+    #[test]
+    fn test_airports_within() {
+        assert_eq!(airports_within("SFO", 100), ["SFO", "OAK", "SJC"]);
+        assert_eq!(airports_within("SFO", 10), ["SFO"]);
+        assert_eq!(airports_within("ZZZ", 500), ["ZZZ"]);
+    }
+}
@@ -0,0 +1,124 @@
+//!  Delulu Travel Agent
+//!
+//!  Copyright (C) 2026  Mamy Ratsimbazafy
+//!
+//!  This program is free software: you can redistribute it and/or modify
+//!  it under the terms of the GNU Affero General Public License as published by
+//!  the Free Software Foundation, either version 3 of the License, or
+//!  (at your option) any later version.
+//!
+//!  This program is distributed in the hope that it will be useful,
+//!  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//!  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//!  GNU Affero General Public License for more details.
+//!
+//!  You should have received a copy of the GNU Affero General Public License
+//!  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Airline Alliances
+//!
+//! Embedded alliance → member IATA codes table, so `preferred_airlines` can
+//! name an alliance ("STAR_ALLIANCE", "oneworld", "SkyTeam") instead of
+//! listing its carriers. Membership is as of 2025 and changes over time.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Alliance {
+    StarAlliance,
+    Oneworld,
+    SkyTeam,
+}
+
+impl Alliance {
+    pub const ALL: [Alliance; 3] = [
+        Alliance::StarAlliance,
+        Alliance::Oneworld,
+        Alliance::SkyTeam,
+    ];
+
+    /// Parses an alliance name, ignoring case, spaces, dashes and underscores:
+    /// "STAR_ALLIANCE", "Star Alliance", "oneworld", "sky-team"...
+    pub fn from_name(name: &str) -> Option<Self> {
+        let normalized: String = name
+            .chars()
+            .filter(|c| !matches!(c, ' ' | '-' | '_'))
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+        match normalized.as_str() {
+            "STARALLIANCE" | "STAR" => Some(Alliance::StarAlliance),
+            "ONEWORLD" => Some(Alliance::Oneworld),
+            "SKYTEAM" => Some(Alliance::SkyTeam),
+            _ => None,
+        }
+    }
+
+    /// IATA codes of the member airlines.
+    pub fn members(self) -> &'static [&'static str] {
+        match self {
+            Alliance::StarAlliance => &[
+                "A3", "AC", "AI", "AV", "BR", "CA", "CM", "ET", "LH", "LO", "LX", "MS", "NH", "NZ",
+                "OS", "OU", "OZ", "SA", "SN", "SQ", "TG", "TK", "TP", "UA", "ZH",
+            ],
+            Alliance::Oneworld => &[
+                "AA", "AS", "AT", "AY", "BA", "CX", "IB", "JL", "MH", "QF", "QR", "RJ", "UL",
+            ],
+            Alliance::SkyTeam => &[
+                "AF", "AM", "AR", "CI", "DL", "GA", "KE", "KL", "KQ", "ME", "MF", "MU", "RO", "SK",
+                "SV", "UX", "VN", "VS",
+            ],
+        }
+    }
+}
+
+/// Replaces alliance names in `airlines` with their member codes, keeping
+/// plain airline codes as given and dropping duplicates.
+pub fn expand_alliances(airlines: &[String]) -> Vec<String> {
+    let mut expanded: Vec<String> = Vec::with_capacity(airlines.len());
+    for entry in airlines {
+        let codes = match Alliance::from_name(entry) {
+            Some(alliance) => alliance.members().iter().map(|c| c.to_string()).collect(),
+            None => vec![entry.clone()],
+        };
+        for code in codes {
+            if !expanded.contains(&code) {
+                expanded.push(code);
+            }
+        }
+    }
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alliance_names() {
+        for name in ["STAR_ALLIANCE", "Star Alliance", "star-alliance"] {
+            assert_eq!(Alliance::from_name(name), Some(Alliance::StarAlliance));
+        }
+        assert_eq!(Alliance::from_name("oneworld"), Some(Alliance::Oneworld));
+        assert_eq!(Alliance::from_name("SKYTEAM"), Some(Alliance::SkyTeam));
+        assert_eq!(Alliance::from_name("UA"), None);
+    }
+
+    #[test]
+    fn test_members_are_disjoint() {
+        let mut seen = std::collections::HashSet::new();
+        for alliance in Alliance::ALL {
+            for code in alliance.members() {
+                assert!(seen.insert(code), "{code} listed twice");
+            }
+        }
+    }
+
+    #[test]
+    fn test_expand_alliances_keeps_codes_and_dedups() {
+        let expanded = expand_alliances(&["UA".to_string(), "STAR_ALLIANCE".to_string()]);
+        assert_eq!(expanded[0], "UA");
+        assert_eq!(expanded.len(), Alliance::StarAlliance.members().len());
+    }
+}
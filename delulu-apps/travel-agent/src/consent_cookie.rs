@@ -17,8 +17,10 @@
 
 //! SOCS cookie generation for Google services.
 
+use anyhow::Result;
 use base64::{Engine as _, engine::general_purpose::STANDARD};
-use chrono::{Datelike, Local};
+use chrono::{Datelike, Local, NaiveDate};
+use std::sync::{Arc, Mutex};
 
 // =============================================================================
 // Constants - Known-Good Browser Values
@@ -96,6 +98,74 @@ pub fn generate_cookie_header() -> String {
     format!("CONSENT=PENDING+987; {}", socs)
 }
 
+/// Source of the consent `Cookie` header sent with every Google request.
+///
+/// Swap it in with `cookie_provider` on the clients when Google changes its
+/// consent scheme, or to send a fixed cookie in tests.
+pub trait CookieProvider: Send + Sync {
+    /// Complete `Cookie` header value for a client using `language`.
+    fn cookie_header(&self, language: &str) -> Result<String>;
+}
+
+/// The built-in provider: [`generate_cookie_header`].
+///
+/// The SOCS value always carries the "en" locale, which Google accepts
+/// whatever the page language, so `language` is ignored.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SocsCookieProvider;
+
+impl CookieProvider for SocsCookieProvider {
+    fn cookie_header(&self, _language: &str) -> Result<String> {
+        Ok(generate_cookie_header())
+    }
+}
+
+/// Cookie header cached for the local day it was generated on.
+///
+/// The SOCS value embeds yesterday's date, so the header is regenerated once
+/// the date (or the language) changes.
+pub(crate) struct CookieCache {
+    provider: Arc<dyn CookieProvider>,
+    cached: Mutex<Option<(NaiveDate, String, String)>>,
+    /// Number of headers generated, to check the cache is used.
+    #[cfg(test)]
+    pub(crate) generations: std::sync::atomic::AtomicUsize,
+}
+
+impl Default for CookieCache {
+    fn default() -> Self {
+        Self::new(Arc::new(SocsCookieProvider))
+    }
+}
+
+impl CookieCache {
+    pub(crate) fn new(provider: Arc<dyn CookieProvider>) -> Self {
+        Self {
+            provider,
+            cached: Mutex::new(None),
+            #[cfg(test)]
+            generations: Default::default(),
+        }
+    }
+
+    /// Returns the cached header, generating it on first use or after midnight.
+    pub(crate) fn header(&self, language: &str) -> Result<String> {
+        let today = Local::now().date_naive();
+        let mut cached = self.cached.lock().unwrap_or_else(|e| e.into_inner());
+        match cached.as_ref() {
+            Some((date, lang, header)) if *date == today && lang == language => Ok(header.clone()),
+            _ => {
+                #[cfg(test)]
+                self.generations
+                    .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                let header = self.provider.cookie_header(language)?;
+                *cached = Some((today, language.to_string(), header.clone()));
+                Ok(header)
+            }
+        }
+    }
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -128,6 +198,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn cookie_cache_generates_once() {
+        let cache = CookieCache::default();
+        assert_eq!(cache.header("en").unwrap(), cache.header("en").unwrap());
+        assert_eq!(
+            cache.generations.load(std::sync::atomic::Ordering::Relaxed),
+            1
+        );
+    }
+
     #[test]
     fn any_protobuf_bytes_work() {
         let socs = generate_socs_cookie();
@@ -0,0 +1,259 @@
+//!  Delulu Travel Agent
+//!
+//!  Copyright (C) 2026  Mamy Ratsimbazafy
+//!
+//!  This program is free software: you can redistribute it and/or modify
+//!  it under the terms of the GNU Affero General Public License as published by
+//!  the Free Software Foundation, either version 3 of the License, or
+//!  (at your option) any later version.
+//!
+//!  This program is distributed in the hope that it will be useful,
+//!  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//!  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//!  GNU Affero General Public License for more details.
+//!
+//!  You should have received a copy of the GNU Affero General Public License
+//!  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Currencies
+//!
+//! Validated currency codes, and client-side price conversion: Google Flights
+//! reliably returns prices in USD only, so callers can opt into converting
+//! them with exchange rates they supply. [`CurrencySymbols`] tells which
+//! currency a scraped price is in from its symbol.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use thiserror::Error;
+
+/// ISO 4217 codes Google accepts as the `curr` of a search.
+pub const SUPPORTED_CURRENCIES: [&str; 32] = [
+    "AED", "ARS", "AUD", "BRL", "CAD", "CHF", "CLP", "CNY", "COP", "CZK", "DKK", "EUR", "GBP",
+    "HKD", "HUF", "IDR", "ILS", "INR", "JPY", "KRW", "MXN", "MYR", "NOK", "NZD", "PHP", "PLN",
+    "SAR", "SEK", "SGD", "THB", "TRY", "USD",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum CurrencyError {
+    /// Not three ASCII letters, e.g. "US$" or "Dollars".
+    #[error("invalid currency code '{0}': expected an ISO 4217 code such as USD")]
+    InvalidCode(String),
+    /// A well-formed code outside [`SUPPORTED_CURRENCIES`].
+    #[error("unsupported currency '{0}'")]
+    Unsupported(String),
+}
+
+/// An uppercase ISO 4217 code from [`SUPPORTED_CURRENCIES`], safe to send as
+/// `curr`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Currency(String);
+
+impl Currency {
+    /// Validates `code`, accepting any letter case: `"eur"` becomes `EUR`.
+    pub fn new(code: &str) -> Result<Self, CurrencyError> {
+        let code = code.trim();
+        if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(CurrencyError::InvalidCode(code.to_string()));
+        }
+        let code = code.to_ascii_uppercase();
+        if !SUPPORTED_CURRENCIES.contains(&code.as_str()) {
+            return Err(CurrencyError::Unsupported(code));
+        }
+        Ok(Self(code))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Currency {
+    type Err = CurrencyError;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        Self::new(code)
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<Currency> for String {
+    fn from(currency: Currency) -> Self {
+        currency.0
+    }
+}
+
+/// Source of exchange rates for client-side price conversion.
+pub trait ExchangeRateProvider: Send + Sync {
+    /// Units of `to` per one unit of `from`, or `None` if the pair is unknown.
+    ///
+    /// Currency codes are ISO 4217 (e.g. "USD", "EUR").
+    fn rate(&self, from: &str, to: &str) -> Option<f64>;
+}
+
+/// Target currency and the provider used to convert into it.
+#[derive(Clone)]
+pub struct CurrencyConversion {
+    pub target: String,
+    pub provider: Arc<dyn ExchangeRateProvider>,
+}
+
+impl CurrencyConversion {
+    pub fn new(target: impl Into<String>, provider: Arc<dyn ExchangeRateProvider>) -> Self {
+        Self {
+            target: target.into(),
+            provider,
+        }
+    }
+
+    /// Converts a whole-unit `amount` from `from`, rounded to the nearest unit.
+    pub fn convert(&self, amount: i32, from: &str) -> Option<i32> {
+        let rate = self.provider.rate(from, &self.target)?;
+        Some((amount as f64 * rate).round() as i32)
+    }
+}
+
+/// Currencies each price symbol may stand for, most common first. `$` and `¥`
+/// are shared, so the configured currency decides between the candidates.
+pub const DEFAULT_CURRENCY_SYMBOLS: &[(&str, &[&str])] = &[
+    (
+        "$",
+        &[
+            "USD", "AUD", "CAD", "NZD", "SGD", "HKD", "MXN", "ARS", "CLP", "COP",
+        ],
+    ),
+    ("US$", &["USD"]),
+    ("A$", &["AUD"]),
+    ("C$", &["CAD"]),
+    ("CA$", &["CAD"]),
+    ("NZ$", &["NZD"]),
+    ("S$", &["SGD"]),
+    ("HK$", &["HKD"]),
+    ("MX$", &["MXN"]),
+    ("R$", &["BRL"]),
+    ("£", &["GBP"]),
+    ("€", &["EUR"]),
+    ("¥", &["JPY", "CNY"]),
+    ("JP¥", &["JPY"]),
+    ("CN¥", &["CNY"]),
+    ("₹", &["INR"]),
+    ("₩", &["KRW"]),
+    ("₪", &["ILS"]),
+    ("₱", &["PHP"]),
+    ("฿", &["THB"]),
+    ("₺", &["TRY"]),
+    ("zł", &["PLN"]),
+    ("Kč", &["CZK"]),
+    ("kr", &["SEK", "NOK", "DKK"]),
+];
+
+/// Maps the symbol of a scraped price to its currency code.
+///
+/// Defaults to [`DEFAULT_CURRENCY_SYMBOLS`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurrencySymbols {
+    symbols: Vec<(String, Vec<String>)>,
+}
+
+impl Default for CurrencySymbols {
+    fn default() -> Self {
+        Self {
+            symbols: DEFAULT_CURRENCY_SYMBOLS
+                .iter()
+                .map(|(symbol, codes)| {
+                    let codes = codes.iter().map(|c| c.to_string()).collect();
+                    (symbol.to_string(), codes)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl CurrencySymbols {
+    /// Maps `symbol` to `codes`, most likely first, replacing any existing
+    /// mapping.
+    pub fn symbol(mut self, symbol: &str, codes: &[&str]) -> Self {
+        let codes = codes.iter().map(|c| c.to_ascii_uppercase()).collect();
+        match self.symbols.iter_mut().find(|(s, _)| s == symbol) {
+            Some((_, existing)) => *existing = codes,
+            None => self.symbols.push((symbol.to_string(), codes)),
+        }
+        self
+    }
+
+    /// Currency of a formatted price such as "$123" or "1.234 €", `None`
+    /// without a known symbol.
+    ///
+    /// The longest matching symbol wins, so "A$123" is AUD rather than the
+    /// `$` default. An ambiguous symbol resolves to `preferred`, the currency
+    /// the search was made in, when it may stand for it, and to its most
+    /// common currency otherwise.
+    pub fn currency_of(&self, price: &str, preferred: Option<&str>) -> Option<&str> {
+        let (_, codes) = self
+            .symbols
+            .iter()
+            .filter(|(symbol, _)| price.contains(symbol.as_str()))
+            .max_by_key(|(symbol, _)| symbol.chars().count())?;
+        preferred
+            .and_then(|p| codes.iter().find(|c| c.eq_ignore_ascii_case(p)))
+            .or_else(|| codes.first())
+            .map(String::as_str)
+    }
+}
+
+impl std::fmt::Debug for CurrencyConversion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CurrencyConversion")
+            .field("target", &self.target)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_currency_normalization() {
+        assert_eq!(Currency::new("usd").unwrap().as_str(), "USD");
+        assert_eq!(Currency::new(" eur ").unwrap().to_string(), "EUR");
+        assert_eq!("JPY".parse::<Currency>().unwrap().as_str(), "JPY");
+
+        for invalid in ["US$", "Dollars", "", "EU", "€"] {
+            assert_eq!(
+                Currency::new(invalid),
+                Err(CurrencyError::InvalidCode(invalid.trim().to_string()))
+            );
+        }
+        assert_eq!(
+            Currency::new("xyz"),
+            Err(CurrencyError::Unsupported("XYZ".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_currency_symbols_use_configured_currency() {
+        let symbols = CurrencySymbols::default();
+        assert_eq!(symbols.currency_of("$123", None), Some("USD"));
+        assert_eq!(symbols.currency_of("$123", Some("USD")), Some("USD"));
+        assert_eq!(symbols.currency_of("$123", Some("AUD")), Some("AUD"));
+        // `$` cannot stand for euros: fall back to its most common currency.
+        assert_eq!(symbols.currency_of("$123", Some("EUR")), Some("USD"));
+
+        assert_eq!(symbols.currency_of("A$123", Some("USD")), Some("AUD"));
+        assert_eq!(symbols.currency_of("¥12,300", None), Some("JPY"));
+        assert_eq!(symbols.currency_of("¥12,300", Some("CNY")), Some("CNY"));
+        assert_eq!(symbols.currency_of("1.234 €", Some("USD")), Some("EUR"));
+        assert_eq!(symbols.currency_of("123", Some("USD")), None);
+
+        let symbols = symbols.symbol("$", &["cad", "usd"]);
+        assert_eq!(symbols.currency_of("$123", None), Some("CAD"));
+        assert_eq!(symbols.currency_of("$123", Some("USD")), Some("USD"));
+    }
+}
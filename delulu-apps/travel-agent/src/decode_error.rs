@@ -0,0 +1,40 @@
+//!  Delulu Travel Agent
+//!
+//!  Copyright (C) 2026  Mamy Ratsimbazafy
+//!
+//!  This program is free software: you can redistribute it and/or modify
+//!  it under the terms of the GNU Affero General Public License as published by
+//!  the Free Software Foundation, either version 3 of the License, or
+//!  (at your option) any later version.
+//!
+//!  This program is distributed in the hope that it will be useful,
+//!  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//!  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//!  GNU Affero General Public License for more details.
+//!
+//!  You should have received a copy of the GNU Affero General Public License
+//!  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Decode Errors
+//!
+//! Typed failures for turning a Google `tfs`/`ts` URL parameter back into
+//! search parameters, so callers can tell a mangled URL apart from a valid
+//! one that is missing something.
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum DecodeError {
+    /// The parameter is not valid base64 (wrong alphabet, bad padding...).
+    #[error("invalid base64: {0}")]
+    InvalidBase64(#[from] base64::DecodeError),
+    /// The bytes are not a valid protobuf message, e.g. truncated mid-field.
+    #[error("invalid protobuf: {0}")]
+    InvalidProtobuf(#[from] prost::DecodeError),
+    /// The message decoded but lacks a field a search cannot do without.
+    #[error("missing required field: {0}")]
+    MissingRequiredField(&'static str),
+    /// A field is present but its value is unusable.
+    #[error("invalid {field}: {reason}")]
+    InvalidField { field: &'static str, reason: String },
+}
@@ -0,0 +1,278 @@
+//!  Delulu Travel Agent
+//!
+//!  Copyright (C) 2026  Mamy Ratsimbazafy
+//!
+//!  This program is free software: you can redistribute it and/or modify
+//!  it under the terms of the GNU Affero General Public License as published by
+//!  the Free Software Foundation, either version 3 of the License, or
+//!  (at your option) any later version.
+//!
+//!  This program is distributed in the hope that it will be useful,
+//!  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//!  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//!  GNU Affero General Public License for more details.
+//!
+//!  You should have received a copy of the GNU Affero General Public License
+//!  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Search Diagnostics
+//!
+//! Explains an empty or suspicious flight search from the page Google served:
+//! consent or CAPTCHA interstitials, a layout the parser does not know,
+//! response size, and how many elements each parser selector matched.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::FlightSearchResult;
+
+/// Flight result pages weigh several hundred KB; anything below this is
+/// unlikely to contain results.
+const MIN_RESULTS_PAGE_BYTES: usize = 50 * 1024;
+
+/// Google's cookie consent interstitial.
+pub(crate) fn is_consent_page(body: &str) -> bool {
+    body.contains("consent.google.com")
+        || body.contains("base href=\"https://consent.google.com\"")
+        || body.contains("ppConfig")
+}
+
+/// Google's "unusual traffic" CAPTCHA interstitial.
+pub(crate) fn is_captcha_page(body: &str) -> bool {
+    body.contains("/sorry/index")
+        || body.contains("g-recaptcha")
+        || body.contains("unusual traffic from your computer network")
+}
+
+/// Results shell whose flights are loaded later by JavaScript.
+pub(crate) fn is_loading_placeholder(body: &str) -> bool {
+    let has_flight_cards = body.contains("pIav2d") || body.contains("JMc5Xc");
+    let has_loading = body.contains("Loading results") || body.contains("jsshadow");
+    !has_flight_cards && has_loading
+}
+
+/// Prefix of the UI bundle a results page is rendered by, e.g.
+/// `FlightsFrontendUi_desktop_ms` for the desktop layout the parser targets.
+const UI_BUNDLE_PREFIX: &str = "FlightsFrontendUi_";
+
+/// Name of the UI bundle of a results page served in another layout than
+/// desktop, typically the mobile one whose markup the selectors do not match.
+pub(crate) fn non_desktop_layout(body: &str) -> Option<&str> {
+    let start = body.find(UI_BUNDLE_PREFIX)?;
+    let len = body[start..]
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(body.len() - start);
+    let bundle = &body[start..start + len];
+    (!bundle[UI_BUNDLE_PREFIX.len()..].starts_with("desktop")).then_some(bundle)
+}
+
+/// A results page rendered in a layout the parser has no selectors for.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error(
+    "Google served the {layout} layout instead of the desktop one; \
+     its markup cannot be parsed, check the client emulation"
+)]
+pub struct UnexpectedLayout {
+    /// UI bundle the page was rendered by.
+    pub layout: String,
+}
+
+impl UnexpectedLayout {
+    /// Fails when `html` is in another layout than desktop.
+    pub(crate) fn check(html: &str) -> Result<(), Self> {
+        match non_desktop_layout(html) {
+            Some(layout) => Err(Self {
+                layout: layout.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Selectors without which no itinerary can be parsed.
+pub const CRITICAL_SELECTORS: [&str; 5] = ["flight_card", "airline", "times", "duration", "price"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+pub struct SelectorMatch {
+    pub selector: String,
+    pub count: usize,
+}
+
+/// Structured explanation of a flight search response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+pub struct SearchDiagnosis {
+    /// URL that was fetched, to open in a browser for comparison.
+    pub search_url: String,
+    pub http_status: u16,
+    pub response_bytes: usize,
+    pub consent_page: bool,
+    pub captcha_page: bool,
+    pub loading_placeholder: bool,
+    /// The page is in the mobile (or another non-desktop) layout.
+    #[serde(default)]
+    pub mobile_layout: bool,
+    pub selector_matches: Vec<SelectorMatch>,
+    /// Human-readable suggestions, most likely cause first.
+    pub next_steps: Vec<String>,
+}
+
+/// Outcome of [`GoogleFlightsClient::self_check`](crate::GoogleFlightsClient::self_check).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+pub struct SelectorHealth {
+    pub search_url: String,
+    /// False if the page could not be fetched or a critical selector
+    /// matched nothing.
+    pub healthy: bool,
+    pub selector_matches: Vec<SelectorMatch>,
+    /// Critical selectors that matched nothing.
+    pub broken_selectors: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl SelectorHealth {
+    pub fn from_flights_page(search_url: &str, html: &str) -> Self {
+        let selector_matches = selector_matches(html);
+        let broken_selectors: Vec<String> = CRITICAL_SELECTORS
+            .iter()
+            .filter(|&&critical| {
+                !selector_matches
+                    .iter()
+                    .any(|m| m.selector == critical && m.count > 0)
+            })
+            .map(|critical| critical.to_string())
+            .collect();
+        Self {
+            search_url: search_url.to_string(),
+            healthy: broken_selectors.is_empty(),
+            selector_matches,
+            broken_selectors,
+            error: None,
+        }
+    }
+
+    pub fn fetch_failed(search_url: &str, error: &anyhow::Error) -> Self {
+        Self {
+            search_url: search_url.to_string(),
+            healthy: false,
+            selector_matches: Vec::new(),
+            broken_selectors: Vec::new(),
+            error: Some(format!("{error:#}")),
+        }
+    }
+}
+
+fn selector_matches(html: &str) -> Vec<SelectorMatch> {
+    FlightSearchResult::selector_match_counts(html)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(selector, count)| SelectorMatch {
+            selector: selector.to_string(),
+            count,
+        })
+        .collect()
+}
+
+impl SearchDiagnosis {
+    pub fn from_flights_page(search_url: &str, http_status: u16, html: &str) -> Self {
+        let selector_matches = selector_matches(html);
+
+        let mut diagnosis = Self {
+            search_url: search_url.to_string(),
+            http_status,
+            response_bytes: html.len(),
+            consent_page: is_consent_page(html),
+            captcha_page: is_captcha_page(html),
+            loading_placeholder: is_loading_placeholder(html),
+            mobile_layout: non_desktop_layout(html).is_some(),
+            selector_matches,
+            next_steps: Vec::new(),
+        };
+        diagnosis.next_steps = diagnosis.suggest_next_steps();
+        diagnosis
+    }
+
+    fn matches(&self, selector: &str) -> usize {
+        self.selector_matches
+            .iter()
+            .find(|m| m.selector == selector)
+            .map_or(0, |m| m.count)
+    }
+
+    fn suggest_next_steps(&self) -> Vec<String> {
+        let mut steps = Vec::new();
+
+        if !(200..300).contains(&self.http_status) {
+            steps.push(format!(
+                "Google answered HTTP {}; retry later.",
+                self.http_status
+            ));
+        }
+        if self.consent_page {
+            steps.push(
+                "Google served its cookie consent page instead of results: the consent cookie \
+                 was not accepted. Retry from another network or a residential IP."
+                    .to_string(),
+            );
+        }
+        if self.captcha_page {
+            steps.push(
+                "Google served a CAPTCHA (unusual traffic) page: lower the query rate and \
+                 wait before retrying."
+                    .to_string(),
+            );
+        }
+        if self.loading_placeholder {
+            steps.push(
+                "The page is a loading placeholder whose results are rendered by JavaScript; \
+                 this happens on sparse routes. Try nearby dates or airports."
+                    .to_string(),
+            );
+        }
+
+        if self.mobile_layout {
+            steps.push(
+                "Google served its mobile layout, whose markup the desktop selectors do not \
+                 match: check the client emulation and User-Agent."
+                    .to_string(),
+            );
+        }
+
+        let interstitial = self.consent_page || self.captcha_page || self.mobile_layout;
+        if !interstitial && self.matches("flight_card") == 0 {
+            steps.push(
+                "No flight cards were found: the route may have no flights on this date, \
+                 or Google changed its markup."
+                    .to_string(),
+            );
+        } else if !interstitial {
+            let stale: Vec<&str> = self
+                .selector_matches
+                .iter()
+                .filter(|m| m.count == 0)
+                .map(|m| m.selector.as_str())
+                .collect();
+            if !stale.is_empty() {
+                steps.push(format!(
+                    "Flight cards were found but these selectors matched nothing: {}. \
+                     The parser likely needs updating.",
+                    stale.join(", ")
+                ));
+            }
+        }
+        if self.response_bytes < MIN_RESULTS_PAGE_BYTES {
+            steps.push(format!(
+                "The response is only {} bytes, too small for a results page.",
+                self.response_bytes
+            ));
+        }
+        steps.push(format!(
+            "Open {} in a browser to compare with what Google shows.",
+            self.search_url
+        ));
+        steps
+    }
+}
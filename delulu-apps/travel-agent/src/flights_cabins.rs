@@ -0,0 +1,204 @@
+//!  Delulu Travel Agent
+//!
+//!  Copyright (C) 2026  Mamy Ratsimbazafy
+//!
+//!  This program is free software: you can redistribute it and/or modify
+//!  it under the terms of the GNU Affero General Public License as published by
+//!  the Free Software Foundation, either version 3 of the License, or
+//!  (at your option) any later version.
+//!
+//!  This program is distributed in the hope that it will be useful,
+//!  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//!  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//!  GNU Affero General Public License for more details.
+//!
+//!  You should have received a copy of the GNU Affero General Public License
+//!  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Cabin Class Comparison
+//!
+//! Searches the same route in every cabin class to show the price gap between
+//! them.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+
+use crate::flights_query_builder::{FlightSearchParams, Seat};
+use crate::flights_results_parser::FlightSearchResult;
+use crate::flights_search::GoogleFlightsClient;
+use crate::providers::FlightProvider;
+
+/// The cabin classes Google sells, cheapest first. One search each.
+pub const CABINS: [Seat; 4] = [
+    Seat::Economy,
+    Seat::PremiumEconomy,
+    Seat::Business,
+    Seat::First,
+];
+
+/// Outcome of searching the route in one cabin class.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub struct CabinFare {
+    pub cabin: Seat,
+    /// Lowest itinerary price, `None` if the search failed or had no prices.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<i32>,
+    pub search_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub struct CabinComparison {
+    /// Lowest price per cabin, for the cabins that returned one.
+    pub lowest_prices: BTreeMap<Seat, i32>,
+    pub cabins: Vec<CabinFare>,
+}
+
+/// Searches `base`'s route once per cabin in [`CABINS`] through `search`,
+/// ignoring `base.cabin_class`, and collects the lowest price of each.
+///
+/// Searches run one after another so they share the caller's rate limit.
+pub async fn compare_cabins_with<F, Fut>(
+    base: &FlightSearchParams,
+    search: F,
+) -> Result<CabinComparison>
+where
+    F: Fn(FlightSearchParams) -> Fut,
+    Fut: Future<Output = Result<FlightSearchResult>>,
+{
+    let mut cabins = Vec::with_capacity(CABINS.len());
+    for cabin in CABINS {
+        let params = FlightSearchParams {
+            cabin_class: cabin,
+            ..base.clone()
+        };
+        let search_url = params.get_search_url();
+
+        let fare = match search(params).await {
+            Ok(result) => CabinFare {
+                cabin,
+                price: result.lowest_price(),
+                search_url,
+                error: None,
+            },
+            Err(e) => CabinFare {
+                cabin,
+                price: None,
+                search_url,
+                error: Some(e.to_string()),
+            },
+        };
+        cabins.push(fare);
+    }
+
+    let lowest_prices = cabins
+        .iter()
+        .filter_map(|fare| Some((fare.cabin, fare.price?)))
+        .collect();
+    Ok(CabinComparison {
+        lowest_prices,
+        cabins,
+    })
+}
+
+/// Compares the lowest fares of `base`'s route, dates and passengers in
+/// every cabin class through `provider`.
+pub async fn compare_cabins(
+    provider: &dyn FlightProvider,
+    base: &FlightSearchParams,
+) -> Result<CabinComparison> {
+    base.validate()?;
+    compare_cabins_with(
+        base,
+        |params| async move { Ok(provider.search(&params).await?) },
+    )
+    .await
+}
+
+impl GoogleFlightsClient {
+    /// See [`compare_cabins`].
+    pub async fn compare_cabins(&self, base: &FlightSearchParams) -> Result<CabinComparison> {
+        compare_cabins(self, base).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flights_results_parser::Itinerary;
+    use chrono::NaiveDate;
+    use std::sync::Mutex;
+
+    fn result_with_price(params: FlightSearchParams, price: i32) -> FlightSearchResult {
+        FlightSearchResult {
+            search_params: params,
+            itineraries: vec![Itinerary {
+                id: "stub".to_string(),
+                flights: Vec::new(),
+                price: Some(price),
+                currency: Some("USD".to_string()),
+                original_price_usd: None,
+                duration_minutes: Some(330),
+                class: None,
+                stops: None,
+                layovers: Vec::new(),
+            }],
+            raw_response: String::new(),
+            warnings: Vec::new(),
+            price_trend: None,
+            currency: "USD".to_string(),
+            retry_count: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compare_cabins_one_query_per_cabin() {
+        let base = FlightSearchParams::builder(
+            "SFO".into(),
+            "JFK".into(),
+            NaiveDate::from_ymd_opt(2026, 5, 1).unwrap(),
+        )
+        .cabin_class(Seat::Business)
+        .build()
+        .unwrap();
+        let queries = Mutex::new(Vec::new());
+
+        let found = compare_cabins_with(&base, |params| {
+            queries.lock().unwrap().push(params.cabin_class);
+            async move {
+                match params.cabin_class {
+                    Seat::Economy => Ok(result_with_price(params, 450)),
+                    Seat::PremiumEconomy => Ok(result_with_price(params, 780)),
+                    Seat::Business => Ok(result_with_price(params, 2100)),
+                    _ => anyhow::bail!("No flights parsed from response"),
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(queries.into_inner().unwrap(), CABINS);
+        assert_eq!(
+            found.lowest_prices,
+            BTreeMap::from([
+                (Seat::Economy, 450),
+                (Seat::PremiumEconomy, 780),
+                (Seat::Business, 2100),
+            ])
+        );
+        let first = &found.cabins[3];
+        assert_eq!(first.cabin, Seat::First);
+        assert!(first.error.is_some());
+
+        // Keyed by cabin name in JSON
+        let json = serde_json::to_value(&found).unwrap();
+        assert_eq!(json["lowest_prices"]["premium_economy"], 780);
+    }
+}
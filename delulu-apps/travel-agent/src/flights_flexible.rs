@@ -0,0 +1,283 @@
+//!  Delulu Travel Agent
+//!
+//!  Copyright (C) 2026  Mamy Ratsimbazafy
+//!
+//!  This program is free software: you can redistribute it and/or modify
+//!  it under the terms of the GNU Affero General Public License as published by
+//!  the Free Software Foundation, either version 3 of the License, or
+//!  (at your option) any later version.
+//!
+//!  This program is distributed in the hope that it will be useful,
+//!  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//!  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//!  GNU Affero General Public License for more details.
+//!
+//!  You should have received a copy of the GNU Affero General Public License
+//!  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Flexible Date Flight Search
+//!
+//! Searches several candidate dates for the same route and picks the cheapest.
+
+use anyhow::{Result, ensure};
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+
+use crate::flights_query_builder::{FlightSearchParams, Trip};
+use crate::flights_results_parser::FlightSearchResult;
+use crate::flights_search::GoogleFlightsClient;
+use crate::providers::FlightProvider;
+
+/// A month has at most 5 Fridays, which also bounds the number of requests.
+pub const MAX_WEEKENDS: usize = 5;
+
+/// Outcome of searching one Friday-to-Sunday round trip.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub struct WeekendFare {
+    pub depart_date: String,
+    pub return_date: String,
+    /// Lowest itinerary price, `None` if the search failed or had no prices.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<i32>,
+    pub search_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub struct CheapestWeekend {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cheapest: Option<WeekendFare>,
+    pub weekends: Vec<WeekendFare>,
+}
+
+/// Friday departure and Sunday return for every Friday in `year`-`month`.
+pub fn weekends_in_month(year: i32, month: u32) -> Result<Vec<(NaiveDate, NaiveDate)>> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)
+        .ok_or_else(|| anyhow::anyhow!("Invalid month: {}-{:02}", year, month))?;
+    let days_to_friday =
+        (Weekday::Fri.num_days_from_monday() + 7 - first.weekday().num_days_from_monday()) % 7;
+
+    let weekends: Vec<_> = first
+        .checked_add_days(Days::new(days_to_friday as u64))
+        .into_iter()
+        .flat_map(|friday| friday.iter_weeks())
+        .take_while(|friday| friday.month() == month)
+        .take(MAX_WEEKENDS)
+        .map(|friday| (friday, friday + Days::new(2)))
+        .collect();
+    Ok(weekends)
+}
+
+/// Searches each weekend round trip of `base`'s route through `search` and
+/// returns every weekend fare along with the cheapest one.
+///
+/// Searches run one after another so they share the caller's rate limit.
+pub async fn cheapest_weekend_with<F, Fut>(
+    base: &FlightSearchParams,
+    weekends: &[(NaiveDate, NaiveDate)],
+    search: F,
+) -> Result<CheapestWeekend>
+where
+    F: Fn(FlightSearchParams) -> Fut,
+    Fut: Future<Output = Result<FlightSearchResult>>,
+{
+    ensure!(!weekends.is_empty(), "No weekends to search");
+    ensure!(
+        weekends.len() <= MAX_WEEKENDS,
+        "At most {} weekends can be searched",
+        MAX_WEEKENDS
+    );
+
+    let mut fares = Vec::with_capacity(weekends.len());
+    for (friday, sunday) in weekends {
+        let mut params = base.clone();
+        params.depart_date = friday.format("%Y-%m-%d").to_string();
+        params.return_date = Some(sunday.format("%Y-%m-%d").to_string());
+        params.trip_type = Trip::RoundTrip;
+        let search_url = params.get_search_url();
+
+        let fare = match search(params.clone()).await {
+            Ok(result) => WeekendFare {
+                depart_date: params.depart_date,
+                return_date: params.return_date.unwrap_or_default(),
+                price: result.lowest_price(),
+                search_url,
+                error: None,
+            },
+            Err(e) => WeekendFare {
+                depart_date: params.depart_date,
+                return_date: params.return_date.unwrap_or_default(),
+                price: None,
+                search_url,
+                error: Some(e.to_string()),
+            },
+        };
+        fares.push(fare);
+    }
+
+    let cheapest = fares
+        .iter()
+        .filter(|f| f.price.is_some())
+        .min_by_key(|f| f.price)
+        .cloned();
+    Ok(CheapestWeekend {
+        cheapest,
+        weekends: fares,
+    })
+}
+
+/// Finds the cheapest Friday-to-Sunday round trip in `year`-`month` for
+/// `base`'s route, cabin and passengers through `provider`. Weekends already
+/// past are skipped.
+pub async fn cheapest_weekend(
+    provider: &dyn FlightProvider,
+    base: &FlightSearchParams,
+    year: i32,
+    month: u32,
+) -> Result<CheapestWeekend> {
+    let today = chrono::Local::now().date_naive();
+    let weekends: Vec<_> = weekends_in_month(year, month)?
+        .into_iter()
+        .filter(|(friday, _)| *friday >= today)
+        .collect();
+    ensure!(
+        !weekends.is_empty(),
+        "No upcoming weekends in {}-{:02}",
+        year,
+        month
+    );
+
+    cheapest_weekend_with(base, &weekends, |params| async move {
+        Ok(provider.search(&params).await?)
+    })
+    .await
+}
+
+impl GoogleFlightsClient {
+    /// See [`cheapest_weekend`].
+    pub async fn cheapest_weekend(
+        &self,
+        base: &FlightSearchParams,
+        year: i32,
+        month: u32,
+    ) -> Result<CheapestWeekend> {
+        cheapest_weekend(self, base, year, month).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flights_results_parser::Itinerary;
+    use std::sync::Mutex;
+
+    fn result_with_price(params: FlightSearchParams, price: i32) -> FlightSearchResult {
+        FlightSearchResult {
+            search_params: params,
+            itineraries: vec![Itinerary {
+                id: "stub".to_string(),
+                flights: Vec::new(),
+                price: Some(price),
+                currency: Some("USD".to_string()),
+                original_price_usd: None,
+                duration_minutes: Some(330),
+                class: None,
+                stops: None,
+                layovers: Vec::new(),
+            }],
+            raw_response: String::new(),
+            warnings: Vec::new(),
+            price_trend: None,
+            currency: "USD".to_string(),
+            retry_count: None,
+        }
+    }
+
+    #[test]
+    fn test_weekends_in_month() {
+        // May 2026 has five Fridays: 1, 8, 15, 22, 29
+        let weekends = weekends_in_month(2026, 5).unwrap();
+        assert_eq!(weekends.len(), 5);
+        assert_eq!(weekends[0].0, NaiveDate::from_ymd_opt(2026, 5, 1).unwrap());
+        assert_eq!(weekends[0].1, NaiveDate::from_ymd_opt(2026, 5, 3).unwrap());
+        assert_eq!(weekends[4].1, NaiveDate::from_ymd_opt(2026, 5, 31).unwrap());
+
+        // February 2026 has four
+        assert_eq!(weekends_in_month(2026, 2).unwrap().len(), 4);
+        assert!(weekends_in_month(2026, 13).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cheapest_weekend_one_query_per_weekend() {
+        let base = FlightSearchParams::builder(
+            "SFO".into(),
+            "JFK".into(),
+            NaiveDate::from_ymd_opt(2026, 5, 1).unwrap(),
+        )
+        .build()
+        .unwrap();
+        let weekends = weekends_in_month(2026, 5).unwrap();
+        let prices = [420, 380, 510, 299, 460];
+        let queries = Mutex::new(Vec::new());
+
+        let found = cheapest_weekend_with(&base, &weekends, |params| {
+            queries.lock().unwrap().push(params.clone());
+            let day: usize = params.depart_date[8..].parse().unwrap();
+            let price = prices[(day - 1) / 7];
+            async move { Ok(result_with_price(params, price)) }
+        })
+        .await
+        .unwrap();
+
+        let queries = queries.into_inner().unwrap();
+        assert_eq!(queries.len(), 5);
+        for (query, (friday, sunday)) in queries.iter().zip(&weekends) {
+            assert_eq!(query.trip_type, Trip::RoundTrip);
+            assert_eq!(query.depart_date, friday.format("%Y-%m-%d").to_string());
+            assert_eq!(
+                query.return_date.as_deref(),
+                Some(sunday.format("%Y-%m-%d").to_string().as_str())
+            );
+        }
+
+        let cheapest = found.cheapest.unwrap();
+        assert_eq!(cheapest.depart_date, "2026-05-22");
+        assert_eq!(cheapest.return_date, "2026-05-24");
+        assert_eq!(cheapest.price, Some(299));
+        assert_eq!(found.weekends.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_cheapest_weekend_reports_failures() {
+        let base = FlightSearchParams::builder(
+            "SFO".into(),
+            "JFK".into(),
+            NaiveDate::from_ymd_opt(2026, 2, 6).unwrap(),
+        )
+        .build()
+        .unwrap();
+        let weekends = weekends_in_month(2026, 2).unwrap();
+
+        let found = cheapest_weekend_with(&base, &weekends, |params| async move {
+            if params.depart_date == "2026-02-13" {
+                Ok(result_with_price(params, 250))
+            } else {
+                anyhow::bail!("No flights parsed from response")
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(found.cheapest.unwrap().depart_date, "2026-02-13");
+        assert_eq!(
+            found.weekends.iter().filter(|w| w.error.is_some()).count(),
+            3
+        );
+    }
+}
@@ -0,0 +1,351 @@
+//!  Delulu Travel Agent
+//!
+//!  Copyright (C) 2026  Mamy Ratsimbazafy
+//!
+//!  This program is free software: you can redistribute it and/or modify
+//!  it under the terms of the GNU Affero General Public License as published by
+//!  the Free Software Foundation, either version 3 of the License, or
+//!  (at your option) any later version.
+//!
+//!  This program is distributed in the hope that it will be useful,
+//!  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//!  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//!  GNU Affero General Public License for more details.
+//!
+//!  You should have received a copy of the GNU Affero General Public License
+//!  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Flight Price Watch
+//!
+//! Polls a flight search on an interval and reports when its lowest price
+//! crosses a threshold or moves by more than a given percentage.
+
+use anyhow::Result;
+use delulu_query_queues::{Backoff, QueryQueue};
+use futures::{Stream, stream};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::flights_query_builder::FlightSearchParams;
+use crate::providers::FlightProvider;
+
+/// Time between two checks unless [`PriceWatch::interval`] is set.
+pub const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+/// Random extra wait, as a fraction of the interval, so that several watches
+/// started together do not poll Google in lockstep.
+const INTERVAL_JITTER: f64 = 0.1;
+
+/// Why a [`PriceUpdate`] was emitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum PriceEvent {
+    /// The lowest price went under the threshold, or was under it on the
+    /// first check.
+    DroppedBelow,
+    /// The lowest price went back to the threshold or above.
+    RoseAbove,
+    /// The lowest price moved by more than the watch's percentage since the
+    /// last update.
+    Changed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub struct PriceUpdate {
+    pub event: PriceEvent,
+    pub lowest_price: i32,
+    /// Price at the last update, or at the first check before any update.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_price: Option<i32>,
+    pub search_url: String,
+}
+
+/// Watches the lowest price of a flight search, see [`PriceWatch::spawn`].
+///
+/// # Examples
+///
+/// ```ignore
+/// let updates = PriceWatch::new(params, 300)
+///     .change_percent(10.0)
+///     .query_queue(client_queue)
+///     .spawn(Arc::new(client))?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct PriceWatch {
+    params: FlightSearchParams,
+    threshold: i32,
+    change_percent: Option<f64>,
+    interval: Duration,
+    max_checks: Option<usize>,
+    query_queue: QueryQueue,
+}
+
+impl PriceWatch {
+    /// Watch `params` for a lowest price under `threshold`.
+    pub fn new(params: FlightSearchParams, threshold: i32) -> Self {
+        Self {
+            params,
+            threshold,
+            change_percent: None,
+            interval: DEFAULT_WATCH_INTERVAL,
+            max_checks: None,
+            query_queue: QueryQueue::with_qps_limit(1),
+        }
+    }
+
+    /// Also report moves of more than `percent` percent since the last update.
+    pub fn change_percent(mut self, percent: f64) -> Self {
+        self.change_percent = Some(percent);
+        self
+    }
+
+    /// Time between two checks, plus up to 10% of jitter.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Stop after `checks` searches instead of watching until the stream is
+    /// dropped.
+    pub fn max_checks(mut self, checks: usize) -> Self {
+        self.max_checks = Some(checks);
+        self
+    }
+
+    /// Run searches through `queue`, e.g. the one of the client serving
+    /// interactive searches, so that the watch shares its rate limit.
+    pub fn query_queue(mut self, queue: QueryQueue) -> Self {
+        self.query_queue = queue;
+        self
+    }
+
+    /// Starts polling `provider` on a background task and returns the stream
+    /// of updates. The task ends after [`max_checks`](Self::max_checks) or
+    /// once the stream is dropped.
+    ///
+    /// A search failing past the queue's retries is logged and skipped, the
+    /// next check tries again.
+    pub fn spawn(
+        self,
+        provider: Arc<dyn FlightProvider>,
+    ) -> Result<impl Stream<Item = PriceUpdate> + Send + 'static> {
+        self.params.validate()?;
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            let mut delays = Backoff::default()
+                .initial_delay(self.interval)
+                .max_delay(self.interval.mul_f64(1.0 + INTERVAL_JITTER))
+                .jitter_factor(INTERVAL_JITTER)
+                .exponential(false);
+            let mut tracker = PriceTracker::new(self.threshold, self.change_percent);
+            let search_url = self.params.get_search_url();
+            let mut checks = 0;
+
+            while !tx.is_closed() {
+                let found = self
+                    .query_queue
+                    .with_retry(|| {
+                        let provider = provider.clone();
+                        let params = self.params.clone();
+                        async move { Ok(provider.search(&params).await?) }
+                    })
+                    .await;
+                match found.map(|result| result.lowest_price()) {
+                    Ok(Some(price)) => {
+                        if let Some((event, previous_price)) = tracker.observe(price) {
+                            let update = PriceUpdate {
+                                event,
+                                lowest_price: price,
+                                previous_price,
+                                search_url: search_url.clone(),
+                            };
+                            if tx.send(update).is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    Ok(None) => tracing::debug!("Price watch on {search_url} found no prices"),
+                    Err(e) => tracing::warn!("Price watch on {search_url} failed: {e:#}"),
+                }
+
+                checks += 1;
+                if self.max_checks.is_some_and(|max| checks >= max) {
+                    break;
+                }
+                let delay = delays.next().unwrap_or(self.interval);
+                tokio::time::sleep(delay).await;
+            }
+        });
+
+        Ok(stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|update| (update, rx))
+        }))
+    }
+}
+
+/// Decides which observed prices are worth an update.
+struct PriceTracker {
+    threshold: i32,
+    change_percent: Option<f64>,
+    /// Whether the last observed price was under the threshold.
+    below: Option<bool>,
+    /// Price of the last update, or of the first observation.
+    reference: Option<i32>,
+}
+
+impl PriceTracker {
+    fn new(threshold: i32, change_percent: Option<f64>) -> Self {
+        Self {
+            threshold,
+            change_percent,
+            below: None,
+            reference: None,
+        }
+    }
+
+    /// The event `price` triggers, if any, with the reference price it is
+    /// compared to.
+    fn observe(&mut self, price: i32) -> Option<(PriceEvent, Option<i32>)> {
+        let below = price < self.threshold;
+        let was_below = self.below.replace(below);
+        let previous = self.reference;
+
+        let event = if below && was_below != Some(true) {
+            Some(PriceEvent::DroppedBelow)
+        } else if !below && was_below == Some(true) {
+            Some(PriceEvent::RoseAbove)
+        } else if let (Some(percent), Some(reference)) = (self.change_percent, previous)
+            && reference > 0
+            && (price - reference).abs() as f64 * 100.0 > percent * reference as f64
+        {
+            Some(PriceEvent::Changed)
+        } else {
+            None
+        };
+
+        if event.is_some() || previous.is_none() {
+            self.reference = Some(price);
+        }
+        event.map(|event| (event, previous))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flights_results_parser::{FlightSearchResult, Itinerary};
+    use crate::providers::{FlightsError, SearchFuture};
+    use chrono::NaiveDate;
+    use futures::StreamExt;
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    /// Answers each search with the next of its prices.
+    struct StubProvider {
+        prices: Mutex<VecDeque<i32>>,
+    }
+
+    impl FlightProvider for StubProvider {
+        fn search<'a>(
+            &'a self,
+            params: &'a FlightSearchParams,
+        ) -> SearchFuture<'a, FlightSearchResult, FlightsError> {
+            let price = self.prices.lock().unwrap().pop_front();
+            Box::pin(async move {
+                let price = price.ok_or_else(|| anyhow::anyhow!("no more prices"))?;
+                Ok(FlightSearchResult {
+                    search_params: params.clone(),
+                    itineraries: vec![Itinerary {
+                        id: "stub".to_string(),
+                        flights: Vec::new(),
+                        price: Some(price),
+                        currency: Some("USD".to_string()),
+                        original_price_usd: None,
+                        duration_minutes: Some(330),
+                        class: None,
+                        stops: None,
+                        layovers: Vec::new(),
+                    }],
+                    raw_response: String::new(),
+                    warnings: Vec::new(),
+                    price_trend: None,
+                    currency: "USD".to_string(),
+                    retry_count: None,
+                })
+            })
+        }
+    }
+
+    async fn watch(watch: PriceWatch, prices: &[i32]) -> Vec<PriceUpdate> {
+        let provider = Arc::new(StubProvider {
+            prices: Mutex::new(prices.iter().copied().collect()),
+        });
+        watch
+            .interval(Duration::from_millis(1))
+            .max_checks(prices.len())
+            .query_queue(QueryQueue::with_concurrency_limit(1))
+            .spawn(provider)
+            .unwrap()
+            .collect()
+            .await
+    }
+
+    fn sfo_jfk() -> FlightSearchParams {
+        FlightSearchParams::builder(
+            "SFO".into(),
+            "JFK".into(),
+            NaiveDate::from_ymd_opt(2026, 4, 6).unwrap(),
+        )
+        .build()
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_threshold_crossing_fires_once() {
+        let params = sfo_jfk();
+        let updates = watch(
+            PriceWatch::new(params.clone(), 300),
+            &[420, 380, 310, 290, 260, 240],
+        )
+        .await;
+
+        assert_eq!(
+            updates,
+            [PriceUpdate {
+                event: PriceEvent::DroppedBelow,
+                lowest_price: 290,
+                previous_price: Some(420),
+                search_url: params.get_search_url(),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_percent_change_against_last_update() {
+        let updates = watch(
+            PriceWatch::new(sfo_jfk(), 200).change_percent(10.0),
+            &[400, 380, 350, 340, 400, 150, 250],
+        )
+        .await;
+
+        let events: Vec<(PriceEvent, i32, Option<i32>)> = updates
+            .iter()
+            .map(|u| (u.event, u.lowest_price, u.previous_price))
+            .collect();
+        assert_eq!(
+            events,
+            [
+                // Small moves add up against the last update
+                (PriceEvent::Changed, 350, Some(400)),
+                (PriceEvent::Changed, 400, Some(350)),
+                (PriceEvent::DroppedBelow, 150, Some(400)),
+                (PriceEvent::RoseAbove, 250, Some(150)),
+            ]
+        );
+    }
+}
@@ -24,18 +24,22 @@ pub mod proto {
     include!("proto/google_travel_flights.rs");
 }
 
-use anyhow::{Context, Result, ensure};
+use anyhow::{Context, Result, bail, ensure};
 use base64::{Engine as _, engine::general_purpose::STANDARD};
 use chrono::{Datelike, NaiveDate};
 use prost::Message;
 use serde::{Deserialize, Serialize};
 
+use crate::airports::airports_within;
+use crate::alliances::expand_alliances;
+use crate::decode_error::DecodeError;
+use crate::proto_debug::dump_protobuf;
 use proto::{
     Airport as AirportProto, FlightData, Passenger as PassengerProto, ProtoFlightSearch,
     Seat as SeatProto, Trip as TripProto,
 };
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
 #[repr(i32)]
 #[serde(rename_all = "snake_case")]
@@ -239,6 +243,17 @@ impl Passenger {
     }
 }
 
+/// One flight of a multi-city trip, after the first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub struct FlightLeg {
+    pub from_airport: String,
+    pub to_airport: String,
+    /// YYYY-MM-DD
+    pub date: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
@@ -253,8 +268,73 @@ pub struct FlightSearchParams {
     pub trip_type: Trip,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub max_stops: Option<i32>,
+    /// IATA airline codes, or alliance names ("STAR_ALLIANCE", "ONEWORLD",
+    /// "SKYTEAM") standing for all their members.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub preferred_airlines: Option<Vec<String>>,
+    /// Round trips only: `preferred_airlines` for the outbound leg, when it
+    /// differs from the return. Never set by [`Self::from_tfs`], which puts the
+    /// outbound leg's airlines in `preferred_airlines`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outbound_preferred_airlines: Option<Vec<String>>,
+    /// Round trips only: `max_stops` for the return leg, when it differs from
+    /// the outbound.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_max_stops: Option<i32>,
+    /// Round trips only: `preferred_airlines` for the return leg, when it
+    /// differs from the outbound.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_preferred_airlines: Option<Vec<String>>,
+    /// Also search from/to every airport within this many kilometers of the
+    /// origin and destination (e.g. SFO with 100 adds OAK and SJC).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_nearby_km: Option<u32>,
+    /// Multi-city trips only: the legs flown after `from_airport` ->
+    /// `to_airport` on `depart_date`, in order. `max_stops` and
+    /// `preferred_airlines` apply to every leg.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_legs: Vec<FlightLeg>,
+    /// Cap on total passengers enforced by [`Self::validate`]. Not sent to
+    /// Google.
+    #[serde(skip, default = "default_max_passengers")]
+    pub max_passengers: u32,
+}
+
+/// Host serving Google Flights.
+pub(crate) const GOOGLE_BASE_URL: &str = "https://www.google.com";
+
+/// A Google Flights search URL taken apart by [`FlightSearchParams::from_url`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DecodedFlightsUrl {
+    #[serde(flatten)]
+    pub params: FlightSearchParams,
+    /// `hl` parameter, the page language
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// `curr` parameter, the currency prices were shown in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+}
+
+/// `tfu` parameter of the search URL, as the Google Flights web app sends it.
+///
+/// An opaque blob of UI state. Google serves the results page with it; if it
+/// starts redirecting or serving another view instead, a new value captured
+/// from a browser can be set with [`crate::GoogleFlightsClient::tfu`].
+pub const DEFAULT_TFU: &str = "EgQIABABIgA";
+
+/// Google Flights accepts at most 9 passengers per search.
+pub const DEFAULT_MAX_PASSENGERS: u32 = 9;
+
+fn default_max_passengers() -> u32 {
+    DEFAULT_MAX_PASSENGERS
+}
+
+/// Whether `code` looks like an IATA airport or metropolitan area code, e.g.
+/// "JFK" or "NYC". Whether such an airport exists is left to Google.
+fn is_airport_code(code: &str) -> bool {
+    code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic())
 }
 
 impl FlightSearchParams {
@@ -264,6 +344,15 @@ impl FlightSearchParams {
             !self.to_airport.is_empty(),
             "Destination airport is required"
         );
+        for (role, code) in [
+            ("Origin", &self.from_airport),
+            ("Destination", &self.to_airport),
+        ] {
+            ensure!(
+                is_airport_code(code),
+                "{role} airport must be a 3-letter IATA code, got '{code}'"
+            );
+        }
 
         let adults: u32 = self
             .passengers
@@ -279,6 +368,8 @@ impl FlightSearchParams {
             .map(|(_, count)| count)
             .sum();
 
+        let total: u32 = self.passengers.iter().map(|(_, count)| count).sum();
+
         ensure!(adults > 0, "At least one adult is required");
         ensure!(
             infants_on_lap <= adults,
@@ -286,10 +377,51 @@ impl FlightSearchParams {
             infants_on_lap,
             adults
         );
+        ensure!(
+            total <= self.max_passengers,
+            "Maximum {} passengers per search, got {}",
+            self.max_passengers,
+            total
+        );
+
+        ensure!(
+            self.trip_type == Trip::RoundTrip
+                || (self.outbound_preferred_airlines.is_none()
+                    && self.return_max_stops.is_none()
+                    && self.return_preferred_airlines.is_none()),
+            "Per-leg filters (outbound_preferred_airlines, return_max_stops, return_preferred_airlines) only apply to round trips"
+        );
+        ensure!(
+            self.trip_type == Trip::MultiCity || self.extra_legs.is_empty(),
+            "Extra legs only apply to multi-city trips"
+        );
 
         let depart_date = NaiveDate::parse_from_str(&self.depart_date, "%Y-%m-%d")
             .context("Invalid depart date format")?;
 
+        let mut previous_date = depart_date;
+        for (i, leg) in self.extra_legs.iter().enumerate() {
+            // Leg 1 is from_airport -> to_airport
+            let n = i + 2;
+            ensure!(
+                !leg.from_airport.is_empty() && !leg.to_airport.is_empty(),
+                "Leg {n} needs an origin and a destination airport"
+            );
+            ensure!(
+                is_airport_code(&leg.from_airport) && is_airport_code(&leg.to_airport),
+                "Leg {n} airports must be 3-letter IATA codes, got '{}' and '{}'",
+                leg.from_airport,
+                leg.to_airport
+            );
+            let date = NaiveDate::parse_from_str(&leg.date, "%Y-%m-%d")
+                .with_context(|| format!("Invalid date format for leg {n}"))?;
+            ensure!(
+                date >= previous_date,
+                "Leg {n} departs before the previous leg"
+            );
+            previous_date = date;
+        }
+
         if let Some(return_date_str) = &self.return_date {
             let return_date = NaiveDate::parse_from_str(return_date_str, "%Y-%m-%d")
                 .context("Invalid return date format")?;
@@ -305,7 +437,26 @@ impl FlightSearchParams {
         Ok(())
     }
 
+    /// `airport` followed by the airports within `include_nearby_km` of it.
+    fn airport_alternatives(&self, airport: &str) -> Vec<AirportProto> {
+        let codes = match self.include_nearby_km {
+            Some(radius_km) => airports_within(airport, radius_km),
+            None => vec![airport.to_string()],
+        };
+        codes
+            .into_iter()
+            .map(|airport| AirportProto { airport })
+            .collect()
+    }
+
+    /// The `tfs` URL parameter: [`Self::tfs_bytes`] in base64.
     pub fn generate_tfs(&self) -> Result<String> {
+        Ok(STANDARD.encode(self.tfs_bytes()?))
+    }
+
+    /// Raw protobuf bytes behind the `tfs` URL parameter, e.g. to embed the
+    /// search in another format or inspect it with protobuf tooling.
+    pub fn tfs_bytes(&self) -> Result<Vec<u8>> {
         self.validate()?;
 
         let depart_checkin = NaiveDate::parse_from_str(&self.depart_date, "%Y-%m-%d")
@@ -324,6 +475,8 @@ impl FlightSearchParams {
             .map(|(ptype, count)| (*ptype as i32, *count))
             .collect();
 
+        let origins = self.airport_alternatives(&self.from_airport);
+        let destinations = self.airport_alternatives(&self.to_airport);
         let outbound = FlightData {
             date: format!(
                 "{:04}-{:02}-{:02}",
@@ -332,30 +485,43 @@ impl FlightSearchParams {
                 depart_checkin.day()
             ),
             max_stops: self.max_stops,
-            airlines: self.preferred_airlines.clone().unwrap_or_default(),
-            from_flight: Some(AirportProto {
-                airport: self.from_airport.clone(),
-            }),
-            to_flight: Some(AirportProto {
-                airport: self.to_airport.clone(),
-            }),
+            airlines: expand_alliances(
+                self.outbound_preferred_airlines
+                    .as_deref()
+                    .or(self.preferred_airlines.as_deref())
+                    .unwrap_or_default(),
+            ),
+            from_flight: origins.clone(),
+            to_flight: destinations.clone(),
         };
 
         let flight_data = match (&self.trip_type, return_checkin) {
             (Trip::RoundTrip, Some(ret)) => {
                 let return_flight = FlightData {
                     date: format!("{:04}-{:02}-{:02}", ret.year(), ret.month(), ret.day()),
-                    max_stops: self.max_stops,
-                    airlines: self.preferred_airlines.clone().unwrap_or_default(),
-                    from_flight: Some(AirportProto {
-                        airport: self.to_airport.clone(),
-                    }),
-                    to_flight: Some(AirportProto {
-                        airport: self.from_airport.clone(),
-                    }),
+                    max_stops: self.return_max_stops.or(self.max_stops),
+                    airlines: expand_alliances(
+                        self.return_preferred_airlines
+                            .as_deref()
+                            .or(self.preferred_airlines.as_deref())
+                            .unwrap_or_default(),
+                    ),
+                    from_flight: destinations,
+                    to_flight: origins,
                 };
                 vec![outbound, return_flight]
             }
+            (Trip::MultiCity, _) => std::iter::once(outbound)
+                .chain(self.extra_legs.iter().map(|leg| FlightData {
+                    date: leg.date.clone(),
+                    max_stops: self.max_stops,
+                    airlines: expand_alliances(
+                        self.preferred_airlines.as_deref().unwrap_or_default(),
+                    ),
+                    from_flight: self.airport_alternatives(&leg.from_airport),
+                    to_flight: self.airport_alternatives(&leg.to_airport),
+                }))
+                .collect(),
             _ => vec![outbound],
         };
 
@@ -374,23 +540,58 @@ impl FlightSearchParams {
         let mut bytes = Vec::new();
         info.encode(&mut bytes)
             .map_err(|e| anyhow::anyhow!("Failed to encode protobuf: {}", e))?;
-        Ok(STANDARD.encode(&bytes))
+        Ok(bytes)
     }
 
     pub fn get_search_url(&self) -> String {
+        self.get_search_url_at(GOOGLE_BASE_URL)
+    }
+
+    /// [`Self::get_search_url`] on another host, e.g. a local mock of Google.
+    pub fn get_search_url_at(&self, base_url: &str) -> String {
+        self.get_search_url_with_tfu(base_url, DEFAULT_TFU)
+    }
+
+    /// [`Self::get_search_url_at`] with `tfu` instead of [`DEFAULT_TFU`].
+    pub fn get_search_url_with_tfu(&self, base_url: &str, tfu: &str) -> String {
         let tfs_param = self.generate_tfs().expect("TFS encoding should work");
         format!(
-            "https://www.google.com/travel/flights/search?tfs={}&hl=en&curr=USD&tfu=EgQIABABIgA",
-            tfs_param
+            "{}/travel/flights/search?tfs={}&hl=en&curr=USD&tfu={}",
+            base_url.trim_end_matches('/'),
+            tfs_param,
+            urlencoding::encode(tfu)
         )
     }
 
-    pub fn from_tfs(tfs_base64: &str) -> Result<Self> {
+    /// Field-by-field dump of the protobuf inside a `tfs` parameter, including
+    /// fields [`Self::from_tfs`] ignores. For reverse-engineering.
+    pub fn decode_tfs_debug(tfs_base64: &str) -> Result<String> {
         let tfs_bytes = STANDARD
             .decode(tfs_base64)
             .map_err(|e| anyhow::anyhow!("Failed to decode base64: {}", e))?;
-        let info = proto::ProtoFlightSearch::decode(tfs_bytes.as_slice())
-            .context("Failed to decode protobuf")?;
+        dump_protobuf(&tfs_bytes).context("Failed to decode protobuf")
+    }
+
+    /// Decodes a `tfs` URL parameter back into search parameters.
+    ///
+    /// Searches listing several airports for one end of a leg, such as those
+    /// built with [`FlightSearchParamsBuilder::include_nearby_km`], cannot be
+    /// represented and are rejected rather than narrowed to the first one.
+    pub fn from_tfs(tfs_base64: &str) -> Result<Self, DecodeError> {
+        let tfs_bytes = STANDARD.decode(tfs_base64)?;
+        let info = proto::ProtoFlightSearch::decode(tfs_bytes.as_slice())?;
+
+        for (idx, flight) in info.data.iter().enumerate() {
+            for airports in [&flight.from_flight, &flight.to_flight] {
+                if airports.len() > 1 {
+                    let codes: Vec<_> = airports.iter().map(|a| a.airport.as_str()).collect();
+                    return Err(DecodeError::InvalidField {
+                        field: "airports",
+                        reason: format!("flight {idx} lists several airports: {codes:?}"),
+                    });
+                }
+            }
+        }
 
         let mut from_airport = String::new();
         let mut to_airport = String::new();
@@ -400,14 +601,16 @@ impl FlightSearchParams {
         let mut trip_type = Trip::RoundTrip;
         let mut max_stops: Option<i32> = None;
         let mut preferred_airlines: Option<Vec<String>> = None;
+        let mut return_max_stops: Option<i32> = None;
+        let mut return_preferred_airlines: Option<Vec<String>> = None;
 
         for (idx, flight) in info.data.iter().enumerate() {
-            if let Some(from) = &flight.from_flight {
+            if let Some(from) = flight.from_flight.first() {
                 if from_airport.is_empty() {
                     from_airport = from.airport.clone();
                 }
             }
-            if let Some(to) = &flight.to_flight {
+            if let Some(to) = flight.to_flight.first() {
                 if to_airport.is_empty() {
                     to_airport = to.airport.clone();
                 }
@@ -415,11 +618,13 @@ impl FlightSearchParams {
             if depart_date.is_empty() {
                 depart_date = flight.date.clone();
             }
-            if flight.max_stops.is_some() {
+            let airlines = Some(flight.airlines.clone()).filter(|a| !a.is_empty());
+            if idx == 0 {
                 max_stops = flight.max_stops;
-            }
-            if !flight.airlines.is_empty() {
-                preferred_airlines = Some(flight.airlines.clone());
+                preferred_airlines = airlines;
+            } else if idx == 1 {
+                return_max_stops = flight.max_stops;
+                return_preferred_airlines = airlines;
             }
             if info.data.len() > 1 && return_date.is_none() && idx > 0 && !flight.date.is_empty() {
                 return_date = Some(flight.date.clone());
@@ -455,29 +660,70 @@ impl FlightSearchParams {
             }
         }
 
-        ensure!(!from_airport.is_empty(), "from_airport is required");
-        ensure!(!to_airport.is_empty(), "to_airport is required");
-        let depart_parsed = NaiveDate::parse_from_str(&depart_date, "%Y-%m-%d")
-            .context("Invalid depart date format")?;
-        let return_parsed = return_date
-            .as_ref()
-            .map(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d"))
-            .transpose()
-            .context("Invalid return date format")?;
-        if let Some(ret) = return_parsed.as_ref() {
-            ensure!(
-                ret >= &depart_parsed,
-                "return_date ({}) must be on or after depart_date ({})",
-                ret,
-                depart_parsed
-            );
+        let mut extra_legs = Vec::new();
+        if trip_type == Trip::MultiCity {
+            // Every flight after the first is a leg, not a return
+            return_date = None;
+            for flight in info.data.iter().skip(1) {
+                let airport = |airports: &[AirportProto]| {
+                    airports
+                        .first()
+                        .map(|a| a.airport.clone())
+                        .unwrap_or_default()
+                };
+                extra_legs.push(FlightLeg {
+                    from_airport: airport(&flight.from_flight),
+                    to_airport: airport(&flight.to_flight),
+                    date: flight.date.clone(),
+                });
+            }
+        }
+
+        // Return-leg filters are only kept when they differ from the outbound
+        if trip_type != Trip::RoundTrip || return_max_stops == max_stops {
+            return_max_stops = None;
+        }
+        if trip_type != Trip::RoundTrip || return_preferred_airlines == preferred_airlines {
+            return_preferred_airlines = None;
+        }
+
+        if from_airport.is_empty() {
+            return Err(DecodeError::MissingRequiredField("from_airport"));
+        }
+        if to_airport.is_empty() {
+            return Err(DecodeError::MissingRequiredField("to_airport"));
+        }
+        if depart_date.is_empty() {
+            return Err(DecodeError::MissingRequiredField("depart_date"));
+        }
+        let parse_date = |field, date: &str| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| DecodeError::InvalidField {
+                field,
+                reason: format!("{date:?}: {e}"),
+            })
+        };
+        let depart_parsed = parse_date("depart_date", &depart_date)?;
+        if let Some(ret) = return_date.as_deref() {
+            let ret = parse_date("return_date", ret)?;
+            if ret < depart_parsed {
+                return Err(DecodeError::InvalidField {
+                    field: "return_date",
+                    reason: format!("{ret} is before depart_date ({depart_parsed})"),
+                });
+            }
+        }
+        for leg in &extra_legs {
+            if leg.from_airport.is_empty() || leg.to_airport.is_empty() {
+                return Err(DecodeError::MissingRequiredField("extra_legs airport"));
+            }
+            parse_date("extra_legs date", &leg.date)?;
+        }
+        if infants_on_lap > adults {
+            return Err(DecodeError::InvalidField {
+                field: "passengers",
+                reason: format!("more infants on lap ({infants_on_lap}) than adults ({adults})"),
+            });
         }
-        ensure!(
-            infants_on_lap <= adults,
-            "Cannot have more infants on lap ({}) than adults ({})",
-            infants_on_lap,
-            adults
-        );
 
         Ok(FlightSearchParams {
             from_airport,
@@ -504,9 +750,95 @@ impl FlightSearchParams {
             trip_type,
             max_stops,
             preferred_airlines,
+            outbound_preferred_airlines: None,
+            return_max_stops,
+            return_preferred_airlines,
+            include_nearby_km: None,
+            extra_legs,
+            max_passengers: DEFAULT_MAX_PASSENGERS,
+        })
+    }
+
+    /// [`Self::from_tfs`] on the `tfs` parameter of a full search URL, also
+    /// recovering its `hl` and `curr` parameters.
+    pub fn from_url(url: &str) -> Result<DecodedFlightsUrl, DecodeError> {
+        let query = url
+            .split_once('?')
+            .map_or("", |(_, query)| query)
+            .split('#')
+            .next()
+            .unwrap_or_default();
+        let param = |name: &str| {
+            query
+                .split('&')
+                .filter_map(|kv| kv.split_once('='))
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| {
+                    urlencoding::decode(value)
+                        .map(|v| v.into_owned())
+                        .map_err(|e| DecodeError::InvalidField {
+                            field: "url",
+                            reason: format!("{name} parameter: {e}"),
+                        })
+                })
+                .transpose()
+        };
+        let tfs = param("tfs")?.ok_or(DecodeError::MissingRequiredField("tfs"))?;
+        Ok(DecodedFlightsUrl {
+            params: Self::from_tfs(&tfs)?,
+            language: param("hl")?.filter(|v| !v.is_empty()),
+            currency: param("curr")?.filter(|v| !v.is_empty()),
         })
     }
 
+    /// Parses a compact route such as `"SFO-JFK 2026-04-06"`: airports joined
+    /// by `-` or `>`, then the dates.
+    ///
+    /// - Two airports and one date is a one-way trip.
+    /// - Two airports and two dates, or `"SFO>JFK>SFO"` and two dates, is a
+    ///   round trip.
+    /// - Any other chain of airports is a multi-city trip, with one date per leg.
+    ///
+    /// Other settings are the [builder](Self::builder)'s defaults.
+    pub fn parse_route(route: &str) -> Result<Self> {
+        let mut tokens = route.split_whitespace();
+        let path = tokens.next().context("Empty route")?;
+        let airports: Vec<String> = path
+            .split(['-', '>'])
+            .map(|code| code.trim().to_ascii_uppercase())
+            .collect();
+        ensure!(
+            airports.len() >= 2 && airports.iter().all(|code| !code.is_empty()),
+            "Invalid route '{path}', expected airports joined by '-' or '>', e.g. SFO-JFK"
+        );
+        let dates = tokens
+            .map(|date| {
+                NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                    .with_context(|| format!("Invalid date '{date}' in route, expected YYYY-MM-DD"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        ensure!(!dates.is_empty(), "Route '{route}' has no date");
+
+        let legs = airports.len() - 1;
+        let returns_home = legs == 2 && airports[0] == airports[2];
+        let builder = Self::builder(airports[0].clone(), airports[1].clone(), dates[0]);
+        match (legs, dates.len()) {
+            (1, 1) => builder.trip_type(Trip::OneWay).build(),
+            (1, 2) => builder.return_date(dates[1]).build(),
+            (2, 2) if returns_home => builder.return_date(dates[1]).build(),
+            (1, n) => bail!("Route '{path}' takes one or two dates, got {n}"),
+            (legs, n) if n == legs => airports[1..]
+                .windows(2)
+                .zip(&dates[1..])
+                .fold(
+                    builder.trip_type(Trip::MultiCity),
+                    |builder, (pair, date)| builder.leg(pair[0].clone(), pair[1].clone(), *date),
+                )
+                .build(),
+            (legs, n) => bail!("Multi-city route '{path}' has {legs} legs but {n} date(s)"),
+        }
+    }
+
     pub fn builder(
         from_airport: String,
         to_airport: String,
@@ -522,6 +854,13 @@ impl FlightSearchParams {
             trip_type: Trip::RoundTrip,
             max_stops: None,
             preferred_airlines: None,
+            outbound_preferred_airlines: None,
+            return_max_stops: None,
+            return_preferred_airlines: None,
+            include_nearby_km: None,
+            extra_legs: Vec::new(),
+            max_passengers: DEFAULT_MAX_PASSENGERS,
+            max_advance_days: None,
         }
     }
 }
@@ -537,6 +876,13 @@ pub struct FlightSearchParamsBuilder {
     trip_type: Trip,
     max_stops: Option<i32>,
     preferred_airlines: Option<Vec<String>>,
+    outbound_preferred_airlines: Option<Vec<String>>,
+    return_max_stops: Option<i32>,
+    return_preferred_airlines: Option<Vec<String>>,
+    include_nearby_km: Option<u32>,
+    extra_legs: Vec<(String, String, NaiveDate)>,
+    max_passengers: u32,
+    max_advance_days: Option<u32>,
 }
 
 impl FlightSearchParamsBuilder {
@@ -550,6 +896,37 @@ impl FlightSearchParamsBuilder {
         self
     }
 
+    /// Number of adults (one by default).
+    pub fn adults(self, count: u32) -> Self {
+        self.passenger_count(Passenger::Adult, count)
+    }
+
+    pub fn children(self, count: u32) -> Self {
+        self.passenger_count(Passenger::Child, count)
+    }
+
+    pub fn infants_on_lap(self, count: u32) -> Self {
+        self.passenger_count(Passenger::InfantOnLap, count)
+    }
+
+    pub fn infants_in_seat(self, count: u32) -> Self {
+        self.passenger_count(Passenger::InfantInSeat, count)
+    }
+
+    /// Sets how many passengers of one type travel, leaving the others as
+    /// they are. A count of 0 removes the type.
+    fn passenger_count(mut self, passenger: Passenger, count: u32) -> Self {
+        match self.passengers.iter().position(|(p, _)| *p == passenger) {
+            Some(i) if count == 0 => {
+                self.passengers.remove(i);
+            }
+            Some(i) => self.passengers[i].1 = count,
+            None if count > 0 => self.passengers.push((passenger, count)),
+            None => {}
+        }
+        self
+    }
+
     pub fn max_stops(mut self, max_stops: Option<i32>) -> Self {
         self.max_stops = max_stops;
         self
@@ -560,6 +937,43 @@ impl FlightSearchParamsBuilder {
         self
     }
 
+    /// Overrides `max_stops` on the return leg of a round trip.
+    pub fn return_max_stops(mut self, max_stops: Option<i32>) -> Self {
+        self.return_max_stops = max_stops;
+        self
+    }
+
+    /// Overrides `preferred_airlines` on the outbound leg of a round trip.
+    pub fn outbound_preferred_airlines(mut self, preferred_airlines: Option<Vec<String>>) -> Self {
+        self.outbound_preferred_airlines = preferred_airlines;
+        self
+    }
+
+    /// Overrides `preferred_airlines` on the return leg of a round trip.
+    pub fn return_preferred_airlines(mut self, preferred_airlines: Option<Vec<String>>) -> Self {
+        self.return_preferred_airlines = preferred_airlines;
+        self
+    }
+
+    /// Also search airports within `radius_km` of the origin and destination.
+    pub fn include_nearby_km(mut self, radius_km: Option<u32>) -> Self {
+        self.include_nearby_km = radius_km;
+        self
+    }
+
+    /// Cap on total passengers (default [`DEFAULT_MAX_PASSENGERS`]).
+    pub fn max_passengers(mut self, max_passengers: u32) -> Self {
+        self.max_passengers = max_passengers;
+        self
+    }
+
+    /// Reject departure or return dates more than `days` days from today.
+    /// Unbounded (`None`) by default.
+    pub fn max_advance_days(mut self, days: Option<u32>) -> Self {
+        self.max_advance_days = days;
+        self
+    }
+
     pub fn return_date(mut self, return_date: NaiveDate) -> Self {
         self.return_date = Some(return_date);
         self
@@ -570,7 +984,29 @@ impl FlightSearchParamsBuilder {
         self
     }
 
+    /// Appends a leg to a multi-city trip, after the first one (and any
+    /// added before). Only valid with [`Trip::MultiCity`].
+    pub fn leg(mut self, from_airport: String, to_airport: String, date: NaiveDate) -> Self {
+        self.extra_legs.push((from_airport, to_airport, date));
+        self
+    }
+
     pub fn build(self) -> Result<FlightSearchParams> {
+        if let Some(days) = self.max_advance_days {
+            let latest = chrono::Local::now().date_naive() + chrono::Days::new(days as u64);
+            let last_date = self
+                .extra_legs
+                .iter()
+                .map(|(_, _, date)| *date)
+                .chain(self.return_date)
+                .fold(self.depart_date, NaiveDate::max);
+            ensure!(
+                last_date <= latest,
+                "Dates more than {} days ahead are not allowed, got {}",
+                days,
+                last_date
+            );
+        }
         let max_stops = self.max_stops.filter(|&v| v != 0);
         let params = FlightSearchParams {
             from_airport: self.from_airport,
@@ -582,6 +1018,20 @@ impl FlightSearchParamsBuilder {
             trip_type: self.trip_type,
             max_stops,
             preferred_airlines: self.preferred_airlines,
+            outbound_preferred_airlines: self.outbound_preferred_airlines,
+            return_max_stops: self.return_max_stops.filter(|&v| v != 0),
+            return_preferred_airlines: self.return_preferred_airlines,
+            include_nearby_km: self.include_nearby_km,
+            extra_legs: self
+                .extra_legs
+                .into_iter()
+                .map(|(from_airport, to_airport, date)| FlightLeg {
+                    from_airport,
+                    to_airport,
+                    date: date.format("%Y-%m-%d").to_string(),
+                })
+                .collect(),
+            max_passengers: self.max_passengers,
         };
         params.validate()?;
         Ok(params)
@@ -591,6 +1041,7 @@ impl FlightSearchParamsBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::alliances::Alliance;
 
     #[test]
     fn test_get_search_url() {
@@ -606,6 +1057,12 @@ mod tests {
 
         let url = params.get_search_url();
         assert!(url.starts_with("https://www.google.com/travel/flights/search?tfs="));
+        assert!(url.ends_with(&format!("&tfu={DEFAULT_TFU}")));
+
+        let url = params.get_search_url_with_tfu("http://localhost:8080/", "CgIIAQ==");
+        assert!(url.starts_with("http://localhost:8080/travel/flights/search?tfs="));
+        assert!(url.ends_with("&tfu=CgIIAQ%3D%3D"));
+        assert!(!url.contains(DEFAULT_TFU));
     }
 
     #[test]
@@ -665,6 +1122,52 @@ mod tests {
         assert!(params.is_err());
     }
 
+    #[test]
+    fn test_passenger_count_setters() {
+        let builder = || {
+            FlightSearchParams::builder(
+                "LAX".to_string(),
+                "ORD".to_string(),
+                NaiveDate::from_ymd_opt(2025, 7, 20).unwrap(),
+            )
+        };
+
+        let explicit = builder()
+            .passengers(vec![
+                (Passenger::Adult, 2),
+                (Passenger::Child, 1),
+                (Passenger::InfantOnLap, 1),
+                (Passenger::InfantInSeat, 1),
+            ])
+            .build()
+            .unwrap();
+        let counted = builder()
+            .adults(2)
+            .children(1)
+            .infants_on_lap(1)
+            .infants_in_seat(1)
+            .build()
+            .unwrap();
+        assert_eq!(counted.passengers, explicit.passengers);
+        assert_eq!(
+            counted.generate_tfs().unwrap(),
+            explicit.generate_tfs().unwrap()
+        );
+
+        // Order of the calls does not matter, and 0 removes a type.
+        let reordered = builder().children(1).adults(2).build().unwrap();
+        assert_eq!(
+            reordered.passengers,
+            [(Passenger::Adult, 2), (Passenger::Child, 1)]
+        );
+        let removed = builder().children(2).children(0).build().unwrap();
+        assert_eq!(removed.passengers, [(Passenger::Adult, 1)]);
+
+        // Validation applies as with the explicit form.
+        assert!(builder().adults(1).infants_on_lap(2).build().is_err());
+        assert!(builder().adults(0).children(1).build().is_err());
+    }
+
     #[test]
     fn test_tfs_roundtrip() {
         let original = FlightSearchParams::builder(
@@ -689,4 +1192,408 @@ mod tests {
         assert_eq!(original.cabin_class, decoded.cabin_class);
         assert_eq!(original.trip_type, decoded.trip_type);
     }
+
+    #[test]
+    fn test_tfs_roundtrip_multi_city() {
+        let date = |day| NaiveDate::from_ymd_opt(2025, 7, day).unwrap();
+        let original = FlightSearchParams::builder("LAX".to_string(), "ORD".to_string(), date(20))
+            .trip_type(Trip::MultiCity)
+            .leg("ORD".to_string(), "JFK".to_string(), date(23))
+            .leg("JFK".to_string(), "LAX".to_string(), date(27))
+            .build()
+            .unwrap();
+
+        let decoded = FlightSearchParams::from_tfs(&original.generate_tfs().unwrap()).unwrap();
+        assert_eq!(decoded.trip_type, Trip::MultiCity);
+        assert_eq!(decoded.from_airport, "LAX");
+        assert_eq!(decoded.to_airport, "ORD");
+        assert_eq!(decoded.return_date, None);
+        assert_eq!(decoded.extra_legs, original.extra_legs);
+        assert_eq!(decoded.extra_legs[1].date, "2025-07-27");
+
+        let builder = || {
+            FlightSearchParams::builder("LAX".to_string(), "ORD".to_string(), date(20))
+                .trip_type(Trip::MultiCity)
+        };
+        assert!(
+            builder()
+                .leg("ORD".to_string(), "JFK".to_string(), date(19))
+                .build()
+                .is_err(),
+            "legs must be in date order"
+        );
+        assert!(
+            builder()
+                .trip_type(Trip::OneWay)
+                .leg("ORD".to_string(), "JFK".to_string(), date(23))
+                .build()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_from_url_recovers_language_and_currency() {
+        let params = FlightSearchParams::builder(
+            "CDG".to_string(),
+            "FCO".to_string(),
+            NaiveDate::from_ymd_opt(2025, 9, 3).unwrap(),
+        )
+        .trip_type(Trip::OneWay)
+        .build()
+        .unwrap();
+        let tfs = urlencoding::encode(&params.generate_tfs().unwrap()).into_owned();
+        let url =
+            format!("https://www.google.com/travel/flights/search?tfs={tfs}&hl=fr-FR&curr=EUR#top");
+
+        let decoded = FlightSearchParams::from_url(&url).unwrap();
+        assert_eq!(decoded.params.from_airport, "CDG");
+        assert_eq!(decoded.params.to_airport, "FCO");
+        assert_eq!(decoded.language.as_deref(), Some("fr-FR"));
+        assert_eq!(decoded.currency.as_deref(), Some("EUR"));
+
+        let bare = format!("https://www.google.com/travel/flights/search?tfs={tfs}");
+        let decoded = FlightSearchParams::from_url(&bare).unwrap();
+        assert_eq!((decoded.language, decoded.currency), (None, None));
+
+        assert!(matches!(
+            FlightSearchParams::from_url("https://www.google.com/travel/flights?hl=en"),
+            Err(DecodeError::MissingRequiredField("tfs"))
+        ));
+    }
+
+    #[test]
+    fn test_tfs_roundtrip_per_direction_airlines() {
+        let original = FlightSearchParams::builder(
+            "JFK".to_string(),
+            "LHR".to_string(),
+            NaiveDate::from_ymd_opt(2025, 7, 20).unwrap(),
+        )
+        .return_date(NaiveDate::from_ymd_opt(2025, 7, 25).unwrap())
+        .preferred_airlines(Some(vec!["DL".to_string()]))
+        .outbound_preferred_airlines(Some(vec!["BA".to_string()]))
+        .return_preferred_airlines(Some(vec!["VS".to_string()]))
+        .trip_type(Trip::RoundTrip)
+        .build()
+        .unwrap();
+
+        let tfs = original.generate_tfs().unwrap();
+        let proto = ProtoFlightSearch::decode(STANDARD.decode(&tfs).unwrap().as_slice()).unwrap();
+        assert_eq!(proto.data[0].airlines, vec!["BA".to_string()]);
+        assert_eq!(proto.data[1].airlines, vec!["VS".to_string()]);
+
+        let decoded = FlightSearchParams::from_tfs(&tfs).unwrap();
+        assert_eq!(decoded.preferred_airlines, Some(vec!["BA".to_string()]));
+        assert_eq!(decoded.outbound_preferred_airlines, None);
+        assert_eq!(
+            decoded.return_preferred_airlines,
+            Some(vec!["VS".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_tfs_roundtrip_return_leg_filters() {
+        let original = FlightSearchParams::builder(
+            "LAX".to_string(),
+            "ORD".to_string(),
+            NaiveDate::from_ymd_opt(2025, 7, 20).unwrap(),
+        )
+        .return_date(NaiveDate::from_ymd_opt(2025, 7, 25).unwrap())
+        .max_stops(Some(1))
+        .return_max_stops(Some(2))
+        .preferred_airlines(Some(vec!["UA".to_string()]))
+        .trip_type(Trip::RoundTrip)
+        .build()
+        .unwrap();
+
+        let tfs = original.generate_tfs().unwrap();
+        let proto = ProtoFlightSearch::decode(STANDARD.decode(&tfs).unwrap().as_slice()).unwrap();
+        assert_eq!(proto.data[0].max_stops, Some(1));
+        assert_eq!(proto.data[1].max_stops, Some(2));
+        assert_eq!(proto.data[1].airlines, vec!["UA".to_string()]);
+
+        let decoded = FlightSearchParams::from_tfs(&tfs).unwrap();
+        assert_eq!(decoded.max_stops, Some(1));
+        assert_eq!(decoded.return_max_stops, Some(2));
+        assert_eq!(decoded.preferred_airlines, Some(vec!["UA".to_string()]));
+        assert_eq!(decoded.return_preferred_airlines, None);
+    }
+
+    #[test]
+    fn test_include_nearby_airports() {
+        let builder = FlightSearchParams::builder(
+            "SFO".to_string(),
+            "JFK".to_string(),
+            NaiveDate::from_ymd_opt(2025, 9, 10).unwrap(),
+        )
+        .return_date(NaiveDate::from_ymd_opt(2025, 9, 17).unwrap());
+        let airports = |list: &[AirportProto]| -> Vec<String> {
+            list.iter().map(|a| a.airport.clone()).collect()
+        };
+
+        let plain = builder.clone().build().unwrap();
+        let tfs = plain.generate_tfs().unwrap();
+        let proto = ProtoFlightSearch::decode(STANDARD.decode(&tfs).unwrap().as_slice()).unwrap();
+        assert_eq!(airports(&proto.data[0].from_flight), ["SFO"]);
+        assert_eq!(airports(&proto.data[0].to_flight), ["JFK"]);
+
+        let nearby = builder.include_nearby_km(Some(100)).build().unwrap();
+        let tfs = nearby.generate_tfs().unwrap();
+        let proto = ProtoFlightSearch::decode(STANDARD.decode(&tfs).unwrap().as_slice()).unwrap();
+        assert_eq!(airports(&proto.data[0].from_flight), ["SFO", "OAK", "SJC"]);
+        assert_eq!(airports(&proto.data[0].to_flight), ["JFK", "LGA", "EWR"]);
+        // The return leg flies back to any of the origin airports.
+        assert_eq!(airports(&proto.data[1].to_flight), ["SFO", "OAK", "SJC"]);
+
+        assert!(matches!(
+            FlightSearchParams::from_tfs(&tfs),
+            Err(DecodeError::InvalidField {
+                field: "airports",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_decode_tfs_debug() {
+        let tfs = FlightSearchParams::builder(
+            "SFO".to_string(),
+            "JFK".to_string(),
+            NaiveDate::from_ymd_opt(2025, 9, 10).unwrap(),
+        )
+        .trip_type(Trip::OneWay)
+        .max_stops(Some(1))
+        .build()
+        .unwrap()
+        .generate_tfs()
+        .unwrap();
+
+        let dump = FlightSearchParams::decode_tfs_debug(&tfs).unwrap();
+        assert!(dump.contains("  2: \"2025-09-10\"\n"), "{dump}");
+        assert!(dump.contains("  5: 1\n"), "{dump}");
+        assert!(dump.contains("  13: {\n    2: \"SFO\"\n  }\n"), "{dump}");
+        assert!(dump.contains("  14: {\n    2: \"JFK\"\n  }\n"), "{dump}");
+        assert!(FlightSearchParams::decode_tfs_debug("not base64!").is_err());
+    }
+
+    #[test]
+    fn test_tfs_bytes_round_trip() {
+        let params = FlightSearchParams::builder(
+            "SFO".to_string(),
+            "JFK".to_string(),
+            NaiveDate::from_ymd_opt(2025, 9, 10).unwrap(),
+        )
+        .return_date(NaiveDate::from_ymd_opt(2025, 9, 17).unwrap())
+        .build()
+        .unwrap();
+
+        let bytes = params.tfs_bytes().unwrap();
+        let tfs = params.generate_tfs().unwrap();
+        assert_eq!(STANDARD.decode(&tfs).unwrap(), bytes);
+        let decoded = proto::ProtoFlightSearch::decode(bytes.as_slice()).unwrap();
+        assert_eq!(decoded.data.len(), 2);
+        assert_eq!(decoded.data[0].from_flight[0].airport, "SFO");
+    }
+
+    #[test]
+    fn test_from_tfs_typed_errors() {
+        assert!(matches!(
+            FlightSearchParams::from_tfs("not base64!"),
+            Err(DecodeError::InvalidBase64(_))
+        ));
+        let not_protobuf = STANDARD.encode("hello world");
+        assert!(matches!(
+            FlightSearchParams::from_tfs(&not_protobuf),
+            Err(DecodeError::InvalidProtobuf(_))
+        ));
+
+        let tfs = FlightSearchParams::builder(
+            "SFO".to_string(),
+            "JFK".to_string(),
+            NaiveDate::from_ymd_opt(2025, 9, 10).unwrap(),
+        )
+        .trip_type(Trip::OneWay)
+        .build()
+        .unwrap()
+        .generate_tfs()
+        .unwrap();
+        let bytes = STANDARD.decode(&tfs).unwrap();
+        let truncated = STANDARD.encode(&bytes[..bytes.len() / 2]);
+        assert!(matches!(
+            FlightSearchParams::from_tfs(&truncated),
+            Err(DecodeError::InvalidProtobuf(_))
+        ));
+        // An empty message is valid protobuf, but not a search.
+        assert!(matches!(
+            FlightSearchParams::from_tfs(""),
+            Err(DecodeError::MissingRequiredField("from_airport"))
+        ));
+    }
+
+    #[test]
+    fn test_tfs_roundtrip_star_alliance() {
+        let original = FlightSearchParams::builder(
+            "SFO".to_string(),
+            "FRA".to_string(),
+            NaiveDate::from_ymd_opt(2025, 9, 10).unwrap(),
+        )
+        .preferred_airlines(Some(vec!["STAR_ALLIANCE".to_string()]))
+        .trip_type(Trip::OneWay)
+        .build()
+        .unwrap();
+
+        let tfs = original.generate_tfs().unwrap();
+        let proto = ProtoFlightSearch::decode(STANDARD.decode(&tfs).unwrap().as_slice()).unwrap();
+        let members = Alliance::StarAlliance.members();
+        assert_eq!(proto.data[0].airlines, members);
+        assert!(proto.data[0].airlines.iter().any(|a| a == "UA"));
+        assert!(proto.data[0].airlines.iter().any(|a| a == "LH"));
+
+        let decoded = FlightSearchParams::from_tfs(&tfs).unwrap();
+        assert_eq!(decoded.preferred_airlines.unwrap(), members);
+    }
+
+    #[test]
+    fn test_return_leg_filters_require_round_trip() {
+        let params = FlightSearchParams::builder(
+            "LAX".to_string(),
+            "ORD".to_string(),
+            NaiveDate::from_ymd_opt(2025, 7, 20).unwrap(),
+        )
+        .return_max_stops(Some(1))
+        .trip_type(Trip::OneWay)
+        .build();
+
+        assert!(params.is_err());
+
+        let params = FlightSearchParams::builder(
+            "LAX".to_string(),
+            "ORD".to_string(),
+            NaiveDate::from_ymd_opt(2025, 7, 20).unwrap(),
+        )
+        .outbound_preferred_airlines(Some(vec!["AA".to_string()]))
+        .trip_type(Trip::OneWay)
+        .build();
+
+        assert!(params.is_err());
+    }
+
+    #[test]
+    fn test_passenger_cap() {
+        let with = |passengers: Vec<(Passenger, u32)>| {
+            FlightSearchParams::builder(
+                "LAX".to_string(),
+                "ORD".to_string(),
+                NaiveDate::from_ymd_opt(2025, 7, 20).unwrap(),
+            )
+            .trip_type(Trip::OneWay)
+            .passengers(passengers)
+            .build()
+        };
+
+        assert!(with(vec![(Passenger::Adult, 9)]).is_ok());
+        let err = with(vec![(Passenger::Adult, 10)]).unwrap_err();
+        assert_eq!(err.to_string(), "Maximum 9 passengers per search, got 10");
+
+        let err = with(vec![
+            (Passenger::Adult, 4),
+            (Passenger::Child, 4),
+            (Passenger::InfantOnLap, 2),
+        ])
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Maximum 9 passengers per search, got 10");
+
+        let raised = FlightSearchParams::builder(
+            "LAX".to_string(),
+            "ORD".to_string(),
+            NaiveDate::from_ymd_opt(2025, 7, 20).unwrap(),
+        )
+        .trip_type(Trip::OneWay)
+        .passengers(vec![(Passenger::Adult, 12)])
+        .max_passengers(12)
+        .build();
+        assert!(raised.is_ok());
+    }
+
+    #[test]
+    fn test_parse_route() {
+        let date = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+
+        let one_way = FlightSearchParams::parse_route("SFO-JFK 2026-04-06").unwrap();
+        let expected = FlightSearchParams::builder("SFO".into(), "JFK".into(), date("2026-04-06"))
+            .trip_type(Trip::OneWay)
+            .build()
+            .unwrap();
+        assert_eq!(one_way.get_search_url(), expected.get_search_url());
+        assert_eq!(one_way.trip_type, Trip::OneWay);
+
+        let round_trip =
+            FlightSearchParams::parse_route("sfo>jfk>sfo 2026-04-06 2026-04-13").unwrap();
+        assert_eq!(round_trip.trip_type, Trip::RoundTrip);
+        assert_eq!(round_trip.from_airport, "SFO");
+        assert_eq!(round_trip.to_airport, "JFK");
+        assert_eq!(round_trip.return_date.as_deref(), Some("2026-04-13"));
+        let same = FlightSearchParams::parse_route("SFO-JFK 2026-04-06 2026-04-13").unwrap();
+        assert_eq!(same.get_search_url(), round_trip.get_search_url());
+
+        let multi =
+            FlightSearchParams::parse_route("SFO>JFK>LHR>SFO 2026-04-06 2026-04-10 2026-04-20")
+                .unwrap();
+        assert_eq!(multi.trip_type, Trip::MultiCity);
+        assert_eq!(
+            (multi.from_airport.as_str(), multi.to_airport.as_str()),
+            ("SFO", "JFK")
+        );
+        assert_eq!(multi.depart_date, "2026-04-06");
+        let legs: Vec<(&str, &str, &str)> = multi
+            .extra_legs
+            .iter()
+            .map(|leg| {
+                (
+                    leg.from_airport.as_str(),
+                    leg.to_airport.as_str(),
+                    leg.date.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            legs,
+            [("JFK", "LHR", "2026-04-10"), ("LHR", "SFO", "2026-04-20")]
+        );
+
+        for malformed in [
+            "",
+            "SFO 2026-04-06",
+            "SFO--JFK 2026-04-06",
+            "SFO-JFK",
+            "SFO-JFK 04/06/2026",
+            "SFO-JFK 2026-04-06 2026-04-13 2026-04-20",
+            "SFO>JFK>LHR 2026-04-06",
+        ] {
+            assert!(
+                FlightSearchParams::parse_route(malformed).is_err(),
+                "{malformed}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_max_advance_days() {
+        let today = chrono::Local::now().date_naive();
+        let trip =
+            |depart: NaiveDate| FlightSearchParams::builder("SFO".into(), "JFK".into(), depart);
+
+        let near = today + chrono::Days::new(30);
+        assert!(trip(near).max_advance_days(Some(330)).build().is_ok());
+
+        let far = today + chrono::Days::new(400);
+        assert!(trip(far).build().is_ok());
+        let err = trip(far).max_advance_days(Some(330)).build().unwrap_err();
+        assert!(err.to_string().contains("330 days ahead"), "{err}");
+
+        let late_return = trip(near)
+            .return_date(far)
+            .max_advance_days(Some(330))
+            .build();
+        assert!(late_return.is_err());
+    }
 }
@@ -0,0 +1,222 @@
+//!  Delulu Travel Agent
+//!
+//!  Copyright (C) 2026  Mamy Ratsimbazafy
+//!
+//!  This program is free software: you can redistribute it and/or modify
+//!  it under the terms of the GNU Affero General Public License as published by
+//!  the Free Software Foundation, either version 3 of the License, or
+//!  (at your option) any later version.
+//!
+//!  This program is distributed in the hope that it will be useful,
+//!  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//!  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//!  GNU Affero General Public License for more details.
+//!
+//!  You should have received a copy of the GNU Affero General Public License
+//!  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Flight Ranking
+//!
+//! Orders itineraries by price, duration, stops, or a "best value" blend of
+//! the three.
+
+use serde::{Deserialize, Serialize};
+
+use crate::flights_results_parser::{FlightSearchResult, Itinerary};
+
+/// Order to sort itineraries in. Ties keep Google's order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum FlightSort {
+    Price,
+    Duration,
+    Stops,
+    /// Lowest [`value_score`] first.
+    BestValue,
+}
+
+/// Weights of the [`value_score`] terms.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case", default)]
+pub struct ValueWeights {
+    /// Weight of the price relative to the cheapest itinerary.
+    pub price: f64,
+    /// Weight of the duration relative to the shortest itinerary.
+    pub duration: f64,
+    /// Added once per stop.
+    pub stop_penalty: f64,
+}
+
+impl Default for ValueWeights {
+    fn default() -> Self {
+        Self {
+            price: 1.0,
+            duration: 0.5,
+            stop_penalty: 0.1,
+        }
+    }
+}
+
+/// "Best value" score of `itinerary`, lower is better.
+///
+/// Price and duration are normalized against the cheapest price and shortest
+/// duration of the result set, so a score of `weights.price + weights.duration`
+/// is a nonstop that is both the cheapest and the fastest. Itineraries without
+/// a price, duration or known stop count score `f64::INFINITY`.
+pub fn value_score(
+    itinerary: &Itinerary,
+    cheapest_price: i32,
+    shortest_duration: i32,
+    weights: &ValueWeights,
+) -> f64 {
+    let (Some(price), Some(duration), Some(stops)) = (
+        itinerary.price,
+        itinerary.duration_minutes,
+        itinerary.stop_count(),
+    ) else {
+        return f64::INFINITY;
+    };
+    let price_ratio = price as f64 / cheapest_price.max(1) as f64;
+    let duration_ratio = duration as f64 / shortest_duration.max(1) as f64;
+    weights.price * price_ratio
+        + weights.duration * duration_ratio
+        + weights.stop_penalty * stops as f64
+}
+
+impl FlightSearchResult {
+    /// Sorts itineraries in place. Itineraries missing the sort key go last;
+    /// `weights` only matter for [`FlightSort::BestValue`].
+    pub fn sort_itineraries(&mut self, sort: FlightSort, weights: &ValueWeights) {
+        match sort {
+            FlightSort::Price => self
+                .itineraries
+                .sort_by_key(|it| (it.price.is_none(), it.price)),
+            FlightSort::Duration => self
+                .itineraries
+                .sort_by_key(|it| (it.duration_minutes.is_none(), it.duration_minutes)),
+            FlightSort::Stops => self
+                .itineraries
+                .sort_by_key(|it| (it.stop_count().is_none(), it.stop_count())),
+            FlightSort::BestValue => {
+                let cheapest = self.lowest_price();
+                let shortest = self
+                    .itineraries
+                    .iter()
+                    .filter_map(|it| it.duration_minutes)
+                    .min();
+                let (Some(cheapest), Some(shortest)) = (cheapest, shortest) else {
+                    return;
+                };
+                self.itineraries.sort_by(|a, b| {
+                    value_score(a, cheapest, shortest, weights)
+                        .total_cmp(&value_score(b, cheapest, shortest, weights))
+                });
+            }
+        }
+    }
+
+    /// Moves nonstop itineraries ahead of connecting ones without dropping
+    /// any, keeping the current order within each group.
+    ///
+    /// A softer alternative to `max_stops: 0`, which can come back empty.
+    pub fn prefer_direct(&mut self) {
+        self.itineraries
+            .sort_by_key(|it| it.stop_count() != Some(0));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FlightSearchParams, Layover};
+
+    fn itinerary(id: &str, price: i32, duration_minutes: i32, stops: usize) -> Itinerary {
+        Itinerary {
+            id: id.to_string(),
+            flights: Vec::new(),
+            price: Some(price),
+            currency: Some("USD".to_string()),
+            original_price_usd: None,
+            duration_minutes: Some(duration_minutes),
+            class: None,
+            stops: Some(stops as u32),
+            layovers: (0..stops)
+                .map(|_| Layover {
+                    _airport_code: None,
+                    airport_city: Some("Chicago".to_string()),
+                    duration_minutes: Some(90),
+                })
+                .collect(),
+        }
+    }
+
+    fn result_with(itineraries: Vec<Itinerary>) -> FlightSearchResult {
+        FlightSearchResult {
+            search_params: FlightSearchParams::builder(
+                "SFO".into(),
+                "JFK".into(),
+                chrono::NaiveDate::from_ymd_opt(2030, 1, 15).unwrap(),
+            )
+            .build()
+            .unwrap(),
+            itineraries,
+            raw_response: String::new(),
+            warnings: Vec::new(),
+            price_trend: None,
+            currency: "USD".to_string(),
+            retry_count: None,
+        }
+    }
+
+    fn ids(result: &FlightSearchResult) -> Vec<&str> {
+        result.itineraries.iter().map(|it| it.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_best_value_beats_cheapest() {
+        let mut result = result_with(vec![
+            itinerary("cheap-slow", 300, 20 * 60, 2),
+            itinerary("fast", 500, 5 * 60, 0),
+            itinerary("balanced", 320, 6 * 60, 0),
+        ]);
+
+        result.sort_itineraries(FlightSort::Price, &ValueWeights::default());
+        assert_eq!(ids(&result), ["cheap-slow", "balanced", "fast"]);
+
+        result.sort_itineraries(FlightSort::BestValue, &ValueWeights::default());
+        assert_eq!(ids(&result), ["balanced", "fast", "cheap-slow"]);
+
+        // Ignoring duration and stops, price alone decides
+        let price_only = ValueWeights {
+            price: 1.0,
+            duration: 0.0,
+            stop_penalty: 0.0,
+        };
+        result.sort_itineraries(FlightSort::BestValue, &price_only);
+        assert_eq!(ids(&result), ["cheap-slow", "balanced", "fast"]);
+    }
+
+    #[test]
+    fn test_prefer_direct_keeps_connections() {
+        let mut result = result_with(vec![
+            itinerary("one-stop-cheap", 250, 8 * 60, 1),
+            itinerary("nonstop-pricey", 600, 5 * 60, 0),
+            itinerary("two-stops", 200, 14 * 60, 2),
+            itinerary("nonstop-cheap", 450, 5 * 60, 0),
+        ]);
+
+        result.sort_itineraries(FlightSort::Price, &ValueWeights::default());
+        result.prefer_direct();
+        assert_eq!(
+            ids(&result),
+            [
+                "nonstop-cheap",
+                "nonstop-pricey",
+                "two-stops",
+                "one-stop-cheap"
+            ]
+        );
+    }
+}
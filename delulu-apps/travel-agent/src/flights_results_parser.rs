@@ -24,6 +24,8 @@
 //!
 //! See [`schemas/flights-response.json`](schemas/flights-response.json) for the canonical JSON schema.
 
+use std::collections::{BTreeMap, HashMap};
+
 use anyhow::Result;
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -31,6 +33,11 @@ use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
 
 use crate::FlightSearchParams;
+use crate::currency::{CurrencyConversion, CurrencySymbols};
+use crate::diagnostics::UnexpectedLayout;
+
+/// Currency Google Flights prices are scraped in.
+const SOURCE_CURRENCY: &str = "USD";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
@@ -38,7 +45,56 @@ use crate::FlightSearchParams;
 pub struct FlightSearchResult {
     pub search_params: FlightSearchParams,
     pub itineraries: Vec<Itinerary>,
+    /// Page HTML the itineraries were parsed from, empty when the client was
+    /// told not to keep it.
     pub raw_response: String,
+    /// Fields that could not be parsed in best-effort mode.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// Google's verdict on current prices for the route, from the price insights banner.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_trend: Option<PriceTrend>,
+    /// Currency of the itinerary prices, kept when filters remove every
+    /// itinerary so the response still reports it.
+    #[serde(default = "source_currency")]
+    pub currency: String,
+    /// Times the client's query queue retried the fetch before it went
+    /// through, `None` for results not fetched by a client.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_count: Option<u32>,
+}
+
+fn source_currency() -> String {
+    SOURCE_CURRENCY.to_string()
+}
+
+/// Whether current prices are low, typical or high compared to usual for the route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum PriceTrend {
+    Low,
+    Typical,
+    High,
+}
+
+impl PriceTrend {
+    pub fn from_str_name(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "low" => Some(Self::Low),
+            "typical" => Some(Self::Typical),
+            "high" => Some(Self::High),
+            _ => None,
+        }
+    }
+
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Typical => "typical",
+            Self::High => "high",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,9 +110,111 @@ pub struct McpFlightResponse {
 pub struct McpFlightsResponse {
     pub total: usize,
     pub query: McpQuery,
-    pub results: Vec<McpItinerary>,
+    /// Absent when only a [`summary`](Self::summary) was requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub results: Option<Vec<McpItinerary>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<McpFlightsSummary>,
+    /// The results bucketed by number of stops, fewest first, in place of
+    /// [`results`](Self::results)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Vec<McpStopsGroup>>,
+    /// Cheapest price among the results, whatever their order
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lowest_price: Option<i32>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub warnings: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_trend: Option<PriceTrend>,
+}
+
+impl McpFlightsResponse {
+    /// Replaces the itinerary list with aggregates over it.
+    pub fn summarize(&mut self) {
+        if let Some(results) = self.results.take() {
+            self.summary = Some(McpFlightsSummary::from_results(&results));
+        }
+    }
+
+    /// Replaces the itinerary list with `grouped`, from
+    /// [`FlightSearchResult::grouped_by_stops`]. Does nothing once
+    /// [summarized](Self::summarize).
+    pub fn group_by_stops(&mut self, grouped: Vec<(Option<u32>, Vec<Itinerary>)>) {
+        if self.results.take().is_some() {
+            self.groups = Some(
+                grouped
+                    .into_iter()
+                    .map(|(stops, itineraries)| McpStopsGroup {
+                        stops,
+                        count: itineraries.len(),
+                        results: itineraries.iter().map(McpItinerary::from).collect(),
+                    })
+                    .collect(),
+            );
+        }
+    }
+}
+
+/// Itineraries sharing a number of stops.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub struct McpStopsGroup {
+    /// Absent for the itineraries whose stops could not be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stops: Option<u32>,
+    pub count: usize,
+    pub results: Vec<McpItinerary>,
+}
+
+/// Aggregates over a result set, returned instead of the itineraries when
+/// the caller only needs the overall picture.
+///
+/// Unknown prices and durations (reported as 0) are left out of the
+/// price and duration figures.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub struct McpFlightsSummary {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lowest_price: Option<i32>,
+    /// Mean of the two middle prices for an even count
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub median_price: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fastest_minutes: Option<i32>,
+    pub nonstop_count: usize,
+    /// Every airline appearing in the results, sorted and deduplicated
+    pub airlines: Vec<String>,
+}
+
+impl McpFlightsSummary {
+    pub fn from_results(results: &[McpItinerary]) -> Self {
+        let mut prices: Vec<i32> = results
+            .iter()
+            .map(|it| it.price)
+            .filter(|&p| p > 0)
+            .collect();
+        prices.sort_unstable();
+        let median_price = match prices.len() {
+            0 => None,
+            n if n % 2 == 1 => Some(prices[n / 2]),
+            n => Some((prices[n / 2 - 1] + prices[n / 2]) / 2),
+        };
+        let mut airlines: Vec<String> = results
+            .iter()
+            .flat_map(|it| it.airlines.iter().cloned())
+            .collect();
+        airlines.sort();
+        airlines.dedup();
+        Self {
+            lowest_price: prices.first().copied(),
+            median_price,
+            fastest_minutes: results.iter().map(|it| it.dur_min).filter(|&d| d > 0).min(),
+            nonstop_count: results.iter().filter(|it| it.stops == Some(0)).count(),
+            airlines,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -69,19 +227,76 @@ pub struct McpQuery {
     pub curr: String,
     pub seat: String,
     pub search_url: String,
+    /// [`FlightSearchResult::retry_count`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_count: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct McpItinerary {
+    /// [`Itinerary::id`], to ask for the full itinerary later
+    pub id: String,
     pub price: i32,
+    /// Marketing carriers
     pub airlines: Vec<String>,
+    /// Carriers actually flying codeshare segments, when they differ
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub operating_airlines: Vec<String>,
     pub dur_min: i32,
+    /// [`Itinerary::stop_count`], absent when unknown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stops: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub layover: Option<Vec<McpStop>>,
 }
 
+impl From<&Itinerary> for McpItinerary {
+    fn from(it: &Itinerary) -> Self {
+        let price = it.price.unwrap_or(0);
+        let duration_minutes = it.duration_minutes.unwrap_or(0);
+
+        let airlines: Vec<String> = it
+            .flights
+            .iter()
+            .filter_map(|f| f.airline.clone())
+            .collect();
+        let operating_airlines: Vec<String> = it
+            .flights
+            .iter()
+            .filter_map(|f| f.operating_airline.clone())
+            .collect();
+
+        let stops = it.stop_count();
+        let layover: Option<Vec<McpStop>> = if stops == Some(0) {
+            None
+        } else {
+            Some(
+                it.layovers
+                    .iter()
+                    .filter_map(|l| {
+                        l.airport_city.as_ref().map(|city| McpStop {
+                            city: city.clone(),
+                            dur_min: l.duration_minutes.unwrap_or(0),
+                        })
+                    })
+                    .collect(),
+            )
+        };
+
+        Self {
+            id: it.id.clone(),
+            price,
+            airlines,
+            operating_airlines,
+            dur_min: duration_minutes,
+            stops,
+            layover,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
@@ -90,67 +305,297 @@ pub struct McpStop {
     pub dur_min: i32,
 }
 
+/// How [`FlightSearchResult::from_html_with_options`] interprets a page.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Keep cards with unparseable fields, see
+    /// [`FlightSearchResult::from_html_best_effort`].
+    pub best_effort: bool,
+    /// Convert scraped USD prices into another currency.
+    pub conversion: Option<CurrencyConversion>,
+    /// `curr` currency the page was requested in. Picks the currency of an
+    /// ambiguous price symbol, e.g. AUD rather than USD for `$`.
+    pub currency: Option<String>,
+    /// Price symbols recognized when tagging each price with its currency.
+    pub currency_symbols: CurrencySymbols,
+    /// `hl` language the page was requested in. Selects the duration unit
+    /// tokens and the price decimal separator.
+    pub language: String,
+    /// Drop itineraries scraped below this USD price, see
+    /// [`FlightSearchResult::drop_implausible_prices`].
+    pub min_plausible_price: Option<i32>,
+}
+
+/// Default [`ParseOptions::min_plausible_price`], in USD. No airfare is this
+/// cheap; such prices are "$0" placeholders or numbers captured by mistake.
+pub const DEFAULT_MIN_PLAUSIBLE_PRICE: i32 = 10;
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            best_effort: false,
+            conversion: None,
+            currency: None,
+            currency_symbols: CurrencySymbols::default(),
+            language: "en".to_string(),
+            min_plausible_price: Some(DEFAULT_MIN_PLAUSIBLE_PRICE),
+        }
+    }
+}
+
 impl FlightSearchResult {
     pub fn from_html(html: &str, search_params: FlightSearchParams) -> Result<Self> {
-        let flights = parse_flights_response(html)?;
-        let itineraries = convert_to_itineraries(
-            flights,
-            &search_params.from_airport,
-            &search_params.to_airport,
-        );
-        anyhow::ensure!(!itineraries.is_empty(), "No flights parsed from response");
+        Self::parse(html, search_params, &ParseOptions::default())
+    }
+
+    /// Like [`Self::from_html`], but a card whose price, duration or stops
+    /// cannot be parsed is kept with those fields set to `None`, and a
+    /// description of each failure is recorded in `warnings`.
+    pub fn from_html_best_effort(html: &str, search_params: FlightSearchParams) -> Result<Self> {
+        let options = ParseOptions {
+            best_effort: true,
+            ..ParseOptions::default()
+        };
+        Self::parse(html, search_params, &options)
+    }
+
+    /// Parses `html` with explicit [`ParseOptions`]: best-effort mode,
+    /// currency conversion and the page language.
+    ///
+    /// With a conversion, prices are converted from USD into
+    /// `conversion.target` and the scraped USD price is kept in
+    /// [`Itinerary::original_price_usd`].
+    pub fn from_html_with_options(
+        html: &str,
+        search_params: FlightSearchParams,
+        options: &ParseOptions,
+    ) -> Result<Self> {
+        Self::parse(html, search_params, options)
+    }
+
+    /// Removes itineraries with a layover shorter than `minutes`.
+    ///
+    /// A layover whose duration could not be parsed does not disqualify its
+    /// itinerary.
+    pub fn filter_min_layover(&mut self, minutes: i32) {
+        self.itineraries.retain(|it| {
+            it.layovers
+                .iter()
+                .all(|l| l.duration_minutes.is_none_or(|d| d >= minutes))
+        });
+    }
+
+    /// Removes itineraries taking longer than `minutes` in total.
+    ///
+    /// Itineraries whose duration could not be parsed are removed too, since
+    /// they cannot be shown to fit the cap.
+    pub fn filter_max_duration(&mut self, minutes: i32) {
+        self.itineraries
+            .retain(|it| it.duration_minutes.is_some_and(|d| d <= minutes));
+    }
+
+    /// Removes itineraries whose scraped USD price is below `min_usd`, with a
+    /// warning for each, so a misparsed "$0" does not top the cheapest-first
+    /// ranking. Itineraries without a USD price are kept.
+    pub fn drop_implausible_prices(&mut self, min_usd: i32) {
+        retain_plausible_prices(&mut self.itineraries, min_usd, &mut self.warnings);
+    }
+
+    /// Counts how many elements each parser selector matches in `html`.
+    ///
+    /// Maintenance aid: a selector matching zero elements on a fresh page is
+    /// the one Google broke.
+    pub fn selector_match_counts(html: &str) -> Result<Vec<(&'static str, usize)>> {
+        let selectors = flight_selectors()?;
+        let document = Html::parse_document(html);
+        Ok(selectors
+            .named()
+            .into_iter()
+            .map(|(name, selector)| (name, document.select(selector).count()))
+            .collect())
+    }
+
+    fn parse(
+        html: &str,
+        search_params: FlightSearchParams,
+        options: &ParseOptions,
+    ) -> Result<Self> {
+        UnexpectedLayout::check(html)?;
+        let selectors = flight_selectors()?;
+        let document = Html::parse_document(&strip_unused_markup(html));
+        let mut warnings = Vec::new();
+        let mut itineraries = Vec::new();
+        visit_itineraries(
+            &document,
+            &search_params,
+            options,
+            &mut warnings,
+            |itinerary| {
+                itineraries.push(itinerary);
+                true
+            },
+        )?;
+        let price_trend = parse_price_trend(&document, selectors);
+        let currency = itineraries[0]
+            .currency
+            .clone()
+            .unwrap_or_else(source_currency);
         Ok(Self {
             search_params,
             itineraries,
             raw_response: html.to_string(),
+            warnings,
+            price_trend,
+            currency,
+            retry_count: None,
         })
     }
 
-    pub fn to_mcp_api_response(&self, warnings: Vec<String>) -> McpFlightResponse {
-        let curr = self
+    /// Parses `html` card by card and hands each itinerary to `emit` as soon
+    /// as its card is parsed, stopping early once `emit` returns `false`.
+    ///
+    /// Yields the same itineraries, in the same order, as
+    /// [`Self::from_html_with_options`] without collecting them first.
+    /// Partial-parse warnings are appended to `warnings`. Fails like the
+    /// other constructors when the page has no itinerary.
+    pub fn for_each_itinerary(
+        html: &str,
+        search_params: &FlightSearchParams,
+        options: &ParseOptions,
+        warnings: &mut Vec<String>,
+        emit: impl FnMut(Itinerary) -> bool,
+    ) -> Result<()> {
+        UnexpectedLayout::check(html)?;
+        let document = Html::parse_document(&strip_unused_markup(html));
+        visit_itineraries(&document, search_params, options, warnings, emit)
+    }
+
+    /// Cheapest itinerary price, whatever order the itineraries are in.
+    /// `None` when no itinerary has a price.
+    pub fn lowest_price(&self) -> Option<i32> {
+        self.itineraries.iter().filter_map(|it| it.price).min()
+    }
+
+    /// The itineraries bucketed by [number of stops](Itinerary::stop_count),
+    /// fewest first, each bucket keeping the current order. Itineraries whose
+    /// stops are unknown come last, under `None`.
+    pub fn grouped_by_stops(&self) -> Vec<(Option<u32>, Vec<Itinerary>)> {
+        let mut groups: BTreeMap<u32, Vec<Itinerary>> = BTreeMap::new();
+        let mut unknown = Vec::new();
+        for it in &self.itineraries {
+            match it.stop_count() {
+                Some(stops) => groups.entry(stops).or_default().push(it.clone()),
+                None => unknown.push(it.clone()),
+            }
+        }
+        let mut grouped: Vec<_> = groups
+            .into_iter()
+            .map(|(stops, itineraries)| (Some(stops), itineraries))
+            .collect();
+        if !unknown.is_empty() {
+            grouped.push((None, unknown));
+        }
+        grouped
+    }
+
+    /// A short paragraph describing the results for an agent to relay, e.g.
+    /// "Found 23 options SFO→JFK on 2026-04-06. Cheapest is $284 nonstop on
+    /// JetBlue (5h 25m). Fastest is 5h 10m. 8 nonstop options."
+    pub fn to_summary_text(&self) -> String {
+        let params = &self.search_params;
+        let route = format!(
+            "{}→{} on {}",
+            params.from_airport, params.to_airport, params.depart_date
+        );
+        let count = self.itineraries.len();
+        if count == 0 {
+            return format!("No flights found {route}.");
+        }
+
+        let mut text = format!(
+            "Found {count} option{} {route}.",
+            if count == 1 { "" } else { "s" }
+        );
+        let cheapest = self
             .itineraries
-            .first()
-            .and_then(|it| it.currency.clone())
-            .unwrap_or_else(|| "USD".to_string());
-        let seat = crate::Seat::as_str_name(&self.search_params.cabin_class).to_string();
+            .iter()
+            .filter_map(|it| it.price.map(|price| (price, it)))
+            .min_by_key(|(price, _)| *price);
+        if let Some((price, it)) = cheapest {
+            let stops = match it.stop_count() {
+                Some(0) => "nonstop".to_string(),
+                Some(1) => "with 1 stop".to_string(),
+                Some(n) => format!("with {n} stops"),
+                None => "with unknown stops".to_string(),
+            };
+            let currency = it.currency.as_deref().unwrap_or(&self.currency);
+            text += &format!(" Cheapest is {} {stops}", fmt_price(price, currency));
+            let mut airlines: Vec<&str> = Vec::new();
+            for airline in it.flights.iter().filter_map(|f| f.airline.as_deref()) {
+                if !airlines.contains(&airline) {
+                    airlines.push(airline);
+                }
+            }
+            if !airlines.is_empty() {
+                text += &format!(" on {}", airlines.join(", "));
+            }
+            if let Some(minutes) = it.duration_minutes {
+                text += &format!(" ({})", fmt_duration(minutes));
+            }
+            text.push('.');
+        }
+        if let Some(fastest) = self
+            .itineraries
+            .iter()
+            .filter_map(|it| it.duration_minutes)
+            .min()
+        {
+            text += &format!(" Fastest is {}.", fmt_duration(fastest));
+        }
+        text += &match self
+            .itineraries
+            .iter()
+            .filter(|it| it.stop_count() == Some(0))
+            .count()
+        {
+            0 => " No nonstop options.".to_string(),
+            1 => " 1 nonstop option.".to_string(),
+            n => format!(" {n} nonstop options."),
+        };
+        text
+    }
 
-        let results: Vec<McpItinerary> = self
+    /// The MCP `search_flights` response. `warnings` are passed through, with
+    /// one more for every [suspicious nonstop](Itinerary::is_suspicious_nonstop)
+    /// and every [inconsistent](Itinerary::inconsistencies) itinerary.
+    pub fn to_mcp_api_response(&self, mut warnings: Vec<String>) -> McpFlightResponse {
+        for it in self
             .itineraries
             .iter()
-            .map(|it| {
-                let price = it.price.unwrap_or(0);
-                let duration_minutes = it.duration_minutes.unwrap_or(0);
+            .filter(|it| it.is_suspicious_nonstop())
+        {
+            let minutes = it.duration_minutes.unwrap_or(0);
+            warnings.push(format!(
+                "Itinerary {} is listed as nonstop but takes {}h{:02}; its stops were probably not parsed",
+                it.id,
+                minutes / 60,
+                minutes % 60
+            ));
+        }
+        for it in &self.itineraries {
+            let inconsistencies = it.inconsistencies();
+            if !inconsistencies.is_empty() {
+                warnings.push(format!(
+                    "Itinerary {} is inconsistent ({}); check it on Google Flights before booking",
+                    it.id,
+                    inconsistencies.join("; ")
+                ));
+            }
+        }
+        let curr = self.currency.clone();
+        let seat = crate::Seat::as_str_name(&self.search_params.cabin_class).to_string();
 
-                let airlines: Vec<String> = it
-                    .flights
-                    .iter()
-                    .filter_map(|f| f.airline.clone())
-                    .collect();
-
-                let layover: Option<Vec<McpStop>> = if it.layovers.is_empty() {
-                    None
-                } else {
-                    Some(
-                        it.layovers
-                            .iter()
-                            .filter_map(|l| {
-                                l.airport_city.as_ref().map(|city| McpStop {
-                                    city: city.clone(),
-                                    dur_min: l.duration_minutes.unwrap_or(0),
-                                })
-                            })
-                            .collect(),
-                    )
-                };
-
-                McpItinerary {
-                    price,
-                    airlines,
-                    dur_min: duration_minutes,
-                    layover,
-                }
-            })
-            .collect();
+        let results: Vec<McpItinerary> = self.itineraries.iter().map(McpItinerary::from).collect();
 
         McpFlightResponse {
             search_flights: McpFlightsResponse {
@@ -162,9 +607,14 @@ impl FlightSearchResult {
                     curr,
                     seat,
                     search_url: self.search_params.get_search_url(),
+                    retry_count: self.retry_count,
                 },
-                results,
+                results: Some(results),
+                summary: None,
+                groups: None,
+                lowest_price: self.lowest_price(),
                 warnings,
+                price_trend: self.price_trend,
             },
         }
     }
@@ -192,18 +642,139 @@ pub struct Itinerary {
     pub price: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub currency: Option<String>,
+    /// Scraped USD price, set when `price` was converted to another currency.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_price_usd: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub duration_minutes: Option<i32>,
     pub class: Option<String>,
+    /// Number of stops the card announces ("Nonstop", "1 stop"...), which
+    /// `layovers` should agree with, or failing that the number of layovers
+    /// read. `None` when the card's stops could not be read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stops: Option<u32>,
     pub layovers: Vec<Layover>,
 }
 
+fn retain_plausible_prices(
+    itineraries: &mut Vec<Itinerary>,
+    min_usd: i32,
+    warnings: &mut Vec<String>,
+) {
+    itineraries.retain(|it| is_plausible_price(it, min_usd, warnings));
+}
+
+/// Whether `itinerary` is priced at `min_usd` or more, warning when it is
+/// not. Itineraries without a USD price, e.g. scraped in another currency,
+/// pass.
+fn is_plausible_price(itinerary: &Itinerary, min_usd: i32, warnings: &mut Vec<String>) -> bool {
+    let in_usd = itinerary.currency.as_deref().unwrap_or(SOURCE_CURRENCY) == "USD";
+    let Some(usd) = itinerary
+        .original_price_usd
+        .or(itinerary.price.filter(|_| in_usd))
+    else {
+        return true;
+    };
+    if usd >= min_usd {
+        return true;
+    }
+    warnings.push(format!(
+        "Dropped itinerary {} priced at ${usd}, below the ${min_usd} plausibility floor; its price was probably misparsed",
+        itinerary.id
+    ));
+    false
+}
+
+fn fmt_price(amount: i32, currency: &str) -> String {
+    match currency {
+        "USD" => format!("${amount}"),
+        _ => format!("{amount} {currency}"),
+    }
+}
+
+fn fmt_duration(minutes: i32) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, mins) => format!("{mins}m"),
+        (hrs, 0) => format!("{hrs}h"),
+        (hrs, mins) => format!("{hrs}h {mins}m"),
+    }
+}
+
+/// Longest nonstop we take at face value. The longest scheduled flights are
+/// around 18h30, so a longer "nonstop" most likely lost its stops in parsing.
+const MAX_PLAUSIBLE_NONSTOP_MINUTES: i32 = 1080;
+
+impl Itinerary {
+    /// Number of stops, as the card announces it or, failing that, counted
+    /// from the layovers. `None` when neither could be read, which must not
+    /// be taken for a nonstop.
+    pub fn stop_count(&self) -> Option<u32> {
+        self.stops
+            .or_else(|| (!self.layovers.is_empty()).then_some(self.layovers.len() as u32))
+    }
+
+    /// Whether this itinerary is listed as nonstop but takes longer than any
+    /// nonstop flight plausibly would.
+    pub fn is_suspicious_nonstop(&self) -> bool {
+        self.stop_count() == Some(0)
+            && self
+                .duration_minutes
+                .is_some_and(|minutes| minutes > MAX_PLAUSIBLE_NONSTOP_MINUTES)
+    }
+
+    /// Ways the stops, layovers, duration and airports of this itinerary
+    /// contradict each other, e.g. listed as nonstop with a layover. Empty
+    /// for a consistent itinerary.
+    ///
+    /// Fewer layovers than stops is not reported: that is a layover label the
+    /// parser could not read, not a contradiction on Google's side.
+    pub fn inconsistencies(&self) -> Vec<String> {
+        let mut found = Vec::new();
+        if let Some(stops) = self.stops
+            && (stops as usize) < self.layovers.len()
+        {
+            found.push(format!(
+                "listed with {} stop(s) but has {} layover(s)",
+                stops,
+                self.layovers.len()
+            ));
+        }
+        let layover_minutes: i32 = self
+            .layovers
+            .iter()
+            .filter_map(|l| l.duration_minutes)
+            .sum();
+        if let Some(total) = self.duration_minutes
+            && layover_minutes > 0
+            && layover_minutes >= total
+        {
+            found.push(format!(
+                "layovers add up to {layover_minutes} min but the whole trip takes {total} min"
+            ));
+        }
+        if let (Some(first), Some(last)) = (self.flights.first(), self.flights.last())
+            && first.departure_airport.is_some()
+            && first.departure_airport == last.arrival_airport
+        {
+            found.push(format!(
+                "departs from and arrives at {}",
+                first.departure_airport.as_deref().unwrap_or_default()
+            ));
+        }
+        found
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct FlightSegment {
+    /// Marketing carrier, the airline selling the ticket
     #[serde(skip_serializing_if = "Option::is_none")]
     pub airline: Option<String>,
+    /// Carrier actually flying a codeshare, from Google's "Operated by" note
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operating_airline: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub flight_number: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -225,12 +796,19 @@ pub struct FlightSegment {
 #[derive(Debug, Clone)]
 struct Flight {
     airline: String,
+    operating_airline: Option<String>,
+    /// Origin and destination shown on the card
+    airports: Option<(String, String)>,
     dep_time: String,
     arr_time: String,
     arrive_plus_days: Option<String>,
-    duration: String,
-    price: String,
-    layovers: Vec<Layover>,
+    duration: Option<String>,
+    price: Option<String>,
+    /// Currency of `price`, from its symbol
+    price_currency: Option<String>,
+    /// Announced number of stops, else the number of layovers read
+    stops: Option<u32>,
+    layovers: Option<Vec<Layover>>,
 }
 
 #[derive(Clone)]
@@ -242,107 +820,415 @@ struct FlightSelectors {
     _aircraft: Selector,
     times: Selector,
     duration: Selector,
-    _stops: Selector,
+    /// Origin and destination codes next to the duration
+    airports: Selector,
+    stops: Selector,
     stops_container: Selector,
     arrives_next_day: Selector,
     price: Selector,
+    price_trend: Selector,
 }
 
 impl FlightSelectors {
-    fn new() -> Self {
-        Self {
-            other_containers: Selector::parse(r#"div[jsname="YdtKid"]"#).unwrap(),
-            flight_card: Selector::parse(r#"ul.Rk10dc li"#).unwrap(),
-            airline: Selector::parse(r#"div.sSHqwe.tPgKwe.ogfYpf span"#).unwrap(),
-            _flight_number: Selector::parse(r#"span.Xsgmwe.sI2Nye"#).unwrap(),
-            _aircraft: Selector::parse(r#"span.Xsgmwe"#).unwrap(),
-            times: Selector::parse(r#"span.mv1WYe div"#).unwrap(),
-            duration: Selector::parse(r#"li div.Ak5kof div"#).unwrap(),
-            _stops: Selector::parse(r#".BbR8Ec .ogfYpf"#).unwrap(),
-            stops_container: Selector::parse(r#".BbR8Ec .sSHqwe"#).unwrap(),
-            arrives_next_day: Selector::parse(r#"span.bOzv6"#).unwrap(),
-            price: Selector::parse(r#".YMlIz.FpEdX"#).unwrap(),
-        }
+    /// Compiles every selector.
+    ///
+    /// Fails only if one of the CSS strings below is invalid, which is a
+    /// programming error caught by the tests, but it is surfaced as an error
+    /// rather than a panic in the library path.
+    fn new() -> Result<Self> {
+        #[cfg(test)]
+        SELECTOR_BUILDS.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        let parse = |css: &str| {
+            Selector::parse(css)
+                .map_err(|e| anyhow::anyhow!("Invalid CSS selector `{}`: {}", css, e))
+        };
+        Ok(Self {
+            other_containers: parse(r#"div[jsname="YdtKid"]"#)?,
+            flight_card: parse(r#"ul.Rk10dc li"#)?,
+            airline: parse(r#"div.sSHqwe.tPgKwe.ogfYpf span"#)?,
+            _flight_number: parse(r#"span.Xsgmwe.sI2Nye"#)?,
+            _aircraft: parse(r#"span.Xsgmwe"#)?,
+            times: parse(r#"span.mv1WYe div"#)?,
+            duration: parse(r#"li div.Ak5kof div"#)?,
+            airports: parse(r#"div.Ak5kof .QylvBf span[aria-describedby]"#)?,
+            stops: parse(r#".BbR8Ec .ogfYpf"#)?,
+            stops_container: parse(r#".BbR8Ec .sSHqwe"#)?,
+            arrives_next_day: parse(r#"span.bOzv6"#)?,
+            price: parse(r#".YMlIz.FpEdX"#)?,
+            price_trend: parse(r#"span.gOatQ"#)?,
+        })
+    }
+
+    /// Selectors used by the parser, by field name.
+    fn named(&self) -> [(&'static str, &Selector); 11] {
+        [
+            ("other_containers", &self.other_containers),
+            ("flight_card", &self.flight_card),
+            ("airline", &self.airline),
+            ("times", &self.times),
+            ("duration", &self.duration),
+            ("airports", &self.airports),
+            ("stops", &self.stops),
+            ("stops_container", &self.stops_container),
+            ("arrives_next_day", &self.arrives_next_day),
+            ("price", &self.price),
+            ("price_trend", &self.price_trend),
+        ]
     }
 }
 
+/// Selectors are immutable, so they are compiled once and shared by every parse.
+static FLIGHT_SELECTORS: Lazy<Result<FlightSelectors, String>> =
+    Lazy::new(|| FlightSelectors::new().map_err(|e| e.to_string()));
+
+/// Number of [`FlightSelectors::new`] calls, to check the cache is used.
+#[cfg(test)]
+static SELECTOR_BUILDS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+fn flight_selectors() -> Result<&'static FlightSelectors> {
+    FLIGHT_SELECTORS
+        .as_ref()
+        .map_err(|e| anyhow::anyhow!("{}", e))
+}
+
 static DURATION_H_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\d+)\s*h").unwrap());
 static DURATION_M_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(\d+)\s*m").unwrap());
+/// Hour and minute unit tokens of the duration label, per primary language
+/// subtag. Languages not listed here use [`DURATION_H_RE`] / [`DURATION_M_RE`].
+static DURATION_UNITS: Lazy<HashMap<&'static str, (Regex, Regex)>> = Lazy::new(|| {
+    let units = |hours: &str, minutes: &str| {
+        (
+            Regex::new(&format!(r"(\d+)\s*(?:{})", hours)).unwrap(),
+            Regex::new(&format!(r"(\d+)\s*(?:{})", minutes)).unwrap(),
+        )
+    };
+    HashMap::from([
+        ("de", units("Std|h", "Min")),
+        ("fr", units("h", "min")),
+        ("es", units("h", "min")),
+        ("it", units("h|or[ae]", "min")),
+        ("pt", units("h", "min")),
+        ("nl", units("u|h", "min|m")),
+    ])
+});
 static LAYOVER_ARIA_RE: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"(\d+)\s*h(?:r\s*(?:(\d+)\s*m(?:in)?)?)?.*?in\s+([A-Za-zÀ-ÿ'\s-][A-Za-zÀ-ÿ'\s-]*)")
         .unwrap()
 });
 
-fn parse_flights_response(html: &str) -> Result<Vec<Flight>> {
-    let selectors = FlightSelectors::new();
-    let document = Html::parse_document(html);
+/// Elements skipped by [`strip_unused_markup`], with the text ending them.
+const UNUSED_MARKUP: [(&str, &str); 4] = [
+    ("<!--", "-->"),
+    ("<script", "</script"),
+    ("<style", "</style"),
+    ("<svg", "</svg"),
+];
+
+/// Drops comments and `<script>`, `<style>` and `<svg>` elements before
+/// parsing.
+///
+/// Building the DOM dominates parse time on multi-megabyte results pages,
+/// and these account for close to 40% of a page while holding nothing the
+/// selectors read. An element whose end cannot be found is kept as is.
+fn strip_unused_markup(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(pos) = rest.find('<') {
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+
+        let skipped = UNUSED_MARKUP
+            .iter()
+            .find(|(open, _)| starts_with_tag(rest, open));
+        let end = skipped.and_then(|&(open, close)| markup_end(rest, open, close));
+        match end {
+            Some(end) => rest = &rest[end..],
+            None => {
+                out.push('<');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
 
-    let mut flights = Vec::new();
+/// Whether `html` starts with the `open` tag (or comment opener), ignoring
+/// ASCII case, so `<svg` matches `<SVG>` but not `<svgx>`.
+fn starts_with_tag(html: &str, open: &str) -> bool {
+    let Some(prefix) = html.get(..open.len()) else {
+        return false;
+    };
+    prefix.eq_ignore_ascii_case(open)
+        && (!open[1..].starts_with(|c: char| c.is_ascii_alphabetic())
+            || html[open.len()..]
+                .starts_with(|c: char| c.is_ascii_whitespace() || c == '>' || c == '/'))
+}
 
-    for container in document.select(&selectors.other_containers) {
-        extract_flights_from_element(container, &selectors, &mut flights);
+/// Byte offset just past the element or comment starting `html`.
+fn markup_end(html: &str, open: &str, close: &str) -> Option<usize> {
+    if open == "<svg" {
+        // `<svg ... />` has no children and no end tag.
+        let tag_end = html.find('>')?;
+        if html[..tag_end].ends_with('/') {
+            return Some(tag_end + 1);
+        }
+    }
+    let close_at = find_ignore_ascii_case(html, close)?;
+    let after = close_at + close.len();
+    if close == "-->" {
+        return Some(after);
     }
+    html[after..].find('>').map(|gt| after + gt + 1)
+}
 
-    anyhow::ensure!(!flights.is_empty(), "No flights parsed from response");
-    Ok(flights)
+/// Byte offset of the first occurrence of `needle`, ignoring ASCII case.
+fn find_ignore_ascii_case(haystack: &str, needle: &str) -> Option<usize> {
+    let needle = needle.as_bytes();
+    haystack
+        .match_indices(needle[0] as char)
+        .map(|(at, _)| at)
+        .find(|&at| {
+            haystack.as_bytes()[at..]
+                .get(..needle.len())
+                .is_some_and(|candidate| candidate.eq_ignore_ascii_case(needle))
+        })
 }
 
-fn extract_flights_from_element<'a>(
-    element: scraper::ElementRef<'a>,
-    selectors: &FlightSelectors,
-    flights: &mut Vec<Flight>,
-) {
-    for card in element.select(&selectors.flight_card) {
-        if let Some(flight) = parse_single_flight(card, selectors) {
-            flights.push(flight);
+/// Parses the flight cards of `document` one at a time, converting each into
+/// an itinerary for `emit` until it returns `false`.
+fn visit_itineraries(
+    document: &Html,
+    search_params: &FlightSearchParams,
+    options: &ParseOptions,
+    warnings: &mut Vec<String>,
+    mut emit: impl FnMut(Itinerary) -> bool,
+) -> Result<()> {
+    let selectors = flight_selectors()?;
+    let conversion = price_conversion(options, warnings);
+    let cards = document
+        .select(&selectors.other_containers)
+        .flat_map(|container| container.select(&selectors.flight_card));
+
+    let mut parsed_any = false;
+    for card in cards {
+        let Some(flight) = parse_single_flight(card, selectors, options, warnings) else {
+            continue;
+        };
+        let itinerary = convert_to_itinerary(
+            flight,
+            &search_params.from_airport,
+            &search_params.to_airport,
+            &search_params.depart_date,
+            &options.language,
+            conversion,
+        );
+        if let Some(min_usd) = options.min_plausible_price
+            && !is_plausible_price(&itinerary, min_usd, warnings)
+        {
+            continue;
+        }
+        parsed_any = true;
+        if !emit(itinerary) {
+            break;
         }
     }
+
+    anyhow::ensure!(parsed_any, "No flights parsed from response");
+    Ok(())
+}
+
+/// Reads the "Prices are currently <span>low</span>" price insights banner.
+fn parse_price_trend(document: &Html, selectors: &FlightSelectors) -> Option<PriceTrend> {
+    document
+        .select(&selectors.price_trend)
+        .find_map(|el| PriceTrend::from_str_name(&el.text().collect::<String>()))
 }
 
-fn parse_single_flight(card: scraper::ElementRef, _selectors: &FlightSelectors) -> Option<Flight> {
+/// Parses one flight card.
+///
+/// Airline and times identify the flight and are always required. In
+/// best-effort mode, a missing or malformed duration, price or stops field
+/// is recorded in `warnings` and left as `None` instead of dropping the card.
+fn parse_single_flight(
+    card: scraper::ElementRef,
+    _selectors: &FlightSelectors,
+    options: &ParseOptions,
+    warnings: &mut Vec<String>,
+) -> Option<Flight> {
+    let best_effort = options.best_effort;
     let airline_el = card.select(&_selectors.airline).next()?;
     let airline = airline_el.text().collect();
+    let operating_airline = card
+        .select(&_selectors.airline)
+        .find_map(|el| parse_operated_by(&el.text().collect::<String>()));
 
-    let times: Vec<_> = card.select(&_selectors.times).collect();
-    if times.len() < 2 {
+    let mut times = card.select(&_selectors.times);
+    let (Some(dep_el), Some(arr_el)) = (times.next(), times.next()) else {
         return None;
-    }
+    };
 
-    let dep_time = normalize_time(&times[0].text().collect::<String>());
-    let arr_time = normalize_time(&times[1].text().collect::<String>());
+    let dep_time = normalize_time(&dep_el.text().collect::<String>());
+    let arr_time = normalize_time(&arr_el.text().collect::<String>());
+
+    let airports = parse_card_airports(card, _selectors);
 
     let arrive_plus_days = card
         .select(&_selectors.arrives_next_day)
         .next()
         .map(|el| el.text().collect());
 
-    let dur_el = card.select(&_selectors.duration).next()?;
-    let duration = dur_el.text().collect();
-
-    let layovers = parse_layovers_from_card(card, _selectors);
+    let duration = match card.select(&_selectors.duration).next() {
+        Some(dur_el) => {
+            let text: String = dur_el.text().collect();
+            if best_effort && try_parse_duration(&text, &options.language).is_none() {
+                warnings.push(format!(
+                    "{} {}-{}: could not parse duration from '{}'",
+                    airline,
+                    dep_time,
+                    arr_time,
+                    text.trim()
+                ));
+                None
+            } else {
+                Some(text)
+            }
+        }
+        None if best_effort => {
+            warnings.push(format!(
+                "{} {}-{}: duration missing",
+                airline, dep_time, arr_time
+            ));
+            None
+        }
+        None => return None,
+    };
 
-    let price_el = card.select(&_selectors.price).next()?;
-    let price = clean_price(price_el.text().collect());
+    let stops = card
+        .select(&_selectors.stops)
+        .next()
+        .and_then(|el| parse_stop_count(&el.text().collect::<String>()));
+
+    // An unreadable stops block leaves the stop count to the card's label,
+    // unknown without one, rather than reading as nonstop
+    let (stops, layovers) = match parse_layovers_from_card(card, _selectors) {
+        (_, false) if best_effort => {
+            warnings.push(format!(
+                "{} {}-{}: could not parse stops",
+                airline, dep_time, arr_time
+            ));
+            (stops, None)
+        }
+        (layovers, _) => (stops.or(Some(layovers.len() as u32)), Some(layovers)),
+    };
+
+    let mut price_currency = None;
+    let price = match card.select(&_selectors.price).next() {
+        Some(price_el) => {
+            let text = price_el.text().collect::<String>();
+            price_currency = options
+                .currency_symbols
+                .currency_of(&text, options.currency.as_deref())
+                .map(str::to_string);
+            let price = clean_price(&text, &options.language);
+            if best_effort && price.parse::<i32>().is_err() {
+                warnings.push(format!(
+                    "{} {}-{}: could not parse price",
+                    airline, dep_time, arr_time
+                ));
+                None
+            } else {
+                Some(price)
+            }
+        }
+        None if best_effort => {
+            warnings.push(format!(
+                "{} {}-{}: price missing",
+                airline, dep_time, arr_time
+            ));
+            None
+        }
+        None => return None,
+    };
 
     Some(Flight {
         airline,
+        operating_airline,
+        airports,
         dep_time,
         arr_time,
         arrive_plus_days,
         duration,
         price,
+        price_currency,
+        stops,
         layovers,
     })
 }
 
+/// Stop count of a card's stops label: "Nonstop" is 0, "2 stops" is 2.
+fn parse_stop_count(label: &str) -> Option<u32> {
+    let digits: String = label
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    match digits.parse() {
+        Ok(count) => Some(count),
+        Err(_) if label.to_lowercase().contains("nonstop") => Some(0),
+        Err(_) => None,
+    }
+}
+
+/// Origin and destination IATA codes the card shows, e.g. ("YYZ", "CDG") for
+/// "YYZ–CDG". `None` unless both are present.
+fn parse_card_airports(
+    card: scraper::ElementRef,
+    selectors: &FlightSelectors,
+) -> Option<(String, String)> {
+    let codes: Vec<String> = card
+        .select(&selectors.airports)
+        .map(|el| el.text().collect::<String>().trim().to_string())
+        .filter(|code| code.len() == 3 && code.chars().all(|c| c.is_ascii_uppercase()))
+        .collect();
+    match codes.as_slice() {
+        [from, .., to] => Some((from.clone(), to.clone())),
+        _ => None,
+    }
+}
+
+/// Operating carrier in a codeshare note, e.g. "Air Europa" in "Operated by
+/// Air Europa for Etihad Airways". A carrier flying several legs is listed
+/// once.
+fn parse_operated_by(text: &str) -> Option<String> {
+    let rest = text.trim().strip_prefix("Operated by ")?;
+    let carriers = rest.split(" for ").next().unwrap_or(rest);
+    let mut unique: Vec<&str> = Vec::new();
+    for carrier in carriers.split(", ") {
+        let carrier = carrier.trim().trim_end_matches('.');
+        if !carrier.is_empty() && !unique.contains(&carrier) {
+            unique.push(carrier);
+        }
+    }
+    (!unique.is_empty()).then(|| unique.join(", "))
+}
+
+/// Returns the extracted layovers, and whether every stops label announcing
+/// a layover matched the layover pattern.
 fn parse_layovers_from_card(
     card: scraper::ElementRef,
     selectors: &FlightSelectors,
-) -> Vec<Layover> {
+) -> (Vec<Layover>, bool) {
     let mut layovers = Vec::new();
+    let mut complete = true;
 
     for container in card.select(&selectors.stops_container) {
         if let Some(aria_label) = container.value().attr("aria-label") {
+            if aria_label.contains("Layover") && !LAYOVER_ARIA_RE.is_match(aria_label) {
+                complete = false;
+            }
             for cap in LAYOVER_ARIA_RE.captures_iter(&aria_label) {
                 let hours = cap.get(1).map(|m| m.as_str()).unwrap_or("");
                 let mins = cap.get(2).map(|m| m.as_str()).unwrap_or("0");
@@ -361,69 +1247,154 @@ fn parse_layovers_from_card(
         }
     }
 
-    layovers
+    (layovers, complete)
 }
 
-fn clean_price(s: String) -> String {
-    s.chars().filter(|c| c.is_ascii_digit()).collect()
+/// Extracts the integer part of a formatted price such as "$1,234",
+/// "1.234 €" or "1 234,50 €".
+///
+/// A decimal part (one or two digits after the language's decimal
+/// separator) is dropped, then every remaining separator is stripped.
+fn clean_price(s: &str, language: &str) -> String {
+    let decimal = decimal_separator(language);
+    let numeric: String = s
+        .chars()
+        .filter(|c| c.is_ascii_digit() || matches!(c, '.' | ','))
+        .collect();
+    let integer = match numeric.rfind(decimal) {
+        Some(pos) if (1..=2).contains(&(numeric.len() - pos - 1)) => &numeric[..pos],
+        _ => numeric.as_str(),
+    };
+    integer.chars().filter(|c| c.is_ascii_digit()).collect()
 }
 
-fn convert_to_itineraries(
-    flights: Vec<Flight>,
+/// Conversion to apply to the scraped USD prices, `None` (with a warning) when
+/// no rate to the target currency is available.
+fn price_conversion<'a>(
+    options: &'a ParseOptions,
+    warnings: &mut Vec<String>,
+) -> Option<&'a CurrencyConversion> {
+    options.conversion.as_ref().filter(|c| {
+        let supported = c.provider.rate(SOURCE_CURRENCY, &c.target).is_some();
+        if !supported {
+            warnings.push(format!(
+                "No {} to {} exchange rate available, prices left in {}",
+                SOURCE_CURRENCY, c.target, SOURCE_CURRENCY
+            ));
+        }
+        supported
+    })
+}
+
+fn convert_to_itinerary(
+    flight: Flight,
     from_airport: &str,
     to_airport: &str,
-) -> Vec<Itinerary> {
-    let currency = Some("USD".to_string());
-
-    let mut itineraries = Vec::new();
-    let base_id = u32::from_str_radix(from_airport, 36).unwrap_or(0) << 16
-        | u32::from_str_radix(to_airport, 36).unwrap_or(0);
-
-    for (idx, flight) in flights.into_iter().enumerate() {
-        let id = format!("{:06}{:02}", base_id, idx);
-
-        let arrival_plus_days = flight
-            .arrive_plus_days
-            .as_ref()
-            .and_then(|s| {
-                let num = s.trim_start_matches('+').split_whitespace().next()?;
-                num.parse().ok()
-            })
-            .unwrap_or(0);
-
-        let combined_arrival = if arrival_plus_days == 0 {
-            Some(flight.arr_time)
-        } else {
-            Some(format!("{} +{}d", flight.arr_time, arrival_plus_days))
-        };
-
-        let segments = vec![FlightSegment {
-            airline: Some(flight.airline),
-            departure_time: Some(flight.dep_time),
-            arrival_time: combined_arrival,
-            arrival_plus_days: Some(arrival_plus_days),
-            duration_minutes: Some(parse_duration(&flight.duration)),
-            departure_airport: Some(from_airport.to_string()),
-            arrival_airport: Some(to_airport.to_string()),
-            flight_number: None,
-            aircraft: None,
-        }];
-
-        let price = flight.price.parse().ok();
-        let duration = parse_duration(&flight.duration);
+    depart_date: &str,
+    language: &str,
+    conversion: Option<&CurrencyConversion>,
+) -> Itinerary {
+    // Only USD prices are converted: a page already in another currency is
+    // left as served.
+    let source = flight
+        .price_currency
+        .unwrap_or_else(|| SOURCE_CURRENCY.to_string());
+    let conversion = conversion.filter(|_| source == SOURCE_CURRENCY);
+    let currency = Some(conversion.map(|c| c.target.clone()).unwrap_or(source));
+
+    let id = itinerary_id(&[
+        from_airport,
+        to_airport,
+        depart_date,
+        &flight.airline,
+        &flight.dep_time,
+        &flight.arr_time,
+        flight.arrive_plus_days.as_deref().unwrap_or(""),
+    ]);
+
+    let arrival_plus_days = flight
+        .arrive_plus_days
+        .as_ref()
+        .and_then(|s| {
+            let num = s.trim_start_matches('+').split_whitespace().next()?;
+            num.parse().ok()
+        })
+        .unwrap_or(0);
 
-        itineraries.push(Itinerary {
-            id,
-            flights: segments,
-            price,
-            currency: currency.clone(),
-            duration_minutes: Some(duration),
-            class: None,
-            layovers: flight.layovers,
-        });
+    let combined_arrival = if arrival_plus_days == 0 {
+        Some(flight.arr_time)
+    } else {
+        Some(format!("{} +{}d", flight.arr_time, arrival_plus_days))
+    };
+
+    // Google may serve another airport than the one searched, e.g. for a
+    // metro area or nearby airports
+    let (departure_airport, arrival_airport) = flight
+        .airports
+        .unwrap_or_else(|| (from_airport.to_string(), to_airport.to_string()));
+
+    let segments = vec![FlightSegment {
+        airline: Some(flight.airline),
+        operating_airline: flight.operating_airline,
+        departure_time: Some(flight.dep_time),
+        arrival_time: combined_arrival,
+        arrival_plus_days: Some(arrival_plus_days),
+        duration_minutes: flight
+            .duration
+            .as_deref()
+            .map(|d| parse_duration_in(d, language)),
+        departure_airport: Some(departure_airport),
+        arrival_airport: Some(arrival_airport),
+        flight_number: None,
+        aircraft: None,
+    }];
+
+    let scraped_price: Option<i32> = flight.price.and_then(|p| p.parse().ok());
+    let (price, original_price_usd) = match conversion {
+        Some(c) => (
+            scraped_price.and_then(|p| c.convert(p, SOURCE_CURRENCY)),
+            scraped_price,
+        ),
+        None => (scraped_price, None),
+    };
+    let duration = flight
+        .duration
+        .as_deref()
+        .map(|d| parse_duration_in(d, language));
+
+    Itinerary {
+        id,
+        flights: segments,
+        price,
+        currency,
+        original_price_usd,
+        duration_minutes: duration,
+        class: None,
+        stops: flight.stops,
+        layovers: flight.layovers.unwrap_or_default(),
     }
+}
 
-    itineraries
+/// Derives a short URL-safe id from the fields that identify a flight, so the
+/// same flight keeps its id regardless of its position in the results.
+///
+/// Uses 64-bit FNV-1a rather than `DefaultHasher`, whose output is not
+/// guaranteed to be stable across Rust releases.
+fn itinerary_id(parts: &[&str]) -> String {
+    use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let mut hash = FNV_OFFSET;
+    for part in parts {
+        // Separator keeps ("AB", "C") distinct from ("A", "BC")
+        for byte in part.bytes().chain(std::iter::once(0x1f)) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+    URL_SAFE_NO_PAD.encode(hash.to_be_bytes())
 }
 
 fn normalize_time(s: &str) -> String {
@@ -431,34 +1402,89 @@ fn normalize_time(s: &str) -> String {
 }
 
 fn parse_duration(s: &str) -> i32 {
+    parse_duration_in(s, "en")
+}
+
+fn parse_duration_in(s: &str, language: &str) -> i32 {
     let s = s.trim();
     if s.is_empty() {
         return 0;
     }
 
-    let hours = DURATION_H_RE
+    try_parse_duration(s, language).unwrap_or_else(|| {
+        tracing::debug!("Could not parse duration from: '{}'", s);
+        0
+    })
+}
+
+/// Parses "6h 30m"-style durations using the unit tokens of `language`,
+/// returning `None` when neither hours nor minutes can be found.
+fn try_parse_duration(s: &str, language: &str) -> Option<i32> {
+    let (hours_re, minutes_re) = duration_units(language);
+
+    let hours = hours_re
         .captures(s)
         .and_then(|caps| caps.get(1))
-        .and_then(|m| m.as_str().parse::<i32>().ok())
-        .unwrap_or(0);
+        .and_then(|m| m.as_str().parse::<i32>().ok());
 
-    let minutes = DURATION_M_RE
+    let minutes = minutes_re
         .captures(s)
         .and_then(|caps| caps.get(1))
-        .and_then(|m| m.as_str().parse::<i32>().ok())
-        .unwrap_or(0);
+        .and_then(|m| m.as_str().parse::<i32>().ok());
 
-    if hours == 0 && minutes == 0 {
-        tracing::debug!("Could not parse duration from: '{}'", s);
+    match (hours, minutes) {
+        (None, None) => None,
+        (h, m) => Some(h.unwrap_or(0) * 60 + m.unwrap_or(0)),
     }
+}
+
+/// Primary subtag of a `hl`-style language code: "en-GB" -> "en".
+fn primary_language(language: &str) -> String {
+    language
+        .split(['-', '_'])
+        .next()
+        .unwrap_or(language)
+        .to_ascii_lowercase()
+}
+
+/// Hour and minute patterns for `language`, falling back to English.
+fn duration_units(language: &str) -> (&'static Regex, &'static Regex) {
+    DURATION_UNITS
+        .get(primary_language(language).as_str())
+        .map(|(h, m)| (h, m))
+        .unwrap_or((&DURATION_H_RE, &DURATION_M_RE))
+}
 
-    hours * 60 + minutes
+/// Decimal separator Google uses when formatting prices in `language`.
+fn decimal_separator(language: &str) -> char {
+    match primary_language(language).as_str() {
+        "de" | "fr" | "es" | "it" | "pt" | "nl" => ',',
+        _ => '.',
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_selectors_compile() {
+        assert!(flight_selectors().is_ok());
+    }
+
+    #[test]
+    fn test_selectors_built_once() {
+        let html = card_html("5 hr 30 min");
+        for _ in 0..3 {
+            FlightSearchResult::from_html(&html, sfo_jfk()).unwrap();
+            FlightSearchResult::selector_match_counts(&html).unwrap();
+        }
+        assert_eq!(
+            SELECTOR_BUILDS.load(std::sync::atomic::Ordering::Relaxed),
+            1
+        );
+    }
+
     #[test]
     fn test_duration_parsing() {
         assert_eq!(parse_duration("6h 30m"), 390);
@@ -467,6 +1493,589 @@ mod tests {
         assert_eq!(parse_duration(""), 0);
     }
 
+    #[test]
+    fn test_localized_duration_parsing() {
+        assert_eq!(parse_duration_in("6 Std. 30 Min.", "de"), 390);
+        assert_eq!(parse_duration_in("12 Std.", "de-AT"), 720);
+        assert_eq!(parse_duration_in("6 h 30 min", "fr"), 390);
+        assert_eq!(parse_duration_in("45 min", "fr-CA"), 45);
+        // English unit tokens do not match German labels
+        assert_eq!(try_parse_duration("6 Std. 30 Min.", "en"), None);
+    }
+
+    #[test]
+    fn test_localized_price_parsing() {
+        assert_eq!(clean_price("$1,234", "en"), "1234");
+        assert_eq!(clean_price("$1,234.50", "en-GB"), "1234");
+        assert_eq!(clean_price("1.234 €", "de"), "1234");
+        assert_eq!(clean_price("1\u{202f}234,50\u{a0}€", "fr"), "1234");
+        assert_eq!(clean_price("350 €", "fr"), "350");
+    }
+
+    fn flight_card(airline: &str, dep: &str, arr: &str, duration: &str) -> String {
+        format!(
+            r#"<li>
+            <div class="sSHqwe tPgKwe ogfYpf"><span>{airline}</span></div>
+            <span class="mv1WYe"><div>{dep}</div><div>{arr}</div></span>
+            <div class="Ak5kof"><div>{duration}</div></div>
+            <div class="YMlIz FpEdX">$350</div>
+            </li>"#
+        )
+    }
+
+    fn results_html(cards: &[String]) -> String {
+        format!(
+            r#"<html><body><div jsname="YdtKid"><ul class="Rk10dc">{}</ul></div></body></html>"#,
+            cards.concat()
+        )
+    }
+
+    fn card_html(duration: &str) -> String {
+        results_html(&[flight_card("United", "8:00 AM", "4:30 PM", duration)])
+    }
+
+    fn itinerary(duration_minutes: Option<i32>, layovers: &[i32]) -> Itinerary {
+        Itinerary {
+            id: String::new(),
+            flights: Vec::new(),
+            price: Some(350),
+            currency: Some("USD".to_string()),
+            original_price_usd: None,
+            duration_minutes,
+            class: None,
+            stops: Some(layovers.len() as u32),
+            layovers: layovers
+                .iter()
+                .map(|&minutes| Layover {
+                    _airport_code: None,
+                    airport_city: Some("Denver".to_string()),
+                    duration_minutes: Some(minutes),
+                })
+                .collect(),
+        }
+    }
+
+    fn result_with(itineraries: Vec<Itinerary>) -> FlightSearchResult {
+        FlightSearchResult {
+            search_params: sfo_jfk(),
+            itineraries,
+            raw_response: String::new(),
+            warnings: Vec::new(),
+            price_trend: None,
+            currency: source_currency(),
+            retry_count: None,
+        }
+    }
+
+    fn sfo_jfk() -> FlightSearchParams {
+        FlightSearchParams::builder(
+            "SFO".into(),
+            "JFK".into(),
+            chrono::NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+        )
+        .build()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_strip_unused_markup() {
+        let html = concat!(
+            r#"<div><!-- <li>x</li> --><script>if (a < b) {}</script>"#,
+            r#"<span>7:00 AM</span><svg viewBox="0 0 1 1"><path d="M0"/></svg>"#,
+            r#"<svg class="i"/><STYLE>.a{}</STYLE><svgx>kept</svgx></div>"#,
+        );
+        assert_eq!(
+            strip_unused_markup(html),
+            "<div><span>7:00 AM</span><svgx>kept</svgx></div>"
+        );
+        // An unterminated element is left for the HTML parser to deal with.
+        assert_eq!(
+            strip_unused_markup("<p>a</p><script>b"),
+            "<p>a</p><script>b"
+        );
+    }
+
+    /// Stripping must not change what is parsed from any committed fixture.
+    #[test]
+    fn test_strip_unused_markup_keeps_parse_output() {
+        let fixtures_dir =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures-flights-parsing");
+        let options = ParseOptions {
+            best_effort: true,
+            ..ParseOptions::default()
+        };
+        let parse = |document: &Html| {
+            let mut warnings = Vec::new();
+            let mut itineraries = Vec::new();
+            visit_itineraries(document, &sfo_jfk(), &options, &mut warnings, |it| {
+                itineraries.push(it);
+                true
+            })
+            .unwrap();
+            let price_trend = parse_price_trend(document, flight_selectors().unwrap());
+            serde_json::json!({
+                "itineraries": itineraries,
+                "warnings": warnings,
+                "price_trend": price_trend,
+            })
+        };
+
+        let mut fixtures = 0;
+        for entry in std::fs::read_dir(&fixtures_dir).unwrap() {
+            let path = entry.unwrap().path();
+            if !path.to_string_lossy().ends_with(".html.zst") {
+                continue;
+            }
+            let compressed = std::fs::read(&path).unwrap();
+            let html = String::from_utf8(zstd::decode_all(&compressed[..]).unwrap()).unwrap();
+            assert_eq!(
+                parse(&Html::parse_document(&strip_unused_markup(&html))),
+                parse(&Html::parse_document(&html)),
+                "{}",
+                path.display()
+            );
+            fixtures += 1;
+        }
+        assert!(fixtures > 0);
+    }
+
+    #[test]
+    fn test_parse_operated_by() {
+        assert_eq!(
+            parse_operated_by("Operated by Air Europa for Etihad Airways").as_deref(),
+            Some("Air Europa")
+        );
+        assert_eq!(
+            parse_operated_by("Operated by Air Baltic").as_deref(),
+            Some("Air Baltic")
+        );
+        assert_eq!(
+            parse_operated_by("Operated by SkyWest DBA United Express, SkyWest DBA United Express")
+                .as_deref(),
+            Some("SkyWest DBA United Express")
+        );
+        assert_eq!(parse_operated_by("Etihad"), None);
+        assert_eq!(parse_operated_by("Operated by "), None);
+    }
+
+    #[test]
+    fn test_filter_min_layover() {
+        let mut result = result_with(vec![
+            itinerary(Some(420), &[45]),
+            itinerary(Some(480), &[120]),
+            itinerary(Some(330), &[]),
+        ]);
+
+        result.filter_min_layover(90);
+
+        assert_eq!(result.itineraries.len(), 2);
+        assert_eq!(
+            result.itineraries[0].layovers[0].duration_minutes,
+            Some(120)
+        );
+        assert!(result.itineraries[1].layovers.is_empty());
+    }
+
+    #[test]
+    fn test_suspicious_nonstop_warning_matches_schema() {
+        let mut long_nonstop = itinerary(Some(20 * 60 + 5), &[]);
+        long_nonstop.id = "long".to_string();
+        let result = result_with(vec![
+            itinerary(Some(6 * 60), &[]),
+            long_nonstop,
+            itinerary(Some(22 * 60), &[90]),
+        ]);
+        assert!(result.itineraries[1].is_suspicious_nonstop());
+        assert!(!result.itineraries[2].is_suspicious_nonstop());
+
+        let response = result.to_mcp_api_response(vec!["Seat defaulted".to_string()]);
+        let warnings = &response.search_flights.warnings;
+        assert_eq!(warnings.len(), 2, "{warnings:?}");
+        assert_eq!(warnings[0], "Seat defaulted");
+        assert!(warnings[1].contains("long is listed as nonstop but takes 20h05"));
+
+        let schema: serde_json::Value =
+            serde_json::from_str(include_str!("schemas/flights-response.json")).unwrap();
+        let validator = jsonschema::Validator::new(&schema).unwrap();
+        let json = serde_json::to_value(&response).unwrap();
+        assert!(validator.is_valid(&json), "{json}");
+    }
+
+    #[test]
+    fn test_summary_text() {
+        let mut cheapest = itinerary(Some(5 * 60 + 25), &[]);
+        cheapest.price = Some(284);
+        cheapest.flights = vec![FlightSegment {
+            airline: Some("JetBlue".to_string()),
+            operating_airline: None,
+            flight_number: None,
+            departure_airport: None,
+            arrival_airport: None,
+            departure_time: None,
+            arrival_time: None,
+            arrival_plus_days: None,
+            duration_minutes: None,
+            aircraft: None,
+        }];
+        let mut result = result_with(vec![
+            itinerary(Some(7 * 60), &[60]),
+            cheapest,
+            itinerary(Some(5 * 60 + 10), &[]),
+        ]);
+
+        assert_eq!(
+            result.to_summary_text(),
+            "Found 3 options SFO→JFK on 2024-01-15. Cheapest is $284 nonstop on JetBlue (5h 25m). \
+             Fastest is 5h 10m. 2 nonstop options."
+        );
+
+        result.itineraries.clear();
+        assert_eq!(
+            result.to_summary_text(),
+            "No flights found SFO→JFK on 2024-01-15."
+        );
+    }
+
+    #[test]
+    fn test_for_each_itinerary_stops_early() {
+        let html = results_html(&[
+            flight_card("Delta", "8:00 AM", "4:30 PM", "5 hr 30 min"),
+            flight_card("United", "9:00 AM", "5:30 PM", "5 hr 30 min"),
+            flight_card("JetBlue", "10:00 AM", "6:30 PM", "5 hr 30 min"),
+        ]);
+        let collected = FlightSearchResult::from_html(&html, sfo_jfk()).unwrap();
+
+        let mut seen = Vec::new();
+        FlightSearchResult::for_each_itinerary(
+            &html,
+            &sfo_jfk(),
+            &ParseOptions::default(),
+            &mut Vec::new(),
+            |itinerary| {
+                seen.push(itinerary.id);
+                seen.len() < 2
+            },
+        )
+        .unwrap();
+        let ids: Vec<_> = collected.itineraries.iter().map(|it| &it.id).collect();
+        assert_eq!(seen.iter().collect::<Vec<_>>(), ids[..2]);
+
+        let empty = results_html(&[]);
+        let visited = FlightSearchResult::for_each_itinerary(
+            &empty,
+            &sfo_jfk(),
+            &ParseOptions::default(),
+            &mut Vec::new(),
+            |_| true,
+        );
+        assert!(visited.is_err());
+    }
+
+    #[test]
+    fn test_drop_implausible_prices() {
+        let mut free = itinerary(Some(330), &[]);
+        free.id = "free".to_string();
+        free.price = Some(0);
+        let mut unpriced = itinerary(Some(400), &[]);
+        unpriced.price = None;
+        // 9 yen is no misparse, and there is no USD price to check.
+        let mut yen = itinerary(Some(420), &[]);
+        yen.price = Some(9);
+        yen.currency = Some("JPY".to_string());
+        let mut result = result_with(vec![itinerary(Some(360), &[]), free, unpriced, yen]);
+
+        result.drop_implausible_prices(DEFAULT_MIN_PLAUSIBLE_PRICE);
+
+        assert_eq!(result.itineraries.len(), 3);
+        assert_eq!(result.itineraries[0].price, Some(350));
+        assert_eq!(result.itineraries[1].price, None);
+        assert_eq!(result.itineraries[2].price, Some(9));
+        assert_eq!(result.warnings.len(), 1);
+        assert!(
+            result.warnings[0].starts_with("Dropped itinerary free priced at $0"),
+            "{:?}",
+            result.warnings
+        );
+    }
+
+    #[test]
+    fn test_inconsistent_itinerary_warning() {
+        let mut nonstop_with_layover = itinerary(Some(6 * 60), &[75]);
+        nonstop_with_layover.id = "odd".to_string();
+        nonstop_with_layover.stops = Some(0);
+        let mut consistent = itinerary(Some(7 * 60), &[60]);
+        consistent.stops = Some(1);
+        let mut unread_layover = itinerary(Some(7 * 60), &[]);
+        unread_layover.stops = Some(1);
+        let result = result_with(vec![nonstop_with_layover, consistent, unread_layover]);
+
+        assert_eq!(
+            result.itineraries[0].inconsistencies(),
+            ["listed with 0 stop(s) but has 1 layover(s)"]
+        );
+        assert!(result.itineraries[1].inconsistencies().is_empty());
+        assert!(result.itineraries[2].inconsistencies().is_empty());
+
+        let warnings = result
+            .to_mcp_api_response(Vec::new())
+            .search_flights
+            .warnings;
+        assert_eq!(
+            warnings,
+            [
+                "Itinerary odd is inconsistent (listed with 0 stop(s) but has 1 layover(s)); \
+              check it on Google Flights before booking"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_stop_count() {
+        assert_eq!(parse_stop_count("Nonstop"), Some(0));
+        assert_eq!(parse_stop_count("1 stop"), Some(1));
+        assert_eq!(parse_stop_count("2 stops"), Some(2));
+        assert_eq!(parse_stop_count("Sans escale"), None);
+    }
+
+    #[test]
+    fn test_lowest_price() {
+        let priced = |price: Option<i32>| {
+            let mut it = itinerary(Some(300), &[]);
+            it.price = price;
+            it
+        };
+        let mut result = result_with(vec![
+            priced(Some(420)),
+            priced(None),
+            priced(Some(199)),
+            priced(Some(250)),
+        ]);
+        assert_eq!(result.lowest_price(), Some(199));
+        assert_eq!(
+            result
+                .to_mcp_api_response(Vec::new())
+                .search_flights
+                .lowest_price,
+            Some(199)
+        );
+
+        result.itineraries = vec![priced(None)];
+        assert_eq!(result.lowest_price(), None);
+        result.itineraries.clear();
+        assert_eq!(result.lowest_price(), None);
+    }
+
+    #[test]
+    fn test_filter_max_duration() {
+        let mut result = result_with(vec![
+            itinerary(Some(10 * 60), &[]),
+            itinerary(Some(15 * 60), &[90]),
+            itinerary(None, &[]),
+        ]);
+
+        result.filter_max_duration(12 * 60);
+
+        assert_eq!(result.itineraries.len(), 1);
+        assert_eq!(result.itineraries[0].duration_minutes, Some(600));
+        assert_eq!(
+            result.to_mcp_api_response(Vec::new()).search_flights.total,
+            1
+        );
+    }
+
+    #[test]
+    fn test_best_effort_keeps_card_with_malformed_duration() {
+        let html = card_html("about five hours");
+
+        let result = FlightSearchResult::from_html_best_effort(&html, sfo_jfk()).unwrap();
+        assert_eq!(result.itineraries.len(), 1);
+        let itinerary = &result.itineraries[0];
+        assert_eq!(itinerary.duration_minutes, None);
+        assert_eq!(itinerary.price, Some(350));
+        assert_eq!(result.warnings.len(), 1);
+        assert!(result.warnings[0].contains("duration"));
+        assert!(result.warnings[0].contains("about five hours"));
+
+        let strict = FlightSearchResult::from_html(&html, sfo_jfk()).unwrap();
+        assert!(strict.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_best_effort_keeps_card_with_malformed_stops() {
+        let card = flight_card("United", "8:00 AM", "4:30 PM", "5 hr 30 min").replace(
+            r#"<div class="YMlIz"#,
+            r#"<div class="BbR8Ec"><div class="sSHqwe" aria-label="Layover (1 of 1) somewhere"></div></div>
+            <div class="YMlIz"#,
+        );
+        let html = results_html(&[card]);
+
+        let result = FlightSearchResult::from_html_best_effort(&html, sfo_jfk()).unwrap();
+        assert_eq!(result.itineraries.len(), 1);
+        let itinerary = &result.itineraries[0];
+        assert_eq!(itinerary.stops, None);
+        assert_eq!(itinerary.stop_count(), None);
+        assert!(!itinerary.is_suspicious_nonstop());
+        assert_eq!(result.warnings.len(), 1);
+        assert!(result.warnings[0].contains("stops"));
+
+        let response = result.to_mcp_api_response(Vec::new()).search_flights;
+        let results = response.results.unwrap();
+        assert_eq!(results[0].stops, None);
+        assert!(results[0].layover.is_some());
+        assert_eq!(McpFlightsSummary::from_results(&results).nonstop_count, 0);
+        assert!(result.to_summary_text().contains("with unknown stops"));
+    }
+
+    #[test]
+    fn test_best_effort_no_warnings_on_clean_card() {
+        let result =
+            FlightSearchResult::from_html_best_effort(&card_html("5 hr 30 min"), sfo_jfk())
+                .unwrap();
+        assert_eq!(result.itineraries[0].duration_minutes, Some(330));
+        assert!(result.warnings.is_empty());
+    }
+
+    struct FixedRates;
+
+    impl crate::ExchangeRateProvider for FixedRates {
+        fn rate(&self, from: &str, to: &str) -> Option<f64> {
+            match (from, to) {
+                ("USD", "EUR") => Some(0.9),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_convert_usd_to_eur() {
+        let conversion = CurrencyConversion::new("EUR", std::sync::Arc::new(FixedRates));
+        let options = ParseOptions {
+            conversion: Some(conversion),
+            ..ParseOptions::default()
+        };
+        let result = FlightSearchResult::from_html_with_options(
+            &card_html("5 hr 30 min"),
+            sfo_jfk(),
+            &options,
+        )
+        .unwrap();
+
+        let itinerary = &result.itineraries[0];
+        assert_eq!(itinerary.price, Some(315));
+        assert_eq!(itinerary.currency.as_deref(), Some("EUR"));
+        assert_eq!(itinerary.original_price_usd, Some(350));
+        assert!(result.warnings.is_empty());
+        assert_eq!(
+            result
+                .to_mcp_api_response(Vec::new())
+                .search_flights
+                .query
+                .curr,
+            "EUR"
+        );
+
+        // Still EUR once filters leave nothing to take the currency from.
+        let mut result = result;
+        result.filter_max_duration(0);
+        assert!(result.itineraries.is_empty());
+        assert_eq!(
+            result
+                .to_mcp_api_response(Vec::new())
+                .search_flights
+                .query
+                .curr,
+            "EUR"
+        );
+    }
+
+    #[test]
+    fn test_convert_unknown_rate_keeps_usd() {
+        let conversion = CurrencyConversion::new("JPY", std::sync::Arc::new(FixedRates));
+        let options = ParseOptions {
+            conversion: Some(conversion),
+            ..ParseOptions::default()
+        };
+        let result = FlightSearchResult::from_html_with_options(
+            &card_html("5 hr 30 min"),
+            sfo_jfk(),
+            &options,
+        )
+        .unwrap();
+
+        let itinerary = &result.itineraries[0];
+        assert_eq!(itinerary.price, Some(350));
+        assert_eq!(itinerary.currency.as_deref(), Some("USD"));
+        assert_eq!(itinerary.original_price_usd, None);
+        assert_eq!(result.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_price_currency_follows_configured_currency() {
+        let html = card_html("5 hr 30 min").replace("$350", "$123");
+        let parse_in = |currency: &str| {
+            let options = ParseOptions {
+                currency: Some(currency.to_string()),
+                ..ParseOptions::default()
+            };
+            FlightSearchResult::from_html_with_options(&html, sfo_jfk(), &options).unwrap()
+        };
+
+        for currency in ["USD", "AUD"] {
+            let result = parse_in(currency);
+            let itinerary = &result.itineraries[0];
+            assert_eq!(itinerary.price, Some(123));
+            assert_eq!(itinerary.currency.as_deref(), Some(currency));
+            assert_eq!(result.currency, currency);
+        }
+
+        // An AUD page is not converted as if it were USD.
+        let conversion = CurrencyConversion::new("EUR", std::sync::Arc::new(FixedRates));
+        let options = ParseOptions {
+            currency: Some("AUD".to_string()),
+            conversion: Some(conversion),
+            ..ParseOptions::default()
+        };
+        let result =
+            FlightSearchResult::from_html_with_options(&html, sfo_jfk(), &options).unwrap();
+        let itinerary = &result.itineraries[0];
+        assert_eq!(itinerary.price, Some(123));
+        assert_eq!(itinerary.currency.as_deref(), Some("AUD"));
+        assert_eq!(itinerary.original_price_usd, None);
+    }
+
+    #[test]
+    fn test_itinerary_id_stable_across_positions() {
+        let united = flight_card("United", "8:00 AM", "4:30 PM", "5 hr 30 min");
+        let delta = flight_card("Delta", "9:15 AM", "5:50 PM", "5 hr 35 min");
+
+        let first = FlightSearchResult::from_html(
+            &results_html(&[united.clone(), delta.clone()]),
+            sfo_jfk(),
+        )
+        .unwrap();
+        let second =
+            FlightSearchResult::from_html(&results_html(&[delta, united]), sfo_jfk()).unwrap();
+
+        let id_of = |result: &FlightSearchResult, airline: &str| {
+            result
+                .itineraries
+                .iter()
+                .find(|i| i.flights[0].airline.as_deref() == Some(airline))
+                .map(|i| i.id.clone())
+                .unwrap()
+        };
+        assert_eq!(id_of(&first, "United"), id_of(&second, "United"));
+        assert_eq!(id_of(&first, "Delta"), id_of(&second, "Delta"));
+        assert_ne!(id_of(&first, "United"), id_of(&first, "Delta"));
+
+        let id = id_of(&first, "United");
+        assert_eq!(id.len(), 11);
+        assert!(
+            id.chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        );
+    }
+
     #[test]
     fn test_normalize_time() {
         assert_eq!(normalize_time("10:30 AM"), "10:30");
@@ -656,4 +2265,102 @@ mod tests {
         );
         assert_eq!(layovers[0].duration_minutes, Some(120)); // 2h
     }
+
+    #[test]
+    fn test_summary_aggregates() {
+        let itinerary = |price: i32, dur_min: i32, airlines: &[&str], stops: usize| McpItinerary {
+            id: String::new(),
+            price,
+            airlines: airlines.iter().map(|a| a.to_string()).collect(),
+            operating_airlines: Vec::new(),
+            dur_min,
+            stops: Some(stops as u32),
+            layover: (stops > 0).then(|| {
+                vec![
+                    McpStop {
+                        city: "Denver".to_string(),
+                        dur_min: 60,
+                    };
+                    stops
+                ]
+            }),
+        };
+        let results = vec![
+            itinerary(420, 330, &["JetBlue"], 0),
+            itinerary(0, 0, &["Delta"], 1),
+            itinerary(199, 410, &["United", "Delta"], 1),
+            itinerary(300, 325, &["United"], 0),
+            itinerary(250, 600, &["Alaska"], 2),
+        ];
+
+        let summary = McpFlightsSummary::from_results(&results);
+        assert_eq!(summary.lowest_price, Some(199));
+        assert_eq!(summary.median_price, Some(275));
+        assert_eq!(summary.fastest_minutes, Some(325));
+        assert_eq!(summary.nonstop_count, 2);
+        assert_eq!(summary.airlines, ["Alaska", "Delta", "JetBlue", "United"]);
+
+        let empty = McpFlightsSummary::from_results(&[]);
+        assert_eq!(empty.lowest_price, None);
+        assert_eq!(empty.median_price, None);
+        assert_eq!(empty.nonstop_count, 0);
+    }
+
+    #[test]
+    fn test_grouped_by_stops() {
+        let with_id = |id: &str, mut it: Itinerary| {
+            it.id = id.to_string();
+            it
+        };
+        // Announced stops win over layovers the parser could not read
+        let mut announced = itinerary(Some(9 * 60), &[]);
+        announced.stops = Some(2);
+        let mut unreadable = itinerary(Some(8 * 60), &[]);
+        unreadable.stops = None;
+        let result = result_with(vec![
+            with_id("unreadable", unreadable),
+            with_id("one-stop", itinerary(Some(7 * 60), &[60])),
+            with_id("nonstop", itinerary(Some(5 * 60), &[])),
+            with_id("two-stops", itinerary(Some(10 * 60), &[45, 90])),
+            with_id("announced", announced),
+            with_id("nonstop-late", itinerary(Some(5 * 60 + 20), &[])),
+        ]);
+
+        let groups = result.grouped_by_stops();
+        let ids: Vec<(Option<u32>, Vec<&str>)> = groups
+            .iter()
+            .map(|(stops, its)| (*stops, its.iter().map(|it| it.id.as_str()).collect()))
+            .collect();
+        assert_eq!(
+            ids,
+            [
+                (Some(0), vec!["nonstop", "nonstop-late"]),
+                (Some(1), vec!["one-stop"]),
+                (Some(2), vec!["two-stops", "announced"]),
+                (None, vec!["unreadable"]),
+            ]
+        );
+
+        let mut response = result.to_mcp_api_response(Vec::new());
+        response.search_flights.group_by_stops(groups);
+        let flights = &response.search_flights;
+        assert!(flights.results.is_none());
+        assert_eq!(flights.total, 6);
+        let counts: Vec<(Option<u32>, usize)> = flights
+            .groups
+            .iter()
+            .flatten()
+            .map(|group| (group.stops, group.count))
+            .collect();
+        assert_eq!(
+            counts,
+            [(Some(0), 2), (Some(1), 1), (Some(2), 2), (None, 1)]
+        );
+
+        let schema: serde_json::Value =
+            serde_json::from_str(include_str!("schemas/flights-response.json")).unwrap();
+        let validator = jsonschema::Validator::new(&schema).unwrap();
+        let json = serde_json::to_value(&response).unwrap();
+        assert!(validator.is_valid(&json), "{json}");
+    }
 }
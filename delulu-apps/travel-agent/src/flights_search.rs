@@ -20,22 +20,89 @@
 //! Effectful (time, network) operations for Google Flights search.
 
 use crate::Trip;
-use crate::consent_cookie::generate_cookie_header;
-use crate::flights_query_builder::FlightSearchParams;
-use crate::flights_results_parser::FlightSearchResult;
-use anyhow::{Context, Result, anyhow, bail};
+use crate::airports::ResultExpectations;
+use crate::consent_cookie::{CookieCache, CookieProvider};
+use crate::currency::{Currency, CurrencyConversion, CurrencySymbols};
+use crate::diagnostics::{
+    SearchDiagnosis, SelectorHealth, is_consent_page, is_loading_placeholder, non_desktop_layout,
+};
+use crate::flights_query_builder::{DEFAULT_TFU, FlightSearchParams, GOOGLE_BASE_URL};
+use crate::flights_results_parser::{FlightSearchResult, Itinerary, ParseOptions};
+use crate::language::Language;
+use crate::providers::FlightsError;
+use anyhow::{Context, Result, bail};
 use delulu_query_queues::QueryQueue;
-use std::sync::Arc;
+use futures::{FutureExt, Stream, StreamExt, future, stream};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use wreq::header::{ACCEPT_LANGUAGE, COOKIE, HeaderMap, HeaderValue};
 use wreq::redirect::Policy;
 use wreq_util::Emulation;
 
+/// Path fetched by the `warm_up` connectivity check.
+const HOME_PATH: &str = "/travel/flights";
+
+/// Itineraries parsed ahead of a [`GoogleFlightsClient::search_stream`]
+/// consumer before the parser waits for it.
+const STREAM_BUFFER: usize = 8;
+
 #[derive(Clone)]
 pub struct GoogleFlightsClient {
     client: Arc<wreq::Client>,
     query_queue: QueryQueue,
-    _language: String,
-    _currency: String,
+    cookies: Arc<CookieCache>,
+    base_url: String,
+    tfu: String,
+    language: String,
+    _currency: Currency,
+    parse_options: ParseOptions,
+    result_expectations: Option<ResultExpectations>,
+    keep_raw_response: bool,
+    retry_on_empty: u32,
+    timeouts: HttpTimeouts,
+    headers: HeaderMap,
+    last_response: Arc<Mutex<Option<LastResponse>>>,
+}
+
+/// Timeouts of the HTTP client, kept so it can be rebuilt when one changes.
+#[derive(Clone, Copy)]
+pub(crate) struct HttpTimeouts {
+    /// Whole request, from connecting to the last body byte.
+    pub(crate) total: Duration,
+    pub(crate) connect: Duration,
+    /// Longest silence between reads.
+    pub(crate) read: Option<Duration>,
+}
+
+impl HttpTimeouts {
+    pub(crate) fn new(timeout: Duration) -> Self {
+        Self {
+            total: timeout,
+            connect: timeout,
+            read: None,
+        }
+    }
+
+    /// Browser-emulating client shared by the Google clients.
+    pub(crate) fn build_client(&self) -> Result<wreq::Client> {
+        let mut builder = wreq::Client::builder()
+            .emulation(Emulation::Safari18_5)
+            .redirect(Policy::default())
+            .timeout(self.total)
+            .connect_timeout(self.connect);
+        if let Some(read) = self.read {
+            builder = builder.read_timeout(read);
+        }
+        builder.build().context("Failed to build HTTP client")
+    }
+}
+
+/// Page kept for [`GoogleFlightsClient::diagnose_last_search`].
+struct LastResponse {
+    url: String,
+    status: u16,
+    body: String,
 }
 
 impl GoogleFlightsClient {
@@ -45,43 +112,248 @@ impl GoogleFlightsClient {
         timeout_secs: u64,
         queries_per_second: u32,
     ) -> Result<Self> {
-        let client = wreq::Client::builder()
-            .emulation(Emulation::Safari18_5)
-            .redirect(Policy::default())
-            .timeout(Duration::from_secs(timeout_secs))
-            .connect_timeout(Duration::from_secs(timeout_secs))
-            .build()
-            .context("Failed to build HTTP client")?;
+        let language = String::from(Language::new(&language)?);
+        let timeouts = HttpTimeouts::new(Duration::from_secs(timeout_secs));
+        let client = timeouts.build_client()?;
         let query_queue = QueryQueue::with_qps_limit(queries_per_second as u64);
+        let currency = Currency::new(&currency)?;
+        let parse_options = ParseOptions {
+            language: language.clone(),
+            currency: Some(currency.to_string()),
+            ..ParseOptions::default()
+        };
         Ok(Self {
             client: Arc::new(client),
             query_queue,
-            _language: language,
+            cookies: Arc::new(CookieCache::default()),
+            base_url: GOOGLE_BASE_URL.to_string(),
+            tfu: DEFAULT_TFU.to_string(),
+            language,
             _currency: currency,
+            parse_options,
+            result_expectations: None,
+            keep_raw_response: true,
+            retry_on_empty: 0,
+            timeouts,
+            headers: HeaderMap::new(),
+            last_response: Arc::new(Mutex::new(None)),
         })
     }
+
+    /// Route requests through `queue` instead of the client's own, e.g. to
+    /// share one rate limit between the flights and hotels clients.
+    pub fn query_queue(mut self, queue: QueryQueue) -> Self {
+        self.query_queue = queue;
+        self
+    }
+
+    /// Send requests to `base_url` instead of `https://www.google.com`,
+    /// e.g. a mirror or a local mock.
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Build the consent cookie with `provider` instead of
+    /// [`SocsCookieProvider`](crate::SocsCookieProvider).
+    pub fn cookie_provider(mut self, provider: Arc<dyn CookieProvider>) -> Self {
+        self.cookies = Arc::new(CookieCache::new(provider));
+        self
+    }
+
+    /// Send `tfu` in search URLs instead of [`DEFAULT_TFU`], for when Google
+    /// stops accepting the default.
+    pub fn tfu(mut self, tfu: impl Into<String>) -> Self {
+        self.tfu = tfu.into();
+        self
+    }
+
+    /// Keep flight cards whose price, duration or stops fail to parse,
+    /// reporting them in [`FlightSearchResult::warnings`] instead of dropping them.
+    pub fn best_effort(mut self, enabled: bool) -> Self {
+        self.parse_options.best_effort = enabled;
+        self
+    }
+
+    /// Warn in [`FlightSearchResult::warnings`] when a search returns fewer
+    /// itineraries than `expectations` for its route class, a sign of a
+    /// partial parse. Off (`None`) by default.
+    pub fn expect_results(mut self, expectations: Option<ResultExpectations>) -> Self {
+        self.result_expectations = expectations;
+        self
+    }
+
+    /// Keep the page HTML in [`FlightSearchResult::raw_response`] (the
+    /// default), or free it once parsed to hold only the itineraries, e.g.
+    /// when keeping many results of a batch search.
+    pub fn keep_raw_response(mut self, keep: bool) -> Self {
+        self.keep_raw_response = keep;
+        self
+    }
+
+    /// Drop itineraries scraped below this USD price as misparsed, or keep
+    /// every price with `None`. Defaults to
+    /// [`DEFAULT_MIN_PLAUSIBLE_PRICE`](crate::DEFAULT_MIN_PLAUSIBLE_PRICE).
+    pub fn min_plausible_price(mut self, min_usd: Option<i32>) -> Self {
+        self.parse_options.min_plausible_price = min_usd;
+        self
+    }
+
+    /// Convert scraped USD prices client-side with caller-supplied rates.
+    /// Off (`None`) by default.
+    pub fn convert_to(mut self, conversion: Option<CurrencyConversion>) -> Self {
+        self.parse_options.conversion = conversion;
+        self
+    }
+
+    /// Recognize price symbols with `symbols` instead of
+    /// [`DEFAULT_CURRENCY_SYMBOLS`](crate::DEFAULT_CURRENCY_SYMBOLS). An
+    /// ambiguous symbol such as `$` resolves to the client's currency.
+    pub fn currency_symbols(mut self, symbols: CurrencySymbols) -> Self {
+        self.parse_options.currency_symbols = symbols;
+        self
+    }
+
+    /// Give up connecting after `timeout` instead of the `timeout_secs` passed
+    /// to [`Self::new`]. A failed connect surfaces as
+    /// [`FlightsError::ConnectTimeout`](crate::FlightsError::ConnectTimeout).
+    pub fn connect_timeout(mut self, timeout: Duration) -> Result<Self> {
+        self.timeouts.connect = timeout;
+        self.client = Arc::new(self.timeouts.build_client()?);
+        Ok(self)
+    }
+
+    /// Give up when Google sends nothing for `timeout` once connected.
+    /// Unset by default, leaving only the overall `timeout_secs`. Either
+    /// surfaces as [`FlightsError::ReadTimeout`](crate::FlightsError::ReadTimeout).
+    pub fn read_timeout(mut self, timeout: Duration) -> Result<Self> {
+        self.timeouts.read = Some(timeout);
+        self.client = Arc::new(self.timeouts.build_client()?);
+        Ok(self)
+    }
+
+    /// Send `headers` with every request, e.g. gateway credentials or
+    /// `X-Forwarded-For`. A `Cookie` is merged with the consent cookie and an
+    /// `Accept-Language` replaces the one derived from the language.
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Fetch again, up to `retries` times, when a page comes back without any
+    /// flight cards. Google sometimes serves an empty shell that fills in on
+    /// the next request. Retries go through the query queue like any fetch.
+    /// Off (`0`) by default.
+    pub fn retry_on_empty(mut self, retries: u32) -> Self {
+        self.retry_on_empty = retries;
+        self
+    }
+
+    /// Explains the page returned by the most recent fetch: consent or CAPTCHA
+    /// interstitial, size, and selector matches.
+    ///
+    /// Returns `None` if nothing was fetched yet or the last request got no
+    /// response. With concurrent searches, "last" is whichever finished last.
+    pub fn diagnose_last_search(&self) -> Option<SearchDiagnosis> {
+        let last = self.last_response.lock().unwrap_or_else(|e| e.into_inner());
+        last.as_ref()
+            .map(|last| SearchDiagnosis::from_flights_page(&last.url, last.status, &last.body))
+    }
+
+    fn record_response(&self, response: Option<LastResponse>) {
+        *self.last_response.lock().unwrap_or_else(|e| e.into_inner()) = response;
+    }
+}
+
+/// `Accept-Language` value for a `hl`-style language code,
+/// e.g. "en" -> "en", "en-GB" -> "en-GB,en;q=0.9".
+pub(crate) fn accept_language(language: &str) -> String {
+    match language.split_once('-') {
+        Some((primary, _)) => format!("{},{};q=0.9", language, primary),
+        None => language.to_string(),
+    }
+}
+
+/// Headers for one request: `custom`, with the consent `cookie` put ahead of
+/// any cookies it carries and `accept_language` unless it sets its own.
+pub(crate) fn request_headers(
+    custom: &HeaderMap,
+    cookie: &str,
+    accept_language: &str,
+) -> HeaderMap {
+    let mut headers = custom.clone();
+    let mut merged = cookie.as_bytes().to_vec();
+    for extra in custom.get_all(COOKIE) {
+        merged.extend_from_slice(b"; ");
+        merged.extend_from_slice(extra.as_bytes());
+    }
+    if let Ok(value) = HeaderValue::from_bytes(&merged) {
+        headers.insert(COOKIE, value);
+    }
+    if !headers.contains_key(ACCEPT_LANGUAGE)
+        && let Ok(value) = HeaderValue::from_str(accept_language)
+    {
+        headers.insert(ACCEPT_LANGUAGE, value);
+    }
+    headers
 }
 
 impl GoogleFlightsClient {
+    /// Generates and caches the consent cookie ahead of the first search.
+    ///
+    /// With `check_connectivity`, also fetches the Google Flights home page so
+    /// network or consent problems surface at startup rather than on the
+    /// first user request.
+    pub async fn warm_up(&self, check_connectivity: bool) -> Result<()> {
+        let cookie_header = self.cookies.header(&self.language)?;
+        if check_connectivity {
+            let response = self
+                .client
+                .get(format!("{}{}", self.base_url, HOME_PATH))
+                .headers(request_headers(
+                    &self.headers,
+                    &cookie_header,
+                    &accept_language(&self.language),
+                ))
+                .send()
+                .await
+                .context("Connectivity check failed")?;
+            let status = response.status();
+            anyhow::ensure!(
+                status.is_success(),
+                "Connectivity check failed: HTTP {}",
+                status
+            );
+        }
+        Ok(())
+    }
+
     pub async fn fetch_raw(&self, url: &str) -> Result<String> {
-        let cookie_header = generate_cookie_header();
+        self.fetch_raw_observed(url).await.map(|(body, _)| body)
+    }
+
+    /// [`Self::fetch_raw`], also returning how many times the query queue
+    /// retried the request.
+    async fn fetch_raw_observed(&self, url: &str) -> Result<(String, u32)> {
+        let headers = request_headers(
+            &self.headers,
+            &self.cookies.header(&self.language)?,
+            &accept_language(&self.language),
+        );
         let client_inner = Arc::clone(&self.client);
+        self.record_response(None);
 
         let queue_start = std::time::Instant::now();
         let response = self
             .query_queue
-            .with_retry(move || {
+            .with_retry_observed(move || {
                 let url = url.to_string();
-                let cookie = cookie_header.clone();
+                let headers = headers.clone();
                 let http_client = client_inner.clone();
                 async move {
                     let http_start = std::time::Instant::now();
                     tracing::trace!("[fetch_raw] Starting HTTP request to: {}", url);
-                    let resp = http_client
-                        .get(url)
-                        .header("Cookie", &cookie)
-                        .send()
-                        .await?;
+                    let resp = http_client.get(url).headers(headers).send().await?;
                     let http_elapsed = http_start.elapsed();
                     tracing::trace!("[fetch_raw] HTTP request completed in {:?}", http_elapsed);
                     Ok(resp)
@@ -94,7 +366,7 @@ impl GoogleFlightsClient {
             total_elapsed
         );
 
-        let response = response.map_err(|e| anyhow!("Request failed: {:?}", e))?;
+        let (response, retry_count) = response.context("Request failed")?;
 
         let status = response.status();
         tracing::debug!(
@@ -112,17 +384,18 @@ impl GoogleFlightsClient {
             body_elapsed,
             body_len_kb
         );
+        self.record_response(Some(LastResponse {
+            url: url.to_string(),
+            status: status.as_u16(),
+            body: body.clone(),
+        }));
 
         if !status.is_success() {
             let body_preview = body.chars().take(500).collect::<String>();
             bail!("HTTP error {}: {}", status, body_preview);
         }
 
-        let is_consent_page = body.contains("consent.google.com")
-            || body.contains("base href=\"https://consent.google.com\"")
-            || body.contains("ppConfig");
-
-        if is_consent_page {
+        if is_consent_page(&body) {
             let body_preview = body.chars().take(300).collect::<String>();
             bail!(
                 "Consent wall detected - cookies not accepted. \
@@ -132,11 +405,49 @@ impl GoogleFlightsClient {
             );
         }
 
-        Ok(body)
+        Ok((body, retry_count))
     }
 
-    pub async fn search_flights(&self, params: &FlightSearchParams) -> Result<FlightSearchResult> {
-        let overall_start = std::time::Instant::now();
+    /// Runs one known-good search (SFO→LAX a week out) through the normal
+    /// fetch path and reports whether the parser selectors still match.
+    ///
+    /// Meant for readiness probes: a critical selector matching nothing
+    /// usually means Google changed its markup.
+    pub async fn self_check(&self) -> SelectorHealth {
+        let depart = chrono::Local::now().date_naive() + chrono::Duration::days(7);
+        let params = FlightSearchParams::builder("SFO".into(), "LAX".into(), depart)
+            .trip_type(Trip::OneWay)
+            .build()
+            .expect("self-check search parameters are valid");
+        let url = params.get_search_url_with_tfu(&self.base_url, &self.tfu);
+        match self.fetch_raw(&url).await {
+            Ok(html) => SelectorHealth::from_flights_page(&url, &html),
+            Err(e) => SelectorHealth::fetch_failed(&url, &e),
+        }
+    }
+
+    /// [`Self::search_flights`], abandoned as soon as `token` is cancelled,
+    /// e.g. when the MCP client that asked for it goes away.
+    ///
+    /// The search is dropped wherever it stands (waiting for the queue,
+    /// mid-request or between retries), which releases its [`QueryQueue`]
+    /// permit, and [`FlightsError::Cancelled`] is returned.
+    pub async fn search_cancellable(
+        &self,
+        params: &FlightSearchParams,
+        token: CancellationToken,
+    ) -> Result<FlightSearchResult, FlightsError> {
+        params.validate().map_err(FlightsError::InvalidParams)?;
+        tokio::select! {
+            biased;
+            _ = token.cancelled() => Err(FlightsError::Cancelled),
+            result = self.search_flights(params) => result.map_err(FlightsError::from_search),
+        }
+    }
+
+    /// Validates `params` and builds the URL [`Self::search_flights`] and
+    /// [`Self::search_stream`] fetch.
+    fn checked_search_url(&self, params: &FlightSearchParams) -> Result<String> {
         params.validate().context("Invalid search parameters")?;
 
         if params.trip_type == Trip::RoundTrip && params.return_date.is_none() {
@@ -146,7 +457,7 @@ impl GoogleFlightsClient {
         }
 
         let url_build_start = std::time::Instant::now();
-        let url = params.get_search_url();
+        let url = params.get_search_url_with_tfu(&self.base_url, &self.tfu);
         let url_build_elapsed = url_build_start.elapsed();
         tracing::info!("🔗 Search URL built in {:?}: {}", url_build_elapsed, url);
 
@@ -161,25 +472,148 @@ impl GoogleFlightsClient {
             anyhow::ensure!(return_date >= today, "Return date cannot be in the past");
         }
 
-        let fetch_start = std::time::Instant::now();
-        tracing::info!("Starting HTTP fetch to Google Flights...");
-        let html = self.fetch_raw(&url).await?;
-        let fetch_elapsed = fetch_start.elapsed();
-        tracing::info!(
-            "HTTP fetch completed in {:?}, got {} KB",
-            fetch_elapsed,
-            html.len() / 1024
-        );
+        Ok(url)
+    }
+
+    /// Results page for `params`, refetched up to `retry_on_empty` times
+    /// while it has no flight cards. Consent walls are never retried.
+    async fn fetch_results_page(&self, params: &FlightSearchParams) -> Result<String> {
+        let url = self.checked_search_url(params)?;
+        let mut retries = 0;
+        loop {
+            let html = self.fetch_raw(&url).await?;
+            if has_flight_cards(&html) || is_consent_page(&html) || retries >= self.retry_on_empty {
+                return Ok(html);
+            }
+            retries += 1;
+            tracing::warn!(
+                "No flights in response, retrying ({}/{})",
+                retries,
+                self.retry_on_empty
+            );
+        }
+    }
+
+    /// Like [`Self::search_flights`], but yields each itinerary as soon as
+    /// its card is parsed instead of once the whole page is.
+    ///
+    /// Parsing runs on a blocking thread at most [`STREAM_BUFFER`]
+    /// itineraries ahead of the consumer and stops once the stream is
+    /// dropped, so taking the first few results skips the rest of the page.
+    /// A fetch or parse failure is yielded as the last item; partial-parse
+    /// warnings are only logged.
+    pub fn search_stream<'a>(
+        &'a self,
+        params: &'a FlightSearchParams,
+    ) -> impl Stream<Item = Result<Itinerary>> + Send + 'a {
+        let (tx, rx) = tokio::sync::mpsc::channel(STREAM_BUFFER);
+        let producer = async move {
+            let html = match self.fetch_results_page(params).await {
+                Ok(html) => html,
+                Err(e) => {
+                    let _ = tx.send(Err(e)).await;
+                    return;
+                }
+            };
+            let params = params.clone();
+            let options = self.parse_options.clone();
+            let parsing = tokio::task::spawn_blocking(move || {
+                let mut warnings = Vec::new();
+                let parsed = FlightSearchResult::for_each_itinerary(
+                    &html,
+                    &params,
+                    &options,
+                    &mut warnings,
+                    |itinerary| tx.blocking_send(Ok(itinerary)).is_ok(),
+                );
+                for warning in &warnings {
+                    tracing::warn!("Partial parse: {}", warning);
+                }
+                if let Err(e) = parsed {
+                    let _ = tx.blocking_send(Err(e));
+                }
+            });
+            if let Err(e) = parsing.await {
+                tracing::error!("Streaming parser panicked: {}", e);
+            }
+        };
+
+        let itineraries = stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|item| (item, rx))
+        });
+        // The producer sends through the channel and yields nothing itself;
+        // selecting on it is what drives the fetch.
+        let producer = producer
+            .into_stream()
+            .filter_map(|()| future::ready(None::<Result<Itinerary>>));
+        stream::select(itineraries, producer)
+    }
+
+    #[tracing::instrument(
+        name = "flights_client",
+        skip_all,
+        fields(from = %params.from_airport, to = %params.to_airport)
+    )]
+    pub async fn search_flights(&self, params: &FlightSearchParams) -> Result<FlightSearchResult> {
+        let overall_start = std::time::Instant::now();
+        let url = self.checked_search_url(params)?;
+
+        let mut retries = 0;
+        let mut retry_count = 0;
+        let (html, parsed, parse_start) = loop {
+            let fetch_start = std::time::Instant::now();
+            tracing::info!("Starting HTTP fetch to Google Flights...");
+            let (html, fetch_retries) = self.fetch_raw_observed(&url).await?;
+            retry_count += fetch_retries;
+            let fetch_elapsed = fetch_start.elapsed();
+            tracing::info!(
+                "HTTP fetch completed in {:?}, got {} KB",
+                fetch_elapsed,
+                html.len() / 1024
+            );
+
+            let parse_start = std::time::Instant::now();
+            let parsed = FlightSearchResult::from_html_with_options(
+                &html,
+                params.clone(),
+                &self.parse_options,
+            );
+            if parsed.is_err()
+                && !has_flight_cards(&html)
+                && !is_consent_page(&html)
+                && retries < self.retry_on_empty
+            {
+                retries += 1;
+                tracing::warn!(
+                    "No flights in response, retrying ({}/{})",
+                    retries,
+                    self.retry_on_empty
+                );
+                continue;
+            }
+            break (html, parsed, parse_start);
+        };
 
-        let parse_start = std::time::Instant::now();
-        match FlightSearchResult::from_html(&html, params.clone()) {
-            Ok(result) => {
+        match parsed {
+            Ok(mut result) => {
                 let parse_elapsed = parse_start.elapsed();
                 tracing::debug!(
                     "Parsed {} itineraries in {:?}",
                     result.itineraries.len(),
                     parse_elapsed
                 );
+                if let Some(warning) = self.result_expectations.and_then(|expectations| {
+                    expectations.low_results_warning(params, result.itineraries.len())
+                }) {
+                    result.warnings.push(warning);
+                }
+                if !self.keep_raw_response {
+                    result.raw_response = String::new();
+                }
+                result.retry_count = Some(retry_count);
+                for warning in &result.warnings {
+                    tracing::warn!("Partial parse: {}", warning);
+                }
                 let total_elapsed = overall_start.elapsed();
                 tracing::info!("Total search_flights time: {:?}", total_elapsed);
                 Ok(result)
@@ -189,13 +623,11 @@ impl GoogleFlightsClient {
                 let preview = html.chars().take(2000).collect::<String>();
                 tracing::error!("Parse failed after {:?}: {:?}", parse_elapsed, e);
 
-                let has_flight_cards = html.contains("pIav2d") || html.contains("JMc5Xc");
-                let has_loading = html.contains("Loading results") || html.contains("jsshadow");
-                let has_consent = html.contains("consent.google.com") || html.contains("ppConfig");
-
-                if has_consent {
+                if is_consent_page(&html) {
                     tracing::error!("Consent wall detected - cookies not accepted");
-                } else if !has_flight_cards && has_loading {
+                } else if let Some(layout) = non_desktop_layout(&html) {
+                    tracing::error!("Google served the {} layout, not desktop", layout);
+                } else if is_loading_placeholder(&html) {
                     tracing::warn!("Detected loading spinner without flight data.");
                     tracing::warn!(
                         "This often happens for sparse routes or when Google loads results via JavaScript."
@@ -206,7 +638,7 @@ impl GoogleFlightsClient {
                     tracing::warn!(
                         "Consider using a headless browser or checking route popularity."
                     );
-                } else if !has_flight_cards {
+                } else if !has_flight_cards(&html) {
                     tracing::warn!("No flight data in response. This may indicate:");
                     tracing::warn!("  - Route returned no flights (might be sold out)");
                     tracing::warn!("  - Google using JS lazy-loading for this route");
@@ -225,3 +657,458 @@ impl GoogleFlightsClient {
         }
     }
 }
+
+/// Whether `html` contains any flight card markup, parseable or not.
+fn has_flight_cards(html: &str) -> bool {
+    html.contains("pIav2d") || html.contains("JMc5Xc")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::CRITICAL_SELECTORS;
+    use crate::test_support::{
+        header_value, refused_url, spawn_echo_server, spawn_flaky_server, spawn_sequence_server,
+    };
+    use std::sync::atomic::Ordering;
+    use wreq::header::HeaderName;
+
+    #[test]
+    fn test_accept_language_value() {
+        assert_eq!(accept_language("en"), "en");
+        assert_eq!(accept_language("en-GB"), "en-GB,en;q=0.9");
+    }
+
+    #[tokio::test]
+    async fn test_sends_accept_language() {
+        let (url, request) = spawn_echo_server("<html></html>").await;
+        let client = GoogleFlightsClient::new("fr".into(), "EUR".into(), 5, 10).unwrap();
+
+        client.fetch_raw(&url).await.unwrap();
+
+        let head = request.await.unwrap();
+        assert_eq!(header_value(&head, "accept-language"), Some("fr"));
+        assert!(header_value(&head, "cookie").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_language_normalized_for_headers_and_parsing() {
+        let french_card = r#"<html><body><div jsname="YdtKid"><ul class="Rk10dc"><li>
+            <div class="sSHqwe tPgKwe ogfYpf"><span>Air France</span></div>
+            <span class="mv1WYe"><div>08:00</div><div>16:30</div></span>
+            <div class="Ak5kof"><div>8 h 30 min</div></div>
+            <div class="YMlIz FpEdX">350 $US</div>
+        </li></ul></div></body></html>"#;
+        let depart = chrono::Local::now().date_naive() + chrono::Duration::days(7);
+        let params = FlightSearchParams::builder("JFK".into(), "CDG".into(), depart)
+            .trip_type(Trip::OneWay)
+            .build()
+            .unwrap();
+
+        let (url, request) = spawn_echo_server(french_card).await;
+        let client = GoogleFlightsClient::new("FR_fr".into(), "EUR".into(), 5, 10)
+            .unwrap()
+            .base_url(&url);
+        assert_eq!(client.parse_options.language, "fr-FR");
+        let result = client.search_flights(&params).await.unwrap();
+        assert_eq!(result.itineraries[0].duration_minutes, Some(510));
+
+        let head = request.await.unwrap();
+        assert_eq!(
+            header_value(&head, "accept-language"),
+            Some("fr-FR,fr;q=0.9")
+        );
+
+        let Err(err) = GoogleFlightsClient::new("english".into(), "USD".into(), 5, 10) else {
+            panic!("accepted an invalid language");
+        };
+        assert!(err.to_string().contains("invalid language code"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_sends_custom_headers() {
+        let (url, request) = spawn_echo_server("<html></html>").await;
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_static("x-forwarded-for"),
+            HeaderValue::from_static("203.0.113.7"),
+        );
+        headers.insert(COOKIE, HeaderValue::from_static("gateway=token"));
+        let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 10)
+            .unwrap()
+            .with_headers(headers);
+
+        client.fetch_raw(&url).await.unwrap();
+
+        let head = request.await.unwrap();
+        assert_eq!(header_value(&head, "x-forwarded-for"), Some("203.0.113.7"));
+        let cookie = header_value(&head, "cookie").unwrap();
+        assert!(cookie.starts_with(client.cookies.header("en").unwrap().as_str()));
+        assert!(cookie.ends_with("; gateway=token"));
+        assert_eq!(header_value(&head, "accept-language"), Some("en"));
+    }
+
+    struct FixedCookie;
+
+    impl CookieProvider for FixedCookie {
+        fn cookie_header(&self, language: &str) -> Result<String> {
+            Ok(format!("SOCS=fixed-{language}"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_cookie_provider() {
+        let (url, request) = spawn_echo_server("<html></html>").await;
+        let client = GoogleFlightsClient::new("fr".into(), "EUR".into(), 5, 10)
+            .unwrap()
+            .cookie_provider(Arc::new(FixedCookie));
+
+        client.fetch_raw(&url).await.unwrap();
+
+        let head = request.await.unwrap();
+        assert_eq!(header_value(&head, "cookie"), Some("SOCS=fixed-fr"));
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_caches_cookie() {
+        let (url, request) = spawn_echo_server("<html></html>").await;
+        let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 10).unwrap();
+
+        client.warm_up(false).await.unwrap();
+        client.fetch_raw(&url).await.unwrap();
+
+        let head = request.await.unwrap();
+        assert_eq!(
+            header_value(&head, "cookie"),
+            Some(client.cookies.header("en").unwrap().as_str())
+        );
+        assert_eq!(client.cookies.generations.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_diagnose_consent_page() {
+        let consent = include_str!("../tests/fixtures-diagnostics/consent-wall.html");
+        let (url, request) = spawn_echo_server(consent).await;
+        let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 10).unwrap();
+        assert!(client.diagnose_last_search().is_none());
+
+        let err = client.fetch_raw(&url).await.unwrap_err();
+        assert!(err.to_string().contains("Consent wall"));
+        request.await.unwrap();
+
+        let diagnosis = client.diagnose_last_search().unwrap();
+        assert_eq!(diagnosis.search_url, url);
+        assert_eq!(diagnosis.http_status, 200);
+        assert_eq!(diagnosis.response_bytes, consent.len());
+        assert!(diagnosis.consent_page);
+        assert!(!diagnosis.captcha_page);
+        assert!(!diagnosis.loading_placeholder);
+        assert_eq!(diagnosis.selector_matches.len(), 11);
+        assert!(diagnosis.selector_matches.iter().all(|m| m.count == 0));
+        assert!(diagnosis.next_steps[0].contains("consent"));
+        assert!(diagnosis.next_steps.last().unwrap().contains(&url));
+    }
+
+    #[test]
+    fn test_client_rejects_invalid_currency() {
+        assert!(GoogleFlightsClient::new("en".into(), "usd".into(), 5, 10).is_ok());
+        let Err(err) = GoogleFlightsClient::new("en".into(), "Dollars".into(), 5, 10) else {
+            panic!("accepted an invalid currency");
+        };
+        assert!(err.to_string().contains("invalid currency code"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_tfu_override_sent() {
+        let (url, request) = spawn_echo_server("<html></html>").await;
+        let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 10)
+            .unwrap()
+            .base_url(&url)
+            .tfu("CgIIAQ");
+
+        client.self_check().await;
+        let head = request.await.unwrap();
+        let request_line = head.lines().next().unwrap();
+        assert!(request_line.contains("&tfu=CgIIAQ "), "{request_line}");
+    }
+
+    #[tokio::test]
+    async fn test_self_check_against_mock_google() {
+        let compressed =
+            include_bytes!("../tests/fixtures-flights-parsing/nonstop-sfo_jfk_economy.html.zst");
+        let html = String::from_utf8(zstd::decode_all(&compressed[..]).unwrap()).unwrap();
+        let (url, request) = spawn_echo_server(Box::leak(html.into_boxed_str())).await;
+        let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 10)
+            .unwrap()
+            .base_url(&url);
+
+        let health = client.self_check().await;
+        let head = request.await.unwrap();
+        assert!(head.starts_with("GET /travel/flights/search?tfs="));
+        assert!(health.healthy, "{health:?}");
+        assert!(health.broken_selectors.is_empty());
+        let cards = health
+            .selector_matches
+            .iter()
+            .find(|m| m.selector == "flight_card")
+            .unwrap();
+        assert!(cards.count > 0);
+
+        let (url, request) = spawn_echo_server("<html><body>Redesigned</body></html>").await;
+        let health = client.base_url(&url).self_check().await;
+        request.await.unwrap();
+        assert!(!health.healthy);
+        assert_eq!(health.broken_selectors, CRITICAL_SELECTORS);
+        assert!(health.error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_retry_on_empty_refetches() {
+        let compressed =
+            include_bytes!("../tests/fixtures-flights-parsing/nonstop-sfo_jfk_economy.html.zst");
+        let html = String::from_utf8(zstd::decode_all(&compressed[..]).unwrap()).unwrap();
+        let empty = "<html><body><div jsname=\"YdtKid\"></div></body></html>";
+        let depart = chrono::Local::now().date_naive() + chrono::Duration::days(7);
+        let params = FlightSearchParams::builder("SFO".into(), "JFK".into(), depart)
+            .trip_type(Trip::OneWay)
+            .build()
+            .unwrap();
+
+        let (url, _server) = spawn_echo_server(empty).await;
+        let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 10)
+            .unwrap()
+            .base_url(&url);
+        assert!(client.search_flights(&params).await.is_err());
+
+        let (url, requests) =
+            spawn_sequence_server(vec![empty, Box::leak(html.into_boxed_str())]).await;
+        let client = client.base_url(&url).retry_on_empty(1);
+        let result = client.search_flights(&params).await.unwrap();
+        assert!(!result.itineraries.is_empty());
+        assert_eq!(requests.await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_on_empty_skips_consent_wall() {
+        let consent = include_str!("../tests/fixtures-diagnostics/consent-wall.html");
+        let depart = chrono::Local::now().date_naive() + chrono::Duration::days(7);
+        let params = FlightSearchParams::builder("SFO".into(), "JFK".into(), depart)
+            .trip_type(Trip::OneWay)
+            .build()
+            .unwrap();
+
+        // The server answers once; a retry would fail to connect instead.
+        let (url, request) = spawn_echo_server(consent).await;
+        let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 10)
+            .unwrap()
+            .base_url(&url)
+            .retry_on_empty(2);
+        let err = client.search_flights(&params).await.unwrap_err();
+        assert!(format!("{err:#}").contains("Consent wall"), "{err:#}");
+        request.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_low_results_warning() {
+        let one_card = r#"<html><body><div jsname="YdtKid"><ul class="Rk10dc"><li>
+            <div class="sSHqwe tPgKwe ogfYpf"><span>Delta</span></div>
+            <span class="mv1WYe"><div>8:00 AM</div><div>4:30 PM</div></span>
+            <div class="Ak5kof"><div>7 hr 30 min</div></div>
+            <div class="YMlIz FpEdX">$350</div>
+        </li></ul></div></body></html>"#;
+        let depart = chrono::Local::now().date_naive() + chrono::Duration::days(7);
+        let params = FlightSearchParams::builder("JFK".into(), "CDG".into(), depart)
+            .trip_type(Trip::OneWay)
+            .build()
+            .unwrap();
+
+        let (url, _server) = spawn_echo_server(one_card).await;
+        let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 10)
+            .unwrap()
+            .base_url(&url)
+            .expect_results(Some(ResultExpectations::default()));
+        let result = client.search_flights(&params).await.unwrap();
+        assert_eq!(result.itineraries.len(), 1);
+        assert!(
+            result
+                .warnings
+                .iter()
+                .any(|w| w.starts_with("Only 1 itinerary(ies) for JFK→CDG, a long-haul route")),
+            "{:?}",
+            result.warnings
+        );
+
+        // Opt-in: without expectations the same page does not warn
+        let (url, _server) = spawn_echo_server(one_card).await;
+        let client = client.base_url(&url).expect_results(None);
+        let result = client.search_flights(&params).await.unwrap();
+        assert!(!result.warnings.iter().any(|w| w.starts_with("Only ")));
+    }
+
+    #[tokio::test]
+    async fn test_search_stream_matches_search() {
+        let compressed =
+            include_bytes!("../tests/fixtures-flights-parsing/nonstop-sfo_jfk_economy.html.zst");
+        let html = String::from_utf8(zstd::decode_all(&compressed[..]).unwrap()).unwrap();
+        let html: &'static str = Box::leak(html.into_boxed_str());
+        let depart = chrono::Local::now().date_naive() + chrono::Duration::days(7);
+        let params = FlightSearchParams::builder("SFO".into(), "JFK".into(), depart)
+            .trip_type(Trip::OneWay)
+            .build()
+            .unwrap();
+
+        let (url, _requests) = spawn_sequence_server(vec![html, html, html]).await;
+        let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 10)
+            .unwrap()
+            .base_url(&url);
+        let collected: Vec<String> = client
+            .search_flights(&params)
+            .await
+            .unwrap()
+            .itineraries
+            .into_iter()
+            .map(|it| it.id)
+            .collect();
+        assert!(collected.len() > 3);
+
+        let streamed: Vec<String> = client
+            .search_stream(&params)
+            .map(|it| it.unwrap().id)
+            .collect()
+            .await;
+        assert_eq!(streamed, collected);
+
+        let first: Vec<String> = client
+            .search_stream(&params)
+            .take(3)
+            .map(|it| it.unwrap().id)
+            .collect()
+            .await;
+        assert_eq!(first, collected[..3]);
+
+        let (url, _server) = spawn_echo_server("<html><body>Redesigned</body></html>").await;
+        let client = client.base_url(&url);
+        let mut stream = std::pin::pin!(client.search_stream(&params));
+        assert!(stream.next().await.unwrap().is_err());
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_mobile_layout_detected() {
+        let mobile = include_str!("../tests/fixtures-diagnostics/mobile-layout.html");
+        let depart = chrono::Local::now().date_naive() + chrono::Duration::days(7);
+        let params = FlightSearchParams::builder("SFO".into(), "JFK".into(), depart)
+            .trip_type(Trip::OneWay)
+            .build()
+            .unwrap();
+
+        let (url, _server) = spawn_echo_server(mobile).await;
+        let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 10)
+            .unwrap()
+            .base_url(&url);
+        let err = client
+            .search_cancellable(&params, CancellationToken::new())
+            .await
+            .unwrap_err();
+        let FlightsError::UnexpectedLayout(layout) = err else {
+            panic!("{err:?}");
+        };
+        assert_eq!(layout.layout, "FlightsFrontendUi_mobile_ms");
+
+        let diagnosis = client.diagnose_last_search().unwrap();
+        assert!(diagnosis.mobile_layout);
+        assert!(diagnosis.next_steps[0].contains("mobile layout"));
+
+        let compressed =
+            include_bytes!("../tests/fixtures-flights-parsing/nonstop-sfo_jfk_economy.html.zst");
+        let desktop = String::from_utf8(zstd::decode_all(&compressed[..]).unwrap()).unwrap();
+        assert_eq!(non_desktop_layout(&desktop), None);
+    }
+
+    #[tokio::test]
+    async fn test_keep_raw_response() {
+        let compressed =
+            include_bytes!("../tests/fixtures-flights-parsing/nonstop-sfo_jfk_economy.html.zst");
+        let html = String::from_utf8(zstd::decode_all(&compressed[..]).unwrap()).unwrap();
+        let html: &'static str = Box::leak(html.into_boxed_str());
+        let depart = chrono::Local::now().date_naive() + chrono::Duration::days(7);
+        let params = FlightSearchParams::builder("SFO".into(), "JFK".into(), depart)
+            .trip_type(Trip::OneWay)
+            .build()
+            .unwrap();
+
+        let (url, _requests) = spawn_sequence_server(vec![html, html]).await;
+        let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 10)
+            .unwrap()
+            .base_url(&url);
+        let kept = client.search_flights(&params).await.unwrap();
+        assert_eq!(kept.raw_response, html);
+
+        let client = client.keep_raw_response(false);
+        let stripped = client.search_flights(&params).await.unwrap();
+        assert!(stripped.raw_response.is_empty());
+        assert_eq!(stripped.itineraries.len(), kept.itineraries.len());
+    }
+
+    #[tokio::test]
+    async fn test_retry_count_reported() {
+        let card = r#"<html><body><div jsname="YdtKid"><ul class="Rk10dc"><li>
+            <div class="sSHqwe tPgKwe ogfYpf"><span>JetBlue</span></div>
+            <span class="mv1WYe"><div>8:30 PM</div><div>5:01 AM</div></span>
+            <div class="Ak5kof"><div>5 hr 31 min</div></div>
+            <div class="YMlIz FpEdX">$284</div>
+        </li></ul></div></body></html>"#;
+        let depart = chrono::Local::now().date_naive() + chrono::Duration::days(7);
+        let params = FlightSearchParams::builder("SFO".into(), "JFK".into(), depart)
+            .trip_type(Trip::OneWay)
+            .build()
+            .unwrap();
+
+        let (url, _request) = spawn_flaky_server(card).await;
+        let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 10)
+            .unwrap()
+            .base_url(&url);
+        let result = client.search_flights(&params).await.unwrap();
+
+        assert_eq!(result.itineraries.len(), 1);
+        assert_eq!(result.retry_count, Some(1));
+        let response = result.to_mcp_api_response(Vec::new());
+        assert_eq!(response.search_flights.query.retry_count, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_search_cancellable_releases_permit() {
+        let depart = chrono::Local::now().date_naive() + chrono::Duration::days(7);
+        let params = FlightSearchParams::builder("SFO".into(), "JFK".into(), depart)
+            .trip_type(Trip::OneWay)
+            .build()
+            .unwrap();
+        // Every attempt is refused, so the search keeps retrying with backoff.
+        let url = refused_url().await;
+        let queue = QueryQueue::with_concurrency_limit(1);
+        let client = GoogleFlightsClient::new("en".into(), "USD".into(), 30, 10)
+            .unwrap()
+            .base_url(&url)
+            .query_queue(queue.clone());
+
+        let token = CancellationToken::new();
+        let search = tokio::spawn({
+            let token = token.clone();
+            async move { client.search_cancellable(&params, token).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(
+            queue.available_permits(),
+            0,
+            "the retrying search holds the permit"
+        );
+
+        token.cancel();
+        let result = tokio::time::timeout(Duration::from_millis(100), search)
+            .await
+            .expect("cancellation should be prompt")
+            .unwrap();
+        assert!(matches!(result, Err(FlightsError::Cancelled)), "{result:?}");
+        assert_eq!(queue.available_permits(), 1);
+    }
+}
@@ -0,0 +1,223 @@
+//!  Delulu Travel Agent
+//!
+//!  Copyright (C) 2026  Mamy Ratsimbazafy
+//!
+//!  This program is free software: you can redistribute it and/or modify
+//!  it under the terms of the GNU Affero General Public License as published by
+//!  the Free Software Foundation, either version 3 of the License, or
+//!  (at your option) any later version.
+//!
+//!  This program is distributed in the hope that it will be useful,
+//!  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//!  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//!  GNU Affero General Public License for more details.
+//!
+//!  You should have received a copy of the GNU Affero General Public License
+//!  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Hotel Preferences
+//!
+//! Maps a free-text preference note such as "pet friendly with a pool" onto
+//! the structured hotel search filters. A convenience for LLM callers: the
+//! structured fields stay authoritative.
+
+use crate::hotels_query_builder::Amenity;
+
+/// What a preference phrase maps to.
+#[derive(Debug, Clone, Copy)]
+enum Mapping {
+    /// A search filter.
+    Amenity(Amenity),
+    /// A card chip, for features Google offers no search filter for.
+    Chip(&'static str),
+}
+
+/// Known phrases, as lowercase words with hyphens replaced by spaces.
+/// Longer phrases are tried first, so "indoor pool" wins over "pool".
+const PHRASES: &[(&str, Mapping)] = &[
+    ("indoor pool", Mapping::Amenity(Amenity::IndoorPool)),
+    ("outdoor pool", Mapping::Amenity(Amenity::OutdoorPool)),
+    ("swimming pool", Mapping::Amenity(Amenity::Pool)),
+    ("pool", Mapping::Amenity(Amenity::Pool)),
+    ("spa", Mapping::Amenity(Amenity::Spa)),
+    ("kid friendly", Mapping::Amenity(Amenity::KidFriendly)),
+    ("family friendly", Mapping::Amenity(Amenity::KidFriendly)),
+    (
+        "air conditioning",
+        Mapping::Amenity(Amenity::AirConditioned),
+    ),
+    ("air conditioned", Mapping::Amenity(Amenity::AirConditioned)),
+    ("ev charger", Mapping::Amenity(Amenity::EvCharger)),
+    ("ev charging", Mapping::Amenity(Amenity::EvCharger)),
+    ("pet friendly", Mapping::Chip("Pet-friendly")),
+    ("dog friendly", Mapping::Chip("Pet-friendly")),
+    ("pets allowed", Mapping::Chip("Pet-friendly")),
+    ("free wifi", Mapping::Chip("Free Wi-Fi")),
+    ("free wi fi", Mapping::Chip("Free Wi-Fi")),
+    ("wifi", Mapping::Chip("Wi-Fi")),
+    ("breakfast", Mapping::Chip("Breakfast")),
+    ("parking", Mapping::Chip("Parking")),
+    ("fitness center", Mapping::Chip("Fitness center")),
+    ("gym", Mapping::Chip("Fitness center")),
+    ("restaurant", Mapping::Chip("Restaurant")),
+    ("bar", Mapping::Chip("Bar")),
+    ("wheelchair accessible", Mapping::Chip("Accessible")),
+    ("accessible", Mapping::Chip("Accessible")),
+];
+
+/// Filler words dropped from unmatched text before it is reported.
+const FILLER: &[&str] = &[
+    "a",
+    "an",
+    "and",
+    "the",
+    "with",
+    "has",
+    "having",
+    "have",
+    "that",
+    "is",
+    "in",
+    "near",
+    "or",
+    "some",
+    "please",
+    "i",
+    "want",
+    "would",
+    "like",
+    "prefer",
+    "preferably",
+    "ideally",
+    "hotel",
+    "hotels",
+    "place",
+    "room",
+    "rooms",
+];
+
+const STAR_WORDS: [&str; 4] = ["two", "three", "four", "five"];
+
+/// Filters read from a free-text preference note.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HotelPreferences {
+    pub amenities: Vec<Amenity>,
+    pub stars: Vec<i32>,
+    /// Card chips a hotel must show, as Google labels them, for features
+    /// without a search filter. See [`HotelSearchResult::require_chips`].
+    ///
+    /// [`HotelSearchResult::require_chips`]: crate::HotelSearchResult::require_chips
+    pub chips: Vec<String>,
+    /// Phrases that matched nothing, in the order they appeared.
+    pub unmapped: Vec<String>,
+}
+
+impl HotelPreferences {
+    /// Matches `note` against amenity names, common feature phrases and
+    /// star ratings ("4-star", "five star").
+    pub fn parse(note: &str) -> Self {
+        let normalized: String = note
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+            .collect();
+        let words: Vec<&str> = normalized.split_whitespace().collect();
+
+        let mut prefs = Self::default();
+        let mut leftover: Vec<&str> = Vec::new();
+        let mut i = 0;
+        while i < words.len() {
+            if let Some(stars) = star_rating(&words[i..]) {
+                prefs.flush(&mut leftover);
+                if !prefs.stars.contains(&stars) {
+                    prefs.stars.push(stars);
+                }
+                i += 2;
+                continue;
+            }
+            let matched = PHRASES
+                .iter()
+                .filter(|(phrase, _)| {
+                    let phrase: Vec<&str> = phrase.split(' ').collect();
+                    words[i..].starts_with(&phrase)
+                })
+                .max_by_key(|(phrase, _)| phrase.split(' ').count());
+            match matched {
+                Some((phrase, mapping)) => {
+                    prefs.flush(&mut leftover);
+                    prefs.add(*mapping);
+                    i += phrase.split(' ').count();
+                }
+                None => {
+                    leftover.push(words[i]);
+                    i += 1;
+                }
+            }
+        }
+        prefs.flush(&mut leftover);
+        prefs
+    }
+
+    fn add(&mut self, mapping: Mapping) {
+        match mapping {
+            Mapping::Amenity(amenity) if !self.amenities.contains(&amenity) => {
+                self.amenities.push(amenity)
+            }
+            Mapping::Chip(chip) if !self.chips.iter().any(|c| c == chip) => {
+                self.chips.push(chip.to_string())
+            }
+            _ => {}
+        }
+    }
+
+    /// Records the unmatched words since the last match as one phrase.
+    fn flush(&mut self, leftover: &mut Vec<&str>) {
+        let phrase: Vec<&str> = leftover.drain(..).filter(|w| !FILLER.contains(w)).collect();
+        if !phrase.is_empty() {
+            self.unmapped.push(phrase.join(" "));
+        }
+    }
+}
+
+/// Star rating at the start of `words`, e.g. "4 star" or "five stars".
+fn star_rating(words: &[&str]) -> Option<i32> {
+    let [count, unit, ..] = words else {
+        return None;
+    };
+    if !matches!(*unit, "star" | "stars") {
+        return None;
+    }
+    let stars = count.parse().ok().or_else(|| {
+        STAR_WORDS
+            .iter()
+            .position(|w| w == count)
+            .map(|i| i as i32 + 2)
+    })?;
+    (2..=5).contains(&stars).then_some(stars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_pet_and_pool_and_keeps_beachfront() {
+        let prefs = HotelPreferences::parse("pet friendly beachfront with a pool");
+        assert_eq!(prefs.amenities, [Amenity::Pool]);
+        assert_eq!(prefs.chips, ["Pet-friendly"]);
+        assert_eq!(prefs.unmapped, ["beachfront"]);
+    }
+
+    #[test]
+    fn longest_phrase_and_stars() {
+        let prefs =
+            HotelPreferences::parse("Quiet 4-star, indoor pool & free Wi-Fi, or five stars");
+        assert_eq!(prefs.amenities, [Amenity::IndoorPool]);
+        assert_eq!(prefs.chips, ["Free Wi-Fi"]);
+        assert_eq!(prefs.stars, [4, 5]);
+        assert_eq!(prefs.unmapped, ["quiet"]);
+
+        assert_eq!(HotelPreferences::parse(""), HotelPreferences::default());
+        assert_eq!(HotelPreferences::parse("9 stars").unmapped, ["9 stars"]);
+    }
+}
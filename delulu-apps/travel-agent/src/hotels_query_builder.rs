@@ -30,6 +30,11 @@ use chrono::{Datelike, NaiveDate};
 use prost::Message;
 use serde::{Deserialize, Serialize};
 
+use crate::currency::Currency;
+use crate::decode_error::DecodeError;
+use crate::language::Language;
+use crate::proto_debug::dump_protobuf;
+
 use proto::{Amenity as AmenityProto, SortType as SortTypeProto};
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -83,31 +88,130 @@ impl TryFrom<i32> for Amenity {
 }
 
 impl Amenity {
+    /// Every amenity filter, in declaration order.
+    pub const ALL: [Amenity; 7] = [
+        Amenity::IndoorPool,
+        Amenity::OutdoorPool,
+        Amenity::Pool,
+        Amenity::Spa,
+        Amenity::KidFriendly,
+        Amenity::AirConditioned,
+        Amenity::EvCharger,
+    ];
+
+    pub fn all() -> &'static [Amenity] {
+        &Self::ALL
+    }
+
+    /// Names accepted by [`Self::from_str_name`] (case-insensitive), the
+    /// canonical [`Self::as_str_name`] first.
+    pub fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            Amenity::IndoorPool => &["indoor_pool", "indoorpool", "indoor"],
+            Amenity::OutdoorPool => &["outdoor_pool", "outdoorpool", "outdoor"],
+            Amenity::Pool => &["pool"],
+            Amenity::Spa => &["spa"],
+            Amenity::KidFriendly => &["kid_friendly", "kidfriendly", "kid"],
+            Amenity::AirConditioned => &["air_conditioned", "airconditioned", "ac"],
+            Amenity::EvCharger => &["ev_charger", "evcharger", "ev"],
+        }
+    }
+
     pub fn from_str_name(s: &str) -> Option<Self> {
         let lower = s.to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|amenity| amenity.aliases().contains(&lower.as_str()))
+    }
+
+    /// Up to two amenities `unknown` was plausibly meant to be, best first.
+    ///
+    /// The whole name and each of its words are compared to every alias and
+    /// alias word, allowing one typo per three letters of the alias part, so
+    /// "swiming_pool" suggests `pool` then `indoor_pool` while an unrelated
+    /// word suggests nothing.
+    pub fn suggestions(unknown: &str) -> Vec<Amenity> {
+        let lower = unknown.to_lowercase();
+        let words = |s: &str| -> Vec<String> {
+            let mut parts: Vec<String> = s
+                .split(['_', '-', ' '])
+                .filter(|w| !w.is_empty())
+                .map(str::to_string)
+                .collect();
+            if parts.len() > 1 {
+                parts.push(s.to_string());
+            }
+            parts
+        };
+        let input_parts = words(&lower);
+        let input_parts = if input_parts.is_empty() {
+            vec![lower.clone()]
+        } else {
+            input_parts
+        };
+
+        // (typos, matched only part of an alias, whole-name distance)
+        let mut scored: Vec<((usize, bool, usize), Amenity)> = Self::ALL
+            .into_iter()
+            .filter_map(|amenity| {
+                let best = amenity
+                    .aliases()
+                    .iter()
+                    .flat_map(|alias| {
+                        words(alias).into_iter().map(move |part| {
+                            let partial = part != *alias;
+                            (part, partial)
+                        })
+                    })
+                    .flat_map(|(part, partial)| {
+                        input_parts.iter().filter_map(move |input| {
+                            let typos = levenshtein(input, &part);
+                            (typos <= part.chars().count() / 3).then_some((typos, partial))
+                        })
+                    })
+                    .min()?;
+                let whole = levenshtein(&lower, amenity.as_str_name());
+                Some(((best.0, best.1, whole), amenity))
+            })
+            .collect();
+        scored.sort_by_key(|(score, _)| *score);
+        scored.into_iter().take(2).map(|(_, a)| a).collect()
+    }
+
+    /// Maps an amenity chip label shown on a hotel card (e.g. "Kid-friendly",
+    /// "Air conditioning") to the matching filter, if any.
+    pub fn from_chip_label(label: &str) -> Option<Self> {
+        let lower = label.trim().to_lowercase();
         match lower.as_str() {
-            "indoor_pool" | "indoorpool" | "indoor" => Some(Amenity::IndoorPool),
-            "outdoor_pool" | "outdoorpool" | "outdoor" => Some(Amenity::OutdoorPool),
-            "pool" => Some(Amenity::Pool),
+            "indoor pool" => Some(Amenity::IndoorPool),
+            "outdoor pool" => Some(Amenity::OutdoorPool),
+            "pool" | "pools" => Some(Amenity::Pool),
             "spa" => Some(Amenity::Spa),
-            "kid_friendly" | "kidfriendly" | "kid" => Some(Amenity::KidFriendly),
-            "air_conditioned" | "airconditioned" | "ac" => Some(Amenity::AirConditioned),
-            "ev_charger" | "evcharger" | "ev" => Some(Amenity::EvCharger),
+            "kid-friendly" | "kid friendly" => Some(Amenity::KidFriendly),
+            "air conditioning" | "air-conditioned" => Some(Amenity::AirConditioned),
+            "ev charger" | "electric vehicle charging station" => Some(Amenity::EvCharger),
             _ => None,
         }
     }
 
     pub fn as_str_name(&self) -> &'static str {
-        match self {
-            Amenity::IndoorPool => "indoor_pool",
-            Amenity::OutdoorPool => "outdoor_pool",
-            Amenity::Pool => "pool",
-            Amenity::Spa => "spa",
-            Amenity::KidFriendly => "kid_friendly",
-            Amenity::AirConditioned => "air_conditioned",
-            Amenity::EvCharger => "ev_charger",
+        self.aliases()[0]
+    }
+}
+
+/// Edit distance between `a` and `b`, in characters.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
         }
+        previous = current;
     }
+    previous[b.len()]
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -183,6 +287,9 @@ pub struct HotelSearchParams {
     pub nights: i32,
     pub used_guests_dropdown: i32,
     pub currency: String,
+    /// Page language sent as `hl`, e.g. "en". Not encoded in `ts`.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub language: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub sort_order: Option<SortType>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -201,9 +308,49 @@ impl HotelSearchParams {
         &self.loc_q_search
     }
 
-    fn validate(&self) -> Result<()> {
+    /// Drops the filter most likely to have emptied a search: the price
+    /// range, else the amenities, else the hotel stars. Returns what was
+    /// dropped, e.g. "price (max 200)", or `None` if none of them is set.
+    pub fn relax_most_restrictive_filter(&mut self) -> Option<String> {
+        let price_range = match (self.min_price.take(), self.max_price.take()) {
+            (Some(min), Some(max)) => Some(format!("{min}-{max}")),
+            (Some(min), None) => Some(format!("min {min}")),
+            (None, Some(max)) => Some(format!("max {max}")),
+            (None, None) => None,
+        };
+        if let Some(range) = price_range {
+            return Some(format!("price ({range})"));
+        }
+        if !self.amenities.is_empty() {
+            let names: Vec<&str> = self.amenities.iter().map(Amenity::as_str_name).collect();
+            self.amenities.clear();
+            return Some(format!("amenities ({})", names.join(", ")));
+        }
+        if !self.hotel_stars.is_empty() {
+            let stars: Vec<String> = self.hotel_stars.iter().map(i32::to_string).collect();
+            self.hotel_stars.clear();
+            return Some(format!("stars ({})", stars.join(", ")));
+        }
+        None
+    }
+
+    pub fn validate(&self) -> Result<()> {
         let total_guests = self.adults + self.children_ages.len() as u32;
         ensure!(self.adults >= 1, "At least one adult is required");
+        if !self.currency.is_empty() {
+            ensure!(
+                Currency::new(&self.currency)?.as_str() == self.currency,
+                "Currency must be an uppercase ISO 4217 code, got '{}'",
+                self.currency
+            );
+        }
+        if !self.language.is_empty() {
+            ensure!(
+                Language::new(&self.language)?.as_str() == self.language,
+                "Language must be a normalized code such as en or en-GB, got '{}'",
+                self.language
+            );
+        }
         ensure!(total_guests <= 6, "Maximum 6 guests allowed");
         ensure!(
             self.children_ages
@@ -256,16 +403,24 @@ impl HotelSearchParams {
             adults,
             children_ages,
             currency: None,
+            language: None,
             min_guest_rating: None,
             hotel_stars: Vec::new(),
             amenities: Vec::new(),
             min_price: None,
             max_price: None,
             sort_order: None,
+            max_advance_days: None,
         }
     }
 
+    /// The `ts` URL parameter: [`Self::ts_bytes`] in URL-safe base64.
     pub fn generate_ts(&self) -> Result<String> {
+        Ok(URL_SAFE_NO_PAD.encode(self.ts_bytes()?))
+    }
+
+    /// Raw protobuf bytes behind the `ts` URL parameter.
+    pub fn ts_bytes(&self) -> Result<Vec<u8>> {
         self.validate()?;
         let checkin = NaiveDate::parse_from_str(&self.checkin_date, "%Y-%m-%d")
             .context(format!("Invalid checkin date: {}", self.checkin_date))?;
@@ -364,24 +519,43 @@ impl HotelSearchParams {
         params
             .encode(&mut bytes)
             .context("Failed to encode protobuf")?;
-        Ok(URL_SAFE_NO_PAD.encode(&bytes))
+        Ok(bytes)
     }
 
+    /// Google Hotels search URL. `curr` and `hl` are added when `currency`
+    /// and `language` are set, so that the page is rendered in the currency
+    /// encoded in `ts`.
     pub fn get_search_url(&self) -> String {
         let ts_param = self.generate_ts().expect("TS encoding should work");
         let encoded_location = urlencoding::encode(&self.loc_q_search);
-        format!(
+        let mut url = format!(
             "https://www.google.com/travel/search?q={}&ts={}",
             encoded_location, ts_param
-        )
+        );
+        if !self.currency.is_empty() {
+            url.push_str(&format!("&curr={}", urlencoding::encode(&self.currency)));
+        }
+        if !self.language.is_empty() {
+            url.push_str(&format!("&hl={}", urlencoding::encode(&self.language)));
+        }
+        url
     }
 
-    pub fn from_ts(ts_base64: &str) -> Result<Self> {
+    /// Field-by-field dump of the protobuf inside a `ts` parameter, including
+    /// fields [`Self::from_ts`] ignores. For reverse-engineering.
+    pub fn decode_ts_debug(ts_base64: &str) -> Result<String> {
         let ts_bytes = URL_SAFE_NO_PAD
             .decode(ts_base64)
             .map_err(|e| anyhow::anyhow!("Failed to decode base64: {}", e))?;
-        let params = proto::ProtoHotelSearch::decode(ts_bytes.as_slice())
-            .context("Failed to decode protobuf")?;
+        dump_protobuf(&ts_bytes).context("Failed to decode protobuf")
+    }
+
+    /// Decodes a `ts` URL parameter back into search parameters.
+    ///
+    /// `language` is left empty: it travels in the `hl` URL parameter.
+    pub fn from_ts(ts_base64: &str) -> Result<Self, DecodeError> {
+        let ts_bytes = URL_SAFE_NO_PAD.decode(ts_base64)?;
+        let params = proto::ProtoHotelSearch::decode(ts_bytes.as_slice())?;
 
         let guests = params.guests.as_ref();
         let search_params = params.search_params.as_ref();
@@ -487,6 +661,18 @@ impl HotelSearchParams {
             }
         }
 
+        // Canonical order, so decoded params compare equal whatever order the
+        // filters were encoded in.
+        amenities.sort_by_key(|&a| a as i32);
+        hotel_stars.sort_unstable();
+
+        if checkin_date.is_empty() {
+            return Err(DecodeError::MissingRequiredField("checkin_date"));
+        }
+        if checkout_date.is_empty() {
+            return Err(DecodeError::MissingRequiredField("checkout_date"));
+        }
+
         Ok(HotelSearchParams {
             version: params.version,
             adults,
@@ -500,6 +686,7 @@ impl HotelSearchParams {
             nights,
             used_guests_dropdown: guests.map_or(0, |g| g.explicit_selection as i32),
             currency,
+            language: String::new(),
             sort_order,
             min_guest_rating,
             hotel_stars,
@@ -518,20 +705,46 @@ pub struct HotelSearchParamsBuilder {
     adults: u32,
     children_ages: Vec<i32>,
     currency: Option<String>,
+    language: Option<String>,
     min_guest_rating: Option<f64>,
     hotel_stars: Vec<i32>,
     amenities: Vec<Amenity>,
     min_price: Option<i32>,
     max_price: Option<i32>,
     sort_order: Option<SortType>,
+    max_advance_days: Option<u32>,
 }
 
 impl HotelSearchParamsBuilder {
+    /// Stay of `n` nights starting on `checkin`.
+    pub fn nights(mut self, checkin: NaiveDate, n: u32) -> Self {
+        self.checkin_date = checkin;
+        self.checkout_date = checkin + chrono::Days::new(n as u64);
+        self
+    }
+
+    /// Friday-to-Sunday stay on the first Friday on or after `after`.
+    pub fn weekend(self, after: NaiveDate) -> Self {
+        let days_to_friday = (chrono::Weekday::Fri.num_days_from_monday() + 7
+            - after.weekday().num_days_from_monday())
+            % 7;
+        self.nights(after + chrono::Days::new(days_to_friday as u64), 2)
+    }
+
+    /// Currency sent as `curr`, an ISO 4217 code in any case; validated and
+    /// uppercased by [`Self::build`]. Empty leaves `curr` out.
     pub fn currency(mut self, currency: String) -> Self {
         self.currency = Some(currency);
         self
     }
 
+    /// Page language sent as `hl`, e.g. "en" or "fr"; validated and
+    /// normalized by [`Self::build`]. Empty leaves `hl` out.
+    pub fn language(mut self, language: String) -> Self {
+        self.language = Some(language);
+        self
+    }
+
     pub fn min_guest_rating(mut self, rating: f64) -> Self {
         self.min_guest_rating = Some(rating);
         self
@@ -562,7 +775,24 @@ impl HotelSearchParamsBuilder {
         self
     }
 
+    /// Reject check-in or checkout dates more than `days` days from today.
+    /// Unbounded (`None`) by default.
+    pub fn max_advance_days(mut self, days: Option<u32>) -> Self {
+        self.max_advance_days = days;
+        self
+    }
+
     pub fn build(self) -> Result<HotelSearchParams> {
+        if let Some(days) = self.max_advance_days {
+            let latest = chrono::Local::now().date_naive() + chrono::Days::new(days as u64);
+            let last_date = self.checkout_date.max(self.checkin_date);
+            ensure!(
+                last_date <= latest,
+                "Dates more than {} days ahead are not allowed, got {}",
+                days,
+                last_date
+            );
+        }
         let params = HotelSearchParams {
             version: 1,
             adults: self.adults,
@@ -575,7 +805,14 @@ impl HotelSearchParamsBuilder {
             checkout_date: self.checkout_date.format("%Y-%m-%d").to_string(),
             nights: (self.checkout_date - self.checkin_date).num_days() as i32,
             used_guests_dropdown: 0,
-            currency: self.currency.unwrap_or_default(),
+            currency: match self.currency {
+                Some(code) if !code.is_empty() => Currency::new(&code)?.into(),
+                _ => String::new(),
+            },
+            language: match self.language {
+                Some(code) if !code.is_empty() => Language::new(&code)?.into(),
+                _ => String::new(),
+            },
             sort_order: self.sort_order,
             min_guest_rating: self.min_guest_rating,
             hotel_stars: self.hotel_stars,
@@ -603,6 +840,212 @@ mod tests {
         assert_eq!(decoded.checkout_date, "2026-01-31");
     }
 
+    #[test]
+    fn decode_paris_basic_debug() {
+        let ts = "CAEaIAoCGgASGhIUCgcI6g8QARgZEgcI6g8QARgfGAYyAggBKgkKBToDRVVSGgA";
+        let dump = HotelSearchParams::decode_ts_debug(ts).unwrap();
+        assert!(dump.starts_with("1: 1\n"), "{dump}");
+        // Check-in and checkout dates as {year, month, day}.
+        assert!(dump.contains("1: 2026\n"), "{dump}");
+        assert!(dump.contains("3: 25\n"), "{dump}");
+        assert!(dump.contains("3: 31\n"), "{dump}");
+        assert!(dump.contains("\"EUR\""), "{dump}");
+    }
+
+    #[test]
+    fn decode_malformed_ts() {
+        let ts = "CAEaIAoCGgASGhIUCgcI6g8QARgZEgcI6g8QARgfGAYyAggBKgkKBToDRVVSGgA";
+        assert!(matches!(
+            HotelSearchParams::from_ts("CAE=="),
+            Err(DecodeError::InvalidBase64(_))
+        ));
+        // Cut inside the search parameters: their length overruns the data.
+        assert!(matches!(
+            HotelSearchParams::from_ts(&ts[..20]),
+            Err(DecodeError::InvalidProtobuf(_))
+        ));
+        // Cut right after the version field: well-formed, but no dates.
+        assert!(matches!(
+            HotelSearchParams::from_ts(&ts[..3]),
+            Err(DecodeError::MissingRequiredField("checkin_date"))
+        ));
+    }
+
+    #[test]
+    fn amenity_names_round_trip() {
+        for amenity in Amenity::all() {
+            // `all()` lists every variant exactly once: the proto value agrees.
+            assert_eq!(Amenity::try_from(*amenity as i32), Ok(*amenity));
+            assert_eq!(
+                Amenity::from_str_name(amenity.as_str_name()),
+                Some(*amenity)
+            );
+            for alias in amenity.aliases() {
+                assert_eq!(Amenity::from_str_name(alias), Some(*amenity), "{alias}");
+                assert_eq!(
+                    Amenity::from_str_name(&alias.to_uppercase()),
+                    Some(*amenity)
+                );
+            }
+        }
+        let values: std::collections::HashSet<i32> =
+            Amenity::all().iter().map(|a| *a as i32).collect();
+        assert_eq!(values.len(), Amenity::all().len());
+        assert_eq!(Amenity::from_str_name("sauna"), None);
+    }
+
+    #[test]
+    fn amenity_suggestions() {
+        assert_eq!(
+            Amenity::suggestions("swiming_pool"),
+            [Amenity::Pool, Amenity::IndoorPool]
+        );
+        assert_eq!(Amenity::suggestions("kid_frendly"), [Amenity::KidFriendly]);
+        assert_eq!(Amenity::suggestions("spaa"), [Amenity::Spa]);
+        for unrelated in ["wifi", "gym", "breakfast", "parking", "xyzzy"] {
+            assert_eq!(Amenity::suggestions(unrelated), [], "{unrelated}");
+        }
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn relax_drops_price_then_amenities_then_stars() {
+        let mut params = paris_builder()
+            .nights(NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(), 3)
+            .hotel_stars(vec![4, 5])
+            .amenities(vec![Amenity::Pool, Amenity::Spa])
+            .max_price(Some(200))
+            .build()
+            .unwrap();
+
+        let relaxed: Vec<_> =
+            std::iter::from_fn(|| params.relax_most_restrictive_filter()).collect();
+        assert_eq!(
+            relaxed,
+            ["price (max 200)", "amenities (pool, spa)", "stars (4, 5)"]
+        );
+        assert_eq!(params.relax_most_restrictive_filter(), None);
+    }
+
+    #[test]
+    fn ts_bytes_round_trip() {
+        let params = paris_builder()
+            .nights(NaiveDate::from_ymd_opt(2026, 1, 25).unwrap(), 6)
+            .amenities(vec![Amenity::Spa])
+            .build()
+            .unwrap();
+
+        let bytes = params.ts_bytes().unwrap();
+        assert_eq!(
+            URL_SAFE_NO_PAD
+                .decode(params.generate_ts().unwrap())
+                .unwrap(),
+            bytes
+        );
+        let decoded = proto::ProtoHotelSearch::decode(bytes.as_slice()).unwrap();
+        let filters = decoded.filter_config.unwrap().filters.unwrap();
+        assert_eq!(filters.amenity, [Amenity::Spa as i32]);
+    }
+
+    fn paris_builder() -> HotelSearchParamsBuilder {
+        let date = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        HotelSearchParams::builder("Paris".to_string(), date, date, 2, Vec::new())
+    }
+
+    #[test]
+    fn weekend_from_wednesday() {
+        // 2026-01-21 is a Wednesday
+        let params = paris_builder()
+            .weekend(NaiveDate::from_ymd_opt(2026, 1, 21).unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(params.checkin_date, "2026-01-23");
+        assert_eq!(params.checkout_date, "2026-01-25");
+        assert_eq!(params.nights, 2);
+    }
+
+    #[test]
+    fn weekend_from_friday_starts_same_day() {
+        let params = paris_builder()
+            .weekend(NaiveDate::from_ymd_opt(2026, 1, 23).unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(params.checkin_date, "2026-01-23");
+        assert_eq!(params.checkout_date, "2026-01-25");
+    }
+
+    #[test]
+    fn search_url_carries_curr_and_hl() {
+        let date = NaiveDate::from_ymd_opt(2026, 1, 30).unwrap();
+        let url = paris_builder()
+            .nights(date, 3)
+            .currency("EUR".into())
+            .language("fr".into())
+            .build()
+            .unwrap()
+            .get_search_url();
+        assert!(url.contains("&curr=EUR"), "{url}");
+        assert!(url.contains("&hl=fr"), "{url}");
+
+        let url = paris_builder()
+            .nights(date, 3)
+            .build()
+            .unwrap()
+            .get_search_url();
+        assert!(!url.contains("curr="), "{url}");
+        assert!(!url.contains("hl="), "{url}");
+    }
+
+    #[test]
+    fn currency_is_normalized_or_rejected() {
+        let date = NaiveDate::from_ymd_opt(2026, 1, 30).unwrap();
+        let params = paris_builder()
+            .nights(date, 3)
+            .currency("eur".into())
+            .build()
+            .unwrap();
+        assert_eq!(params.currency, "EUR");
+        assert!(params.get_search_url().contains("&curr=EUR"));
+
+        for invalid in ["Dollars", "US$", "xyz"] {
+            let err = paris_builder()
+                .nights(date, 3)
+                .currency(invalid.into())
+                .build()
+                .unwrap_err();
+            assert!(err.is::<crate::CurrencyError>(), "{err:?}");
+        }
+    }
+
+    #[test]
+    fn language_is_normalized_or_rejected() {
+        let date = NaiveDate::from_ymd_opt(2026, 1, 30).unwrap();
+        let params = paris_builder()
+            .nights(date, 3)
+            .language("FR_fr".into())
+            .build()
+            .unwrap();
+        assert!(params.get_search_url().contains("&hl=fr-FR"));
+
+        let err = paris_builder()
+            .nights(date, 3)
+            .language("french".into())
+            .build()
+            .unwrap_err();
+        assert!(err.is::<crate::LanguageError>(), "{err:?}");
+    }
+
+    #[test]
+    fn nights_sets_checkout() {
+        let params = paris_builder()
+            .nights(NaiveDate::from_ymd_opt(2026, 1, 30).unwrap(), 3)
+            .build()
+            .unwrap();
+        assert_eq!(params.checkin_date, "2026-01-30");
+        assert_eq!(params.checkout_date, "2026-02-02");
+        assert_eq!(params.nights, 3);
+    }
+
     #[test]
     fn encode_decode_roundtrip() {
         let builder = HotelSearchParams::builder(
@@ -612,12 +1055,53 @@ mod tests {
             2,
             Vec::new(),
         );
-        let params = builder.build().unwrap();
+        let params = builder
+            .hotel_stars(vec![3, 5, 4])
+            .amenities(vec![Amenity::Spa, Amenity::IndoorPool])
+            .build()
+            .unwrap();
         let ts = params.generate_ts().unwrap();
         let decoded = HotelSearchParams::from_ts(&ts).unwrap();
         assert_eq!(decoded.checkin_date, "2026-01-25");
         assert_eq!(decoded.checkout_date, "2026-01-31");
         assert_eq!(decoded.adults, 2);
         assert!(decoded.children_ages.is_empty());
+        assert_eq!(decoded.hotel_stars, [3, 4, 5]);
+        assert_eq!(decoded.amenities, [Amenity::IndoorPool, Amenity::Spa]);
+    }
+
+    #[test]
+    fn decoded_stars_sorted_regardless_of_input_order() {
+        let checkin = NaiveDate::from_ymd_opt(2026, 1, 25).unwrap();
+        for stars in [vec![5, 2, 4], vec![2, 4, 5], vec![4, 5, 2]] {
+            let params = HotelSearchParams::builder("Paris".into(), checkin, checkin, 2, vec![])
+                .nights(checkin, 2)
+                .hotel_stars(stars)
+                .build()
+                .unwrap();
+            let decoded = HotelSearchParams::from_ts(&params.generate_ts().unwrap()).unwrap();
+            assert_eq!(decoded.hotel_stars, [2, 4, 5]);
+        }
+    }
+
+    #[test]
+    fn max_advance_days_bounds_stay() {
+        let today = chrono::Local::now().date_naive();
+        let stay = |checkin: NaiveDate| {
+            HotelSearchParams::builder("Paris".into(), checkin, checkin, 2, vec![])
+                .nights(checkin, 3)
+        };
+
+        let near = today + chrono::Days::new(30);
+        assert!(stay(near).max_advance_days(Some(60)).build().is_ok());
+
+        let far = today + chrono::Days::new(400);
+        assert!(stay(far).build().is_ok());
+        let err = stay(far).max_advance_days(Some(60)).build().unwrap_err();
+        assert!(err.to_string().contains("60 days ahead"), "{err}");
+
+        // The checkout date counts too.
+        let edge = today + chrono::Days::new(59);
+        assert!(stay(edge).max_advance_days(Some(60)).build().is_err());
     }
 }
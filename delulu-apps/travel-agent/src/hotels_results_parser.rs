@@ -24,24 +24,113 @@
 //!
 //! See [`schemas/hotels-response.json`](schemas/hotels-response.json) for the canonical JSON schema.
 
+use std::collections::HashMap;
+
 use anyhow::Result;
+use once_cell::sync::Lazy;
+use regex::Regex;
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
 
+use crate::Amenity;
+use crate::airports::haversine_km;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct Hotel {
     pub name: String,
     pub price: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub rating: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub reviews: Option<u32>,
-    #[serde(default)]
+    /// Amenity chips shown on the card. Chips matching an [`Amenity`] filter use
+    /// its snake_case name (e.g. "kid_friendly"); others keep Google's label.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub amenities: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub location_rating: Option<String>,
+    /// Straight-line distance from the center of the searched area, in
+    /// kilometers. Cards show no distance, so it is computed from the
+    /// coordinates in the page's embedded map data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distance_km: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub star_class: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub address: Option<String>,
+    /// Whether `price` includes taxes and fees, from the card's "with taxes +
+    /// fees" or "+ taxes" annotation. `None` when the card has neither.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub taxes_included: Option<bool>,
+    /// `price` split between every guest, set by
+    /// [`HotelSearchResult::compute_price_per_person`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_per_person: Option<i32>,
+    /// Whether the hotel can be booked for the searched dates.
+    #[serde(default)]
+    pub availability: Availability,
+}
+
+/// Booking status shown on a hotel card.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Availability {
+    /// The card shows a price.
+    Available,
+    /// The card says "Sold out" or "Unavailable for your dates". Such cards
+    /// are kept, without a price, so the user can be told why.
+    SoldOut,
+    /// Not recorded, e.g. results saved before this field existed.
+    #[default]
+    Unknown,
+}
+
+impl Hotel {
+    /// Amenities on this hotel that correspond to an [`Amenity`] filter.
+    pub fn known_amenities(&self) -> Vec<Amenity> {
+        self.amenities
+            .iter()
+            .filter_map(|a| Amenity::from_str_name(a))
+            .collect()
+    }
+
+    /// This hotel in the MCP `search_hotels` result shape: integer price,
+    /// numeric star class, and only the fields in the hotels response schema.
+    pub fn to_mcp_hotel(&self) -> McpHotel {
+        let price = self.price_amount().unwrap_or(0);
+        let stars = self
+            .star_class
+            .as_ref()
+            .and_then(|s| s.trim().parse().ok())
+            .filter(|&s| s > 0);
+
+        McpHotel {
+            name: self.name.clone(),
+            price,
+            rating: self.rating.unwrap_or(0.0),
+            stars,
+            amenities: self.amenities.clone(),
+            distance_km: self.distance_km,
+            taxes_included: self.taxes_included,
+            price_per_person: self.price_per_person,
+            availability: self.availability,
+        }
+    }
+
+    /// Digits of `price`, e.g. 1234 for "$1,234".
+    fn price_amount(&self) -> Option<i32> {
+        self.price
+            .chars()
+            .filter(|c| c.is_ascii_digit())
+            .collect::<String>()
+            .parse()
+            .ok()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,8 +138,14 @@ pub struct Hotel {
 #[serde(rename_all = "snake_case")]
 pub struct HotelSearchResult {
     pub hotels: Vec<Hotel>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub lowest_price: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub current_price: Option<String>,
+    /// Times the client's query queue retried the fetch before it went
+    /// through, `None` for results not fetched by a client.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_count: Option<u32>,
 }
 
 impl HotelSearchResult {
@@ -63,34 +158,7 @@ impl HotelSearchResult {
         search_url: String,
         warnings: Vec<String>,
     ) -> McpHotelResponse {
-        let results: Vec<McpHotel> = self
-            .hotels
-            .iter()
-            .map(|hotel| {
-                let price = hotel
-                    .price
-                    .chars()
-                    .filter(|c| c.is_ascii_digit())
-                    .collect::<String>()
-                    .parse()
-                    .unwrap_or(0);
-                let stars = hotel
-                    .star_class
-                    .as_ref()
-                    .and_then(|s| s.trim().parse().ok())
-                    .filter(|&s| s > 0);
-                let rating = hotel.rating.unwrap_or(0.0);
-                let amenities: Vec<String> = hotel.amenities.clone();
-
-                McpHotel {
-                    name: hotel.name.clone(),
-                    price,
-                    rating,
-                    stars,
-                    amenities,
-                }
-            })
-            .collect();
+        let results: Vec<McpHotel> = self.hotels.iter().map(Hotel::to_mcp_hotel).collect();
 
         McpHotelResponse {
             search_hotels: McpHotelsResponse {
@@ -101,6 +169,7 @@ impl HotelSearchResult {
                     out: checkout_date,
                     curr: currency,
                     search_url,
+                    retry_count: self.retry_count,
                 },
                 results,
                 warnings,
@@ -137,6 +206,9 @@ pub struct McpHotelQuery {
     pub out: String,
     pub curr: String,
     pub search_url: String,
+    /// [`HotelSearchResult::retry_count`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_count: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -150,15 +222,52 @@ pub struct McpHotel {
     pub stars: Option<i32>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub amenities: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distance_km: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub taxes_included: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_per_person: Option<i32>,
+    pub availability: Availability,
 }
 
 impl HotelSearchResult {
+    /// Counts how many elements each parser selector matches in `html`.
+    ///
+    /// Maintenance aid: a selector matching zero elements on a fresh page is
+    /// the one Google broke.
+    pub fn selector_match_counts(html: &str) -> Result<Vec<(&'static str, usize)>> {
+        let selectors = hotel_selectors()?;
+        let document = Html::parse_document(html);
+        Ok(selectors
+            .named()
+            .into_iter()
+            .map(|(name, selector)| (name, document.select(selector).count()))
+            .collect())
+    }
+
+    /// Parses a Google Hotels results page.
+    ///
+    /// A results page listing no hotel, e.g. when the filters exclude every
+    /// property, parses to an empty result. Anything else without a valid
+    /// hotel (consent wall, error page, changed markup) is an error.
     pub fn from_html(html: &str) -> Result<Self> {
-        let selectors = HotelSelectors::new();
+        let selectors = hotel_selectors()?;
         let document = Html::parse_document(html);
         let mut hotels = Vec::new();
+        let distances = hotel_distances_km(html);
+
+        let cards: Vec<_> = document.select(&selectors.hotel_card).collect();
+        if cards.is_empty() && document.select(&selectors.results_list).next().is_some() {
+            return Ok(HotelSearchResult {
+                hotels,
+                lowest_price: None,
+                current_price: None,
+                retry_count: None,
+            });
+        }
 
-        for card in document.select(&selectors.hotel_card) {
+        for card in cards {
             let name = card
                 .select(&selectors.name)
                 .next()
@@ -171,8 +280,11 @@ impl HotelSearchResult {
                 .select(&selectors.price)
                 .next()
                 .map(|e| e.text().collect::<String>());
-            let Some(price) = price else {
-                continue;
+            let sold_out = card.text().any(|text| SOLD_OUT_RE.is_match(text.trim()));
+            let (price, availability) = match price {
+                _ if sold_out => (String::new(), Availability::SoldOut),
+                Some(price) => (price, Availability::Available),
+                None => continue,
             };
 
             let rating = card
@@ -189,6 +301,10 @@ impl HotelSearchResult {
                 .select(&selectors.amenities)
                 .map(|e| e.text().collect::<String>())
                 .filter(|s| !s.is_empty() && s.len() > 2)
+                .map(|s| match Amenity::from_chip_label(&s) {
+                    Some(amenity) => amenity.as_str_name().to_string(),
+                    None => s,
+                })
                 .collect();
             let location_rating = card
                 .select(&selectors.location_rating)
@@ -210,6 +326,9 @@ impl HotelSearchResult {
                     }
                 });
 
+            let taxes_included = card.text().find_map(parse_taxes_included);
+            let distance_km = distances.get(&name).copied();
+
             hotels.push(Hotel {
                 name,
                 price,
@@ -217,9 +336,13 @@ impl HotelSearchResult {
                 reviews,
                 amenities,
                 location_rating,
+                distance_km,
                 star_class,
                 url,
                 address: None,
+                taxes_included,
+                price_per_person: None,
+                availability,
             });
         }
 
@@ -227,21 +350,125 @@ impl HotelSearchResult {
             hotels,
             lowest_price: None,
             current_price: None,
+            retry_count: None,
         };
 
         anyhow::ensure!(result.is_valid(), "No valid hotel results found");
         Ok(result)
     }
 
+    /// Sets [`Hotel::price_per_person`] on every hotel with a price, for a
+    /// stay of `guests` people (adults and children).
+    pub fn compute_price_per_person(&mut self, guests: u32) {
+        let guests = guests.max(1);
+        for hotel in &mut self.hotels {
+            hotel.price_per_person = hotel
+                .price_amount()
+                .map(|price| (price as f64 / guests as f64).round() as i32);
+        }
+    }
+
+    /// Keeps only hotels showing every chip in `chips`, matched
+    /// case-insensitively as a substring so "Wi-Fi" matches "Free Wi-Fi".
+    pub fn require_chips(&mut self, chips: &[String]) {
+        let chips: Vec<String> = chips.iter().map(|c| c.to_lowercase()).collect();
+        self.hotels.retain(|h| {
+            chips.iter().all(|chip| {
+                h.amenities
+                    .iter()
+                    .any(|a| a.to_lowercase().contains(chip.as_str()))
+            })
+        });
+    }
+
+    /// Removes hotels farther than `km` from the search center.
+    ///
+    /// Hotels without a distance are removed too, since they cannot be shown
+    /// to be close enough.
+    pub fn within_km(&mut self, km: f64) {
+        self.hotels
+            .retain(|h| h.distance_km.is_some_and(|d| d <= km));
+    }
+
     pub fn hotels(&self) -> impl Iterator<Item = &Hotel> {
         self.hotels.iter()
     }
 
     fn is_valid(&self) -> bool {
-        !self.hotels.is_empty() && self.hotels.iter().any(|h| !h.price.is_empty())
+        self.hotels
+            .iter()
+            .any(|h| !h.price.is_empty() || h.availability == Availability::SoldOut)
     }
 }
 
+/// Sold-out notices, matched on the card's text nodes rather than a class
+/// name.
+static SOLD_OUT_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^(?:sold out|unavailable for your dates|no availability)\b").unwrap()
+});
+
+/// Tax annotation next to the price: "2 nights with taxes + fees", "incl.
+/// taxes" on one side, "+ taxes & fees", "excl. taxes" on the other.
+static TAXES_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)(with|incl(?:\.|uding)?|excl(?:\.|uding)?|before|plus|\+)\s*taxes\b").unwrap()
+});
+
+/// Whether a text node says the price includes taxes, or `None` if it is
+/// not a tax annotation.
+fn parse_taxes_included(text: &str) -> Option<bool> {
+    let caps = TAXES_RE.captures(text)?;
+    let word = caps[1].to_lowercase();
+    Some(word == "with" || word.starts_with("incl"))
+}
+
+/// Map viewport of the searched place in the page's embedded data:
+/// `[[south,west],[north,east]],null,["/m/<id>","<place>"`.
+static VIEWPORT_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\[\[(-?\d+\.\d+),(-?\d+\.\d+)\],\[(-?\d+\.\d+),(-?\d+\.\d+)\]\],null,\["/[mg]/"#)
+        .unwrap()
+});
+
+/// A hotel record in the page's embedded data, starting with its name and
+/// coordinates: `"397419284":[[null,"<name>",[[lat,lng]`.
+static HOTEL_COORDINATES_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#""397419284":\[\[null,("(?:[^"\\]|\\.)*"),\[\[(-?\d+\.\d+),(-?\d+\.\d+)\]"#)
+        .unwrap()
+});
+
+/// Distance of every hotel in the embedded data from the center of the
+/// search viewport, in kilometers rounded to 0.1, by hotel name. Empty when
+/// the page has no viewport.
+fn hotel_distances_km(html: &str) -> HashMap<String, f64> {
+    let coordinate = |caps: &regex::Captures, i: usize| caps[i].parse::<f64>().ok();
+    let Some(center) = VIEWPORT_RE.captures(html).and_then(|caps| {
+        let (south, west) = (coordinate(&caps, 1)?, coordinate(&caps, 2)?);
+        let (north, east) = (coordinate(&caps, 3)?, coordinate(&caps, 4)?);
+        Some(((south + north) / 2.0, (west + east) / 2.0))
+    }) else {
+        return HashMap::new();
+    };
+
+    HOTEL_COORDINATES_RE
+        .captures_iter(html)
+        .filter_map(|caps| {
+            let name: String = serde_json::from_str(&caps[1]).ok()?;
+            let hotel = (coordinate(&caps, 2)?, coordinate(&caps, 3)?);
+            let km = (haversine_km(center, hotel) * 10.0).round() / 10.0;
+            Some((name, km))
+        })
+        .collect()
+}
+
+/// Selectors are immutable, so they are compiled once and shared by every parse.
+static HOTEL_SELECTORS: Lazy<Result<HotelSelectors, String>> =
+    Lazy::new(|| HotelSelectors::new().map_err(|e| e.to_string()));
+
+fn hotel_selectors() -> Result<&'static HotelSelectors> {
+    HOTEL_SELECTORS
+        .as_ref()
+        .map_err(|e| anyhow::anyhow!("{}", e))
+}
+
 struct HotelSelectors {
     hotel_card: Selector,
     name: Selector,
@@ -253,21 +480,130 @@ struct HotelSelectors {
     location_rating: Selector,
     star_class: Selector,
     link: Selector,
+    results_list: Selector,
 }
 
 impl HotelSelectors {
-    fn new() -> Self {
-        Self {
-            hotel_card: Selector::parse(r#"div.uaTTDe"#).unwrap(),
-            name: Selector::parse(r#"h2.BgYkof"#).unwrap(),
-            rating: Selector::parse(r#"span.KFi5wf.lA0BZ"#).unwrap(),
-            rating_aria: Selector::parse(r#"span[aria-label*="out of 5 stars"]"#).unwrap(),
-            reviews: Selector::parse(r#"span.jdzyld"#).unwrap(),
-            price: Selector::parse(r#"span.qQOQpe"#).unwrap(),
-            amenities: Selector::parse(r#"span.LtjZ2d"#).unwrap(),
-            location_rating: Selector::parse(r#"span.uTUoTb"#).unwrap(),
-            star_class: Selector::parse(r#"span.UqrZme"#).unwrap(),
-            link: Selector::parse(r#"a[href]"#).unwrap(),
-        }
+    /// Compiles every selector.
+    ///
+    /// Fails only if one of the CSS strings below is invalid, which is a
+    /// programming error caught by the tests, but it is surfaced as an error
+    /// rather than a panic in the library path.
+    fn new() -> Result<Self> {
+        let parse = |css: &str| {
+            Selector::parse(css)
+                .map_err(|e| anyhow::anyhow!("Invalid CSS selector `{}`: {}", css, e))
+        };
+        Ok(Self {
+            hotel_card: parse(r#"div.uaTTDe"#)?,
+            name: parse(r#"h2.BgYkof"#)?,
+            rating: parse(r#"span.KFi5wf.lA0BZ"#)?,
+            rating_aria: parse(r#"span[aria-label*="out of 5 stars"]"#)?,
+            reviews: parse(r#"span.jdzyld"#)?,
+            price: parse(r#"span.qQOQpe"#)?,
+            amenities: parse(r#"span.LtjZ2d"#)?,
+            location_rating: parse(r#"span.uTUoTb"#)?,
+            star_class: parse(r#"span.UqrZme"#)?,
+            link: parse(r#"a[href]"#)?,
+            results_list: parse(r#"c-wiz.K1smNd"#)?,
+        })
+    }
+
+    /// Selectors used by the parser, by field name.
+    fn named(&self) -> [(&'static str, &Selector); 11] {
+        [
+            ("hotel_card", &self.hotel_card),
+            ("name", &self.name),
+            ("rating", &self.rating),
+            ("rating_aria", &self.rating_aria),
+            ("reviews", &self.reviews),
+            ("price", &self.price),
+            ("amenities", &self.amenities),
+            ("location_rating", &self.location_rating),
+            ("star_class", &self.star_class),
+            ("link", &self.link),
+            ("results_list", &self.results_list),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selectors_compile() {
+        assert!(HotelSelectors::new().is_ok());
+    }
+
+    #[test]
+    fn test_empty_results_page_is_ok() {
+        let empty = r#"<html><body><c-wiz class="K1smNd"><p>No results</p></c-wiz></body></html>"#;
+        let result = HotelSearchResult::from_html(empty).unwrap();
+        assert!(result.hotels.is_empty());
+
+        // Without the results list, nothing shows this is a results page.
+        assert!(HotelSearchResult::from_html("<html><body>Sorry</body></html>").is_err());
+    }
+
+    #[test]
+    fn test_taxes_and_price_per_person() {
+        assert_eq!(
+            parse_taxes_included("2 nights with taxes + fees"),
+            Some(true)
+        );
+        assert_eq!(parse_taxes_included("Incl. taxes"), Some(true));
+        assert_eq!(parse_taxes_included("+ taxes & fees"), Some(false));
+        assert_eq!(parse_taxes_included("excluding taxes"), Some(false));
+        assert_eq!(parse_taxes_included("Free cancellation"), None);
+
+        let html = r#"<div class="uaTTDe"><h2 class="BgYkof">Family Inn</h2><span class="qQOQpe">$1,000</span><div>+ taxes &amp; fees</div></div>"#;
+        let mut result = HotelSearchResult::from_html(html).unwrap();
+        assert_eq!(result.hotels[0].taxes_included, Some(false));
+        assert_eq!(result.hotels[0].price_per_person, None);
+
+        result.compute_price_per_person(3);
+        let mcp = result.hotels[0].to_mcp_hotel();
+        assert_eq!(mcp.price_per_person, Some(333));
+        assert_eq!(mcp.taxes_included, Some(false));
+    }
+
+    #[test]
+    fn test_sold_out_card_is_kept() {
+        let html = r#"<html><body>
+            <div class="uaTTDe"><h2 class="BgYkof">Open Hotel</h2><span class="qQOQpe">$150</span></div>
+            <div class="uaTTDe"><h2 class="BgYkof">Full Hotel</h2><div>Sold out</div></div>
+            <div class="uaTTDe"><h2 class="BgYkof">Busy Inn</h2><span class="qQOQpe">$90</span><span>Unavailable for your dates</span></div>
+            <div class="uaTTDe"><h2 class="BgYkof">Ghost Inn</h2></div>
+        </body></html>"#;
+
+        let result = HotelSearchResult::from_html(html).unwrap();
+        let statuses: Vec<_> = result
+            .hotels
+            .iter()
+            .map(|h| (h.name.as_str(), h.availability))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                ("Open Hotel", Availability::Available),
+                ("Full Hotel", Availability::SoldOut),
+                ("Busy Inn", Availability::SoldOut),
+            ]
+        );
+        assert!(result.hotels[2].price.is_empty());
+
+        let mcp = result.hotels[1].to_mcp_hotel();
+        assert_eq!(mcp.price, 0);
+        assert_eq!(mcp.availability, Availability::SoldOut);
+        assert_eq!(
+            serde_json::to_value(&mcp).unwrap()["availability"],
+            "sold_out"
+        );
+
+        // A page where every listed hotel is sold out is still a results page.
+        let all_sold_out =
+            r#"<div class="uaTTDe"><h2 class="BgYkof">Full Hotel</h2><div>Sold out</div></div>"#;
+        assert!(HotelSearchResult::from_html(all_sold_out).is_ok());
     }
 }
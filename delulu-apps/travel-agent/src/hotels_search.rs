@@ -19,59 +19,143 @@
 //!
 //! Effectful (time, network) operations for Google Hotels search.
 
-use crate::consent_cookie::generate_cookie_header;
+use crate::consent_cookie::{CookieCache, CookieProvider};
+use crate::diagnostics::is_consent_page;
+use crate::flights_search::{HttpTimeouts, accept_language, request_headers};
 use crate::hotels_query_builder::HotelSearchParams;
 use crate::hotels_results_parser::HotelSearchResult;
-use anyhow::{Context, Result, anyhow, bail};
+use crate::language::Language;
+use anyhow::{Context, Result, bail};
 use delulu_query_queues::QueryQueue;
 use std::sync::Arc;
 use std::time::Duration;
-use wreq::redirect::Policy;
-use wreq_util::Emulation;
+use wreq::header::HeaderMap;
+
+/// Fetched by the `warm_up` connectivity check.
+const HOME_URL: &str = "https://www.google.com/travel/hotels";
 
 #[derive(Clone)]
 pub struct GoogleHotelsClient {
     client: Arc<wreq::Client>,
     query_queue: QueryQueue,
+    cookies: Arc<CookieCache>,
+    language: String,
+    timeouts: HttpTimeouts,
+    headers: HeaderMap,
 }
 
 impl GoogleHotelsClient {
     pub fn new(timeout_secs: u64, queries_per_second: u32) -> Result<Self> {
-        let client = wreq::Client::builder()
-            .emulation(Emulation::Safari18_5)
-            .redirect(Policy::default())
-            .timeout(Duration::from_secs(timeout_secs))
-            .connect_timeout(Duration::from_secs(timeout_secs))
-            .build()
-            .context("Failed to build HTTP client")?;
+        let timeouts = HttpTimeouts::new(Duration::from_secs(timeout_secs));
+        let client = timeouts.build_client()?;
         let query_queue = QueryQueue::with_qps_limit(queries_per_second as u64);
         Ok(Self {
             client: Arc::new(client),
             query_queue,
+            cookies: Arc::new(CookieCache::default()),
+            language: "en".to_string(),
+            timeouts,
+            headers: HeaderMap::new(),
         })
     }
+
+    /// Route requests through `queue` instead of the client's own, e.g. to
+    /// share one rate limit between the flights and hotels clients.
+    pub fn query_queue(mut self, queue: QueryQueue) -> Self {
+        self.query_queue = queue;
+        self
+    }
+
+    /// Give up connecting after `timeout` instead of the `timeout_secs` passed
+    /// to [`Self::new`]. A failed connect surfaces as
+    /// [`HotelsError::ConnectTimeout`](crate::HotelsError::ConnectTimeout).
+    pub fn connect_timeout(mut self, timeout: Duration) -> Result<Self> {
+        self.timeouts.connect = timeout;
+        self.client = Arc::new(self.timeouts.build_client()?);
+        Ok(self)
+    }
+
+    /// Give up when Google sends nothing for `timeout` once connected.
+    /// Unset by default, leaving only the overall `timeout_secs`. Either
+    /// surfaces as [`HotelsError::ReadTimeout`](crate::HotelsError::ReadTimeout).
+    pub fn read_timeout(mut self, timeout: Duration) -> Result<Self> {
+        self.timeouts.read = Some(timeout);
+        self.client = Arc::new(self.timeouts.build_client()?);
+        Ok(self)
+    }
+
+    /// Send `headers` with every request, e.g. gateway credentials or
+    /// `X-Forwarded-For`. A `Cookie` is merged with the consent cookie and an
+    /// `Accept-Language` replaces the one derived from the language.
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Build the consent cookie with `provider` instead of
+    /// [`SocsCookieProvider`](crate::SocsCookieProvider).
+    pub fn cookie_provider(mut self, provider: Arc<dyn CookieProvider>) -> Self {
+        self.cookies = Arc::new(CookieCache::new(provider));
+        self
+    }
+
+    /// Language sent as `Accept-Language` (default "en").
+    pub fn language(mut self, language: Language) -> Self {
+        self.language = language.into();
+        self
+    }
 }
 
 impl GoogleHotelsClient {
-    async fn fetch_raw(&self, url: &str) -> Result<String> {
-        let cookie_header = generate_cookie_header();
+    /// Generates and caches the consent cookie ahead of the first search.
+    ///
+    /// With `check_connectivity`, also fetches the Google Hotels home page so
+    /// network or consent problems surface at startup rather than on the
+    /// first user request.
+    pub async fn warm_up(&self, check_connectivity: bool) -> Result<()> {
+        let cookie_header = self.cookies.header(&self.language)?;
+        if check_connectivity {
+            let response = self
+                .client
+                .get(HOME_URL)
+                .headers(request_headers(
+                    &self.headers,
+                    &cookie_header,
+                    &accept_language(&self.language),
+                ))
+                .send()
+                .await
+                .context("Connectivity check failed")?;
+            let status = response.status();
+            anyhow::ensure!(
+                status.is_success(),
+                "Connectivity check failed: HTTP {}",
+                status
+            );
+        }
+        Ok(())
+    }
+
+    /// Page at `url`, and how many times the query queue retried the request.
+    async fn fetch_raw(&self, url: &str) -> Result<(String, u32)> {
+        let headers = request_headers(
+            &self.headers,
+            &self.cookies.header(&self.language)?,
+            &accept_language(&self.language),
+        );
         let client_inner = Arc::clone(&self.client);
 
         let queue_start = std::time::Instant::now();
         let response = self
             .query_queue
-            .with_retry(move || {
+            .with_retry_observed(move || {
                 let url = url.to_string();
-                let cookie = cookie_header.clone();
+                let headers = headers.clone();
                 let http_client = client_inner.clone();
                 async move {
                     let http_start = std::time::Instant::now();
                     tracing::info!("[fetch_raw] Starting HTTP request to: {}", url);
-                    let resp = http_client
-                        .get(url)
-                        .header("Cookie", &cookie)
-                        .send()
-                        .await?;
+                    let resp = http_client.get(url).headers(headers).send().await?;
                     let http_elapsed = http_start.elapsed();
                     tracing::info!("[fetch_raw] HTTP request completed in {:?}", http_elapsed);
                     Ok(resp)
@@ -84,7 +168,7 @@ impl GoogleHotelsClient {
             queue_elapsed
         );
 
-        let response = response.map_err(|e| anyhow!("Request failed: {:?}", e))?;
+        let (response, retry_count) = response.context("Request failed")?;
 
         let status = response.status();
         tracing::debug!(
@@ -107,11 +191,7 @@ impl GoogleHotelsClient {
             bail!("HTTP error {}: {}", status, body_preview);
         }
 
-        let is_consent_page = body.contains("consent.google.com")
-            || body.contains("base href=\"https://consent.google.com\"")
-            || body.contains("ppConfig");
-
-        if is_consent_page {
+        if is_consent_page(&body) {
             let body_preview = body.chars().take(300).collect::<String>();
             bail!(
                 "Consent wall detected - cookies not accepted. \
@@ -137,9 +217,14 @@ impl GoogleHotelsClient {
             tracing::warn!("[fetch_raw] Page may have changed - no hotel markers found");
         }
 
-        Ok(body)
+        Ok((body, retry_count))
     }
 
+    #[tracing::instrument(
+        name = "hotels_client",
+        skip_all,
+        fields(location = %params.loc_q_search)
+    )]
     pub async fn search_hotels(&self, params: &HotelSearchParams) -> Result<HotelSearchResult> {
         let overall_start = std::time::Instant::now();
         let today = chrono::Local::now().date_naive();
@@ -154,7 +239,7 @@ impl GoogleHotelsClient {
 
         let fetch_start = std::time::Instant::now();
         tracing::info!("[search_hotels] Starting HTTP fetch to Google Hotels...");
-        let html = self.fetch_raw(&url).await?;
+        let (html, retry_count) = self.fetch_raw(&url).await?;
         let fetch_elapsed = fetch_start.elapsed();
         tracing::info!(
             "[search_hotels] HTTP fetch completed in {:?}, got {} KB",
@@ -164,7 +249,9 @@ impl GoogleHotelsClient {
 
         let parse_start = std::time::Instant::now();
         match HotelSearchResult::from_html(&html) {
-            Ok(result) => {
+            Ok(mut result) => {
+                result.compute_price_per_person(params.adults + params.children_ages.len() as u32);
+                result.retry_count = Some(retry_count);
                 let parse_elapsed = parse_start.elapsed();
                 tracing::debug!(
                     "[search_hotels] Parsed {} hotels in {:?}",
@@ -217,3 +304,59 @@ impl GoogleHotelsClient {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HotelsError;
+    use crate::test_support::{header_value, refused_url, spawn_echo_server, spawn_stalled_server};
+    use std::sync::atomic::Ordering;
+
+    #[tokio::test]
+    async fn test_sends_accept_language() {
+        let (url, request) = spawn_echo_server("<html></html>").await;
+        let client = GoogleHotelsClient::new(5, 10)
+            .unwrap()
+            .language(Language::new("de-de").unwrap());
+
+        client.fetch_raw(&url).await.unwrap();
+
+        let head = request.await.unwrap();
+        assert_eq!(
+            header_value(&head, "accept-language"),
+            Some("de-DE,de;q=0.9")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_caches_cookie() {
+        let (url, request) = spawn_echo_server("<html></html>").await;
+        let client = GoogleHotelsClient::new(5, 10).unwrap();
+
+        client.warm_up(false).await.unwrap();
+        client.fetch_raw(&url).await.unwrap();
+
+        let head = request.await.unwrap();
+        assert_eq!(
+            header_value(&head, "cookie"),
+            Some(client.cookies.header("en").unwrap().as_str())
+        );
+        assert_eq!(client.cookies.generations.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_timeouts_map_to_distinct_errors() {
+        let client = GoogleHotelsClient::new(30, 10)
+            .unwrap()
+            .read_timeout(Duration::from_millis(100))
+            .unwrap();
+
+        let stalled = spawn_stalled_server().await;
+        let err = HotelsError::from_search(client.fetch_raw(&stalled).await.unwrap_err());
+        assert!(matches!(err, HotelsError::ReadTimeout(_)), "{err:?}");
+
+        let refused = refused_url().await;
+        let err = HotelsError::from_search(client.fetch_raw(&refused).await.unwrap_err());
+        assert!(matches!(err, HotelsError::ConnectTimeout(_)), "{err:?}");
+    }
+}
@@ -0,0 +1,143 @@
+//!  Delulu Travel Agent
+//!
+//!  Copyright (C) 2026  Mamy Ratsimbazafy
+//!
+//!  This program is free software: you can redistribute it and/or modify
+//!  it under the terms of the GNU Affero General Public License as published by
+//!  the Free Software Foundation, either version 3 of the License, or
+//!  (at your option) any later version.
+//!
+//!  This program is distributed in the hope that it will be useful,
+//!  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//!  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//!  GNU Affero General Public License for more details.
+//!
+//!  You should have received a copy of the GNU Affero General Public License
+//!  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Page Languages
+//!
+//! Validated `hl` language codes. A code reaches the URL, the
+//! `Accept-Language` header, the consent cookie provider and the locale-aware
+//! parsers, so a typo like "english" is rejected up front instead of silently
+//! serving an unexpected page.
+
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("invalid language code '{0}': expected a code such as en, de or en-GB")]
+pub struct LanguageError(pub String);
+
+/// A BCP 47 style language code: a 2 or 3 letter primary subtag, optionally
+/// followed by a 4 letter script and a 2 letter or 3 digit region, e.g. `en`,
+/// `pt-BR`, `zh-Hant-TW`.
+///
+/// Subtags are normalized to their conventional case, `EN_us` becoming
+/// `en-US`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Language(String);
+
+impl Language {
+    pub fn new(code: &str) -> Result<Self, LanguageError> {
+        let invalid = || LanguageError(code.to_string());
+        let mut subtags = code.trim().split(['-', '_']);
+
+        let primary = subtags.next().ok_or_else(invalid)?;
+        if !(2..=3).contains(&primary.len()) || !primary.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(invalid());
+        }
+        let mut normalized = primary.to_ascii_lowercase();
+
+        let mut rest = subtags.peekable();
+        if let Some(script) = rest.next_if(|s| s.len() == 4) {
+            if !script.chars().all(|c| c.is_ascii_alphabetic()) {
+                return Err(invalid());
+            }
+            normalized.push('-');
+            normalized.push_str(&script[..1].to_ascii_uppercase());
+            normalized.push_str(&script[1..].to_ascii_lowercase());
+        }
+        if let Some(region) = rest.next() {
+            let letters = region.len() == 2 && region.chars().all(|c| c.is_ascii_alphabetic());
+            let digits = region.len() == 3 && region.chars().all(|c| c.is_ascii_digit());
+            if !(letters || digits) {
+                return Err(invalid());
+            }
+            normalized.push('-');
+            normalized.push_str(&region.to_ascii_uppercase());
+        }
+        if rest.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(Self(normalized))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Primary subtag, e.g. `en` for `en-GB`.
+    pub fn primary(&self) -> &str {
+        self.0.split('-').next().unwrap_or(&self.0)
+    }
+}
+
+impl Default for Language {
+    fn default() -> Self {
+        Self("en".to_string())
+    }
+}
+
+impl FromStr for Language {
+    type Err = LanguageError;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        Self::new(code)
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<Language> for String {
+    fn from(language: Language) -> Self {
+        language.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_normalization() {
+        for (input, expected) in [
+            ("en", "en"),
+            ("DE", "de"),
+            ("EN-us", "en-US"),
+            ("en_gb", "en-GB"),
+            (" fr-FR ", "fr-FR"),
+            ("es-419", "es-419"),
+            ("zh-hant-tw", "zh-Hant-TW"),
+        ] {
+            assert_eq!(Language::new(input).unwrap().as_str(), expected, "{input}");
+        }
+        assert_eq!(Language::new("en-GB").unwrap().primary(), "en");
+
+        for invalid in [
+            "english", "", "e", "en-", "en-GBR", "en-G1", "fr-FR-x", "e1",
+        ] {
+            assert_eq!(
+                Language::new(invalid),
+                Err(LanguageError(invalid.to_string())),
+                "{invalid}"
+            );
+        }
+    }
+}
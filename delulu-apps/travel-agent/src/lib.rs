@@ -20,22 +20,71 @@
 
 // Testing access - consent_cookie is re-exported for test modules
 pub(crate) mod consent_cookie;
-pub use consent_cookie::generate_cookie_header;
+pub use consent_cookie::{CookieProvider, SocsCookieProvider, generate_cookie_header};
+mod airports;
+mod alliances;
+mod currency;
+mod decode_error;
+mod diagnostics;
+mod flights_cabins;
+mod flights_flexible;
+mod flights_price_watch;
 mod flights_query_builder;
+mod flights_ranking;
 mod flights_results_parser;
 mod flights_search;
+mod hotels_preferences;
 mod hotels_query_builder;
 mod hotels_results_parser;
 mod hotels_search;
+mod language;
+#[cfg(feature = "mcp")]
+mod mcp_sessions;
+mod proto_debug;
+mod providers;
+#[cfg(feature = "replay")]
+mod replay;
+#[cfg(test)]
+mod test_support;
 
+pub use airports::{
+    MEDIUM_HAUL_MAX_KM, ResultExpectations, RouteClass, SHORT_HAUL_MAX_KM, TYPICAL_NONSTOP_MAX_KM,
+    airport_coordinates, airports_within, great_circle_km, nonstop_hint,
+};
+pub use alliances::{Alliance, expand_alliances};
+pub use currency::{
+    Currency, CurrencyConversion, CurrencyError, CurrencySymbols, DEFAULT_CURRENCY_SYMBOLS,
+    ExchangeRateProvider, SUPPORTED_CURRENCIES,
+};
+pub use decode_error::DecodeError;
+pub use diagnostics::{
+    CRITICAL_SELECTORS, SearchDiagnosis, SelectorHealth, SelectorMatch, UnexpectedLayout,
+};
+pub use flights_cabins::{CABINS, CabinComparison, CabinFare, compare_cabins};
+pub use flights_flexible::{
+    CheapestWeekend, MAX_WEEKENDS, WeekendFare, cheapest_weekend, weekends_in_month,
+};
+pub use flights_price_watch::{DEFAULT_WATCH_INTERVAL, PriceEvent, PriceUpdate, PriceWatch};
 pub use flights_query_builder::{
-    FlightSearchParams, FlightSearchParamsBuilder, Passenger, Seat, Trip,
+    DEFAULT_MAX_PASSENGERS, DEFAULT_TFU, DecodedFlightsUrl, FlightLeg, FlightSearchParams,
+    FlightSearchParamsBuilder, Passenger, Seat, Trip,
 };
+pub use flights_ranking::{FlightSort, ValueWeights, value_score};
 pub use flights_results_parser::{
-    FlightSearchResult, FlightSegment, Itinerary, Layover, McpFlightResponse,
+    DEFAULT_MIN_PLAUSIBLE_PRICE, FlightSearchResult, FlightSegment, Itinerary, Layover,
+    McpFlightResponse, ParseOptions, PriceTrend,
 };
 pub use flights_search::GoogleFlightsClient;
 
+pub use hotels_preferences::HotelPreferences;
 pub use hotels_query_builder::{Amenity, HotelSearchParams, HotelSearchParamsBuilder, SortType};
-pub use hotels_results_parser::{Hotel, HotelSearchResult};
+pub use hotels_results_parser::{Availability, Hotel, HotelSearchResult, McpHotel};
 pub use hotels_search::GoogleHotelsClient;
+pub use language::{Language, LanguageError};
+#[cfg(feature = "mcp")]
+pub use mcp_sessions::{
+    FileSessionStore, MemorySessionStore, PersistentSessionManager, SessionStore, SessionStoreError,
+};
+pub use providers::{FlightProvider, FlightsError, HotelProvider, HotelsError, SearchFuture};
+#[cfg(feature = "replay")]
+pub use replay::{ReplayFlightsClient, ReplayHotelsClient};
@@ -19,9 +19,11 @@
 
 use anyhow::{Context, Result};
 use chrono::NaiveDate;
+use chrono::format::{Item, StrftimeItems};
 use clap::Parser;
 use delulu_travel_agent::{
-    FlightSearchParams, FlightSearchResult, GoogleFlightsClient, Passenger, Seat, Trip,
+    DecodedFlightsUrl, FlightSearchParams, FlightSearchResult, GoogleFlightsClient, Passenger,
+    Seat, Trip,
 };
 use std::cmp::max;
 use term_size;
@@ -32,16 +34,16 @@ use term_size;
 #[command(author, version, about, long_about = None)]
 struct CliArgs {
     /// Origin airport code (e.g., SFO, LAX)
-    #[arg(short, long)]
-    from: String,
+    #[arg(short, long, required_unless_present = "decode")]
+    from: Option<String>,
 
     /// Destination airport code (e.g., JFK, LHR)
-    #[arg(short, long)]
-    to: String,
+    #[arg(short, long, required_unless_present = "decode")]
+    to: Option<String>,
 
     /// Departure date (YYYY-MM-DD or YYYY/MM/DD)
-    #[arg(short, long)]
-    date: String,
+    #[arg(short, long, required_unless_present = "decode")]
+    date: Option<String>,
 
     /// Return date for round trips (YYYY-MM-DD or YYYY/MM/DD)
     #[arg(short = 'R', long)]
@@ -56,14 +58,14 @@ struct CliArgs {
     passengers: u32,
 
     /// Trip type: roundtrip, oneway
-    #[arg(short, long, default_value = "roundtrip")]
+    #[arg(short = 'T', long, default_value = "roundtrip")]
     trip: String,
 
     /// Maximum number of stops (0 = nonstop only)
     #[arg(long)]
     max_stops: Option<i32>,
 
-    /// Preferred airlines (comma-separated, e.g., "AA,DL,UA")
+    /// Preferred airlines or alliances (comma-separated, e.g., "AA,DL,UA" or "ONEWORLD")
     #[arg(long)]
     preferred_airlines: Option<String>,
 
@@ -74,6 +76,18 @@ struct CliArgs {
     /// Save raw HTML response to file for debugging
     #[arg(long)]
     save_html: bool,
+
+    /// strftime format for printed dates, e.g. "%d %b %Y" for "15 Apr 2026"
+    #[arg(long, default_value = "%Y-%m-%d")]
+    date_format: String,
+
+    /// Print the search parameters encoded in `--tfs` as JSON and exit
+    #[arg(long, requires = "tfs")]
+    decode: bool,
+
+    /// `tfs` parameter to decode, raw or as a full Google Flights URL
+    #[arg(long, value_name = "TFS_OR_URL", requires = "decode")]
+    tfs: Option<String>,
 }
 
 /// Configure logging based on verbosity level
@@ -109,6 +123,31 @@ fn parse_trip(s: &str) -> Result<Trip> {
     }
 }
 
+/// Decode a `tfs` (raw or inside a URL) into pretty-printed search parameters,
+/// with the URL's language and currency when given one.
+fn decode_to_json(input: &str) -> Result<String> {
+    let decoded = if input.contains('?') {
+        FlightSearchParams::from_url(input)
+    } else {
+        FlightSearchParams::from_tfs(input.trim()).map(|params| DecodedFlightsUrl {
+            params,
+            language: None,
+            currency: None,
+        })
+    }
+    .context("Invalid tfs")?;
+    serde_json::to_string_pretty(&decoded).context("Failed to serialize search parameters")
+}
+
+/// Rejects strftime strings that chrono cannot format, which would otherwise
+/// panic when the date is printed.
+fn check_date_format(format: &str) -> Result<()> {
+    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        anyhow::bail!("Invalid date format: {}", format);
+    }
+    Ok(())
+}
+
 /// Parse date string to NaiveDate
 fn parse_date(s: &str) -> Result<NaiveDate> {
     NaiveDate::parse_from_str(s, "%Y-%m-%d")
@@ -163,11 +202,12 @@ fn first_seg<'a>(
 }
 
 /// Format stops and layovers combined: "2 stops: 5h09@Vancouver, 2h20@Brisbane"
-fn fmt_stops_and_layovers(layovers: &[delulu_travel_agent::Layover]) -> String {
-    let stops = layovers.len();
-    match stops {
-        0 => "direct".to_string(),
-        1 => {
+fn fmt_stops_and_layovers(itin: &delulu_travel_agent::Itinerary) -> String {
+    let layovers = &itin.layovers;
+    match itin.stop_count() {
+        None => "stops unknown".to_string(),
+        Some(0) => "direct".to_string(),
+        Some(1) => {
             if let Some(l) = layovers.first() {
                 let dur = l
                     .duration_minutes
@@ -178,7 +218,8 @@ fn fmt_stops_and_layovers(layovers: &[delulu_travel_agent::Layover]) -> String {
                 "1 stop".to_string()
             }
         }
-        n => {
+        Some(n) if layovers.is_empty() => format!("{} stops", n),
+        Some(n) => {
             let parts: Vec<String> = layovers
                 .iter()
                 .map(|l| {
@@ -216,7 +257,7 @@ fn calc_column_widths(
                 max_duration,
                 fmt_duration(opt_i32(&itin.duration_minutes, 0)).len(),
             );
-            let stops_label = fmt_stops_and_layovers(&itin.layovers);
+            let stops_label = fmt_stops_and_layovers(itin);
             max_stops = max(max_stops, stops_label.len());
         }
     }
@@ -242,21 +283,33 @@ fn calc_column_widths(
     (rank_width, max_airline, max_times, max_duration, max_stops)
 }
 
-/// Render results to stdout
-fn render_results(result: &delulu_travel_agent::FlightSearchResult, search_url: Option<&str>) {
-    let params = &result.search_params;
+/// Reformat an ISO date for display, leaving anything else as is
+fn fmt_date(date: &str, format: &str) -> String {
+    match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+        Ok(date) => date.format(format).to_string(),
+        Err(_) => date.to_string(),
+    }
+}
 
-    let title_bar = format!(
+/// Banner above the results, with the departure date in `date_format`
+fn title_bar(params: &FlightSearchParams, date_format: &str) -> String {
+    format!(
         "================================================================================================\n  🛫  {} → {} on {}\n================================================================================================",
-        params.from_airport, params.to_airport, params.depart_date
-    );
-    println!("{}\n", title_bar);
+        params.from_airport,
+        params.to_airport,
+        fmt_date(&params.depart_date, date_format)
+    )
+}
+
+/// Render results to stdout
+fn render_results(
+    result: &delulu_travel_agent::FlightSearchResult,
+    search_url: Option<&str>,
+    date_format: &str,
+) {
+    println!("{}\n", title_bar(&result.search_params, date_format));
 
-    let best_price = result
-        .itineraries
-        .first()
-        .and_then(|i| i.price)
-        .unwrap_or(0);
+    let best_price = result.lowest_price().unwrap_or(0);
 
     println!("💰 Best Price:  ${}", best_price);
     println!("📊 Total Flights: {}", result.itineraries.len());
@@ -283,9 +336,8 @@ fn render_results(result: &delulu_travel_agent::FlightSearchResult, search_url:
     // Data rows with individual cell formatting
     for (i, itin) in result.itineraries.iter().take(5).enumerate() {
         if let Some(seg) = first_seg(itin) {
-            let stops_label = fmt_stops_and_layovers(&itin.layovers);
-            let is_suspicious =
-                stops_label == "direct" && opt_i32(&itin.duration_minutes, 0) > 1080;
+            let stops_label = fmt_stops_and_layovers(itin);
+            let is_suspicious = itin.is_suspicious_nonstop();
             let price = opt_i32(&itin.price, 0);
             let warn = if is_suspicious { " ⚠️" } else { "" };
 
@@ -318,21 +370,31 @@ fn dash_bar() -> String {
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = CliArgs::parse();
+
+    if let Some(tfs) = &args.tfs {
+        println!("{}", decode_to_json(tfs)?);
+        return Ok(());
+    }
+
     setup_logging(args.verbose);
 
     tracing::info!("Starting delulu-flights CLI");
     tracing::info!("Args: {:?}", args);
 
     // Parse and validate inputs
+    let (Some(from), Some(to), Some(date)) = (args.from, args.to, args.date) else {
+        anyhow::bail!("--from, --to and --date are required unless --decode is given");
+    };
+    check_date_format(&args.date_format)?;
     let cabin = parse_cabin(&args.cabin)?;
     let trip = parse_trip(&args.trip)?;
-    let depart_date = parse_date(&args.date)?;
+    let depart_date = parse_date(&date)?;
     let return_date = args.return_date.map(|d| parse_date(&d)).transpose()?;
 
     tracing::info!(
         "Parsed request: {} -> {} on {:?} ({:?}, {:?})",
-        args.from,
-        args.to,
+        from,
+        to,
         depart_date,
         cabin,
         trip
@@ -340,14 +402,11 @@ async fn main() -> Result<()> {
 
     // Build search params
     let passengers = vec![(Passenger::Adult, args.passengers)];
-    let mut builder = FlightSearchParams::builder(
-        args.from.to_uppercase(),
-        args.to.to_uppercase(),
-        depart_date,
-    )
-    .cabin_class(cabin)
-    .passengers(passengers)
-    .trip_type(trip);
+    let mut builder =
+        FlightSearchParams::builder(from.to_uppercase(), to.to_uppercase(), depart_date)
+            .cabin_class(cabin)
+            .passengers(passengers)
+            .trip_type(trip);
 
     if let Some(rd) = return_date {
         builder = builder.return_date(rd);
@@ -371,7 +430,7 @@ async fn main() -> Result<()> {
     let result = if args.save_html {
         let url = params.get_search_url();
         let html = client.fetch_raw(&url).await.context("Fetch failed")?;
-        let filename = format!("debug_{}_{}.html", args.from, args.to);
+        let filename = format!("debug_{}_{}.html", from, to);
         std::fs::write(&filename, &html).context("Failed to write HTML file")?;
         tracing::info!("Saved HTML to {}", filename);
 
@@ -394,7 +453,69 @@ async fn main() -> Result<()> {
     );
 
     // Render results
-    render_results(&result, Some(&search_url));
+    render_results(&result, Some(&search_url), &args.date_format);
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sfo_jfk() -> FlightSearchParams {
+        let date = NaiveDate::from_ymd_opt(2030, 1, 15).unwrap();
+        FlightSearchParams::builder("SFO".into(), "JFK".into(), date)
+            .trip_type(Trip::OneWay)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_decode_tfs() {
+        let tfs = sfo_jfk().generate_tfs().unwrap();
+        let args = CliArgs::try_parse_from(["delulu-flights", "--decode", "--tfs", &tfs]).unwrap();
+        let json = decode_to_json(args.tfs.as_deref().unwrap()).unwrap();
+        assert!(json.contains("\"SFO\""), "{json}");
+        assert!(json.contains("\"JFK\""), "{json}");
+    }
+
+    #[test]
+    fn test_decode_full_url() {
+        let url = sfo_jfk().get_search_url().replace('+', "%2B");
+        let json = decode_to_json(&url).unwrap();
+        assert!(json.contains("\"SFO\""), "{json}");
+        assert!(json.contains("\"currency\": \"USD\""), "{json}");
+        assert!(decode_to_json("https://www.google.com/travel/flights?hl=en").is_err());
+    }
+
+    #[test]
+    fn test_search_args_required_without_decode() {
+        assert!(CliArgs::try_parse_from(["delulu-flights", "--from", "SFO"]).is_err());
+        assert!(CliArgs::try_parse_from(["delulu-flights", "--decode"]).is_err());
+    }
+
+    #[test]
+    fn test_title_bar_date_format() {
+        let args = CliArgs::try_parse_from([
+            "delulu-flights",
+            "-f",
+            "SFO",
+            "-t",
+            "JFK",
+            "-d",
+            "2030-01-15",
+            "--date-format",
+            "%d %b %Y",
+        ])
+        .unwrap();
+        check_date_format(&args.date_format).unwrap();
+        let title = title_bar(&sfo_jfk(), &args.date_format);
+        assert!(title.contains("SFO → JFK on 15 Jan 2030"), "{title}");
+
+        let default = CliArgs::try_parse_from(["delulu-flights", "-f", "A", "-t", "B", "-d", "x"]);
+        let title = title_bar(&sfo_jfk(), &default.unwrap().date_format);
+        assert!(title.contains("on 2030-01-15"), "{title}");
+
+        assert!(check_date_format("%Y-%Q").is_err());
+    }
+}
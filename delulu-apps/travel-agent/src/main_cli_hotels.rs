@@ -49,29 +49,41 @@
 //! delulu-hotels -L "Tokyo" -i 2026-02-15 -o 2026-02-20 --dry-run
 //! ```
 //!
+//! ## Decode an existing search
+//!
+//! ```bash
+//! # Print the parameters behind a Google Hotels URL (or bare `ts` value) as JSON
+//! delulu-hotels --decode --ts "https://www.google.com/travel/search?q=Tokyo&ts=..."
+//! ```
+//!
 //! # Output
 //!
-//! The tool prints a summary of the search parameters followed by matching hotels with:
+//! The tool prints a summary of the search parameters followed by a table of
+//! matching hotels with:
 //! - Name and star rating
 //! - Price per night
 //! - Guest rating and review count
-//! - Available amenities
+//! - Location rating
+//! - Amenities (`--show-amenities`)
+//!
+//! Columns are truncated to fit the terminal width.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::{Parser, ValueEnum};
-use delulu_travel_agent::{Amenity, GoogleHotelsClient, HotelSearchParams};
+use delulu_travel_agent::{Amenity, Availability, GoogleHotelsClient, Hotel, HotelSearchParams};
+use std::cmp::max;
 
 #[derive(Parser, Debug)]
 #[command(name = "delulu-hotels")]
 #[command(version = "0.1.0")]
 #[command(about = "Search hotels via Google Hotels API")]
 struct Args {
-    #[arg(short = 'L', long)]
-    location: String,
-    #[arg(short = 'i', long)]
-    checkin: String,
-    #[arg(short = 'o', long)]
-    checkout: String,
+    #[arg(short = 'L', long, required_unless_present = "decode")]
+    location: Option<String>,
+    #[arg(short = 'i', long, required_unless_present = "decode")]
+    checkin: Option<String>,
+    #[arg(short = 'o', long, required_unless_present = "decode")]
+    checkout: Option<String>,
     #[arg(short = 'a', long, default_value = "2")]
     adults: u32,
     #[arg(
@@ -102,6 +114,136 @@ struct Args {
     limit: usize,
     #[arg(long, help = "Show search URL without making request")]
     dry_run: bool,
+    #[arg(long, help = "Add an amenities column to the results table")]
+    show_amenities: bool,
+    #[arg(
+        long,
+        requires = "ts",
+        help = "Print the search parameters encoded in --ts as JSON and exit"
+    )]
+    decode: bool,
+    #[arg(
+        long,
+        value_name = "TS_OR_URL",
+        requires = "decode",
+        help = "ts parameter to decode, raw or as a full Google Hotels URL"
+    )]
+    ts: Option<String>,
+}
+
+/// Optional columns of the results table.
+#[derive(Debug, Clone, Copy, Default)]
+struct ExtraColumns {
+    amenities: bool,
+}
+
+fn get_terminal_width() -> usize {
+    term_size::dimensions().map(|(w, _)| w).unwrap_or(100)
+}
+
+/// Shortens `s` to `width` characters, marking the cut with "…".
+fn truncate(s: &str, width: usize) -> String {
+    if s.chars().count() <= width {
+        return s.to_string();
+    }
+    let mut out: String = s.chars().take(width.saturating_sub(1)).collect();
+    out.push('…');
+    out
+}
+
+/// Renders `hotels` as a table no wider than `terminal_width` when possible:
+/// the free-text columns (name, amenities) shrink proportionally, down
+/// to a readable minimum.
+fn render_hotels_table(hotels: &[Hotel], columns: ExtraColumns, terminal_width: usize) -> String {
+    let stars = |h: &Hotel| h.star_class.as_deref().unwrap_or("").trim().to_string();
+    let rating = |h: &Hotel| match h.rating {
+        Some(r) => format!("{:.1} ({})", r, h.reviews.unwrap_or(0)),
+        None => String::new(),
+    };
+    let location = |h: &Hotel| h.location_rating.clone().unwrap_or_default();
+    let amenities = |h: &Hotel| h.amenities.join(", ");
+    let price = |h: &Hotel| match h.availability {
+        Availability::SoldOut => "Sold out".to_string(),
+        _ => h.price.clone(),
+    };
+
+    let rank_width = max(1, hotels.len().to_string().len());
+    let widest = |header: &str, cell: &dyn Fn(&Hotel) -> String| {
+        hotels
+            .iter()
+            .map(|h| cell(h).chars().count())
+            .fold(header.len(), max)
+    };
+    let stars_width = widest("STARS", &stars);
+    let price_width = widest("PRICE", &price);
+    let rating_width = widest("RATING", &rating);
+    let location_width = widest("LOCATION", &location);
+    let mut name_width = widest("NAME", &|h| h.name.clone());
+    let mut amenities_width = if columns.amenities {
+        widest("AMENITIES", &amenities)
+    } else {
+        0
+    };
+
+    // Two spaces between columns
+    let column_count = 6 + usize::from(columns.amenities);
+    let fixed = rank_width
+        + stars_width
+        + price_width
+        + rating_width
+        + location_width
+        + 2 * (column_count - 1);
+    let flexible = name_width + amenities_width;
+    let available = terminal_width.saturating_sub(fixed);
+    if flexible > available {
+        let ratio = available as f64 / flexible as f64;
+        let shrink = |w: usize, min: usize| max((w as f64 * ratio).floor() as usize, min.min(w));
+        name_width = shrink(name_width, 12);
+        amenities_width = shrink(amenities_width, 10);
+    }
+
+    let row = |cells: [&str; 7]| {
+        let [rank, name, stars, price, rating, location, amenities] = cells;
+        let mut line = format!(
+            "{:>rank_width$}  {:<name_width$}  {:<stars_width$}  {:>price_width$}  {:<rating_width$}  {:<location_width$}",
+            rank,
+            truncate(name, name_width),
+            stars,
+            price,
+            rating,
+            location,
+        );
+        if columns.amenities {
+            line.push_str(&format!(
+                "  {:<amenities_width$}",
+                truncate(amenities, amenities_width)
+            ));
+        }
+        line.trim_end().to_string()
+    };
+
+    let mut lines = vec![row([
+        "#",
+        "NAME",
+        "STARS",
+        "PRICE",
+        "RATING",
+        "LOCATION",
+        "AMENITIES",
+    ])];
+    lines.push("-".repeat(lines[0].chars().count()));
+    for (i, hotel) in hotels.iter().enumerate() {
+        lines.push(row([
+            &(i + 1).to_string(),
+            &hotel.name,
+            &stars(hotel),
+            &price(hotel),
+            &rating(hotel),
+            &location(hotel),
+            &amenities(hotel),
+        ]));
+    }
+    lines.join("\n")
 }
 
 #[derive(Clone, Debug, ValueEnum)]
@@ -179,12 +321,42 @@ fn parse_stars(s: &str) -> Result<Vec<i32>> {
         .collect()
 }
 
+/// Extract the `ts` parameter when given a full search URL.
+fn ts_param(input: &str) -> Result<String> {
+    let Some((_, query)) = input.split_once('?') else {
+        return Ok(input.trim().to_string());
+    };
+    let raw = query
+        .split('&')
+        .find_map(|kv| kv.strip_prefix("ts="))
+        .context("URL has no ts parameter")?;
+    Ok(urlencoding::decode(raw)
+        .context("ts parameter is not valid UTF-8")?
+        .into_owned())
+}
+
+/// Decode a `ts` (raw or inside a URL) into pretty-printed search parameters.
+fn decode_to_json(input: &str) -> Result<String> {
+    let params = HotelSearchParams::from_ts(&ts_param(input)?).context("Invalid ts")?;
+    serde_json::to_string_pretty(&params).context("Failed to serialize search parameters")
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
 
-    let checkin = parse_date(&args.checkin)?;
-    let checkout = parse_date(&args.checkout)?;
+    if let Some(ts) = &args.ts {
+        println!("{}", decode_to_json(ts)?);
+        return Ok(());
+    }
+
+    let (Some(location), Some(checkin), Some(checkout)) =
+        (args.location, args.checkin, args.checkout)
+    else {
+        anyhow::bail!("--location, --checkin and --checkout are required unless --decode is given");
+    };
+    let checkin = parse_date(&checkin)?;
+    let checkout = parse_date(&checkout)?;
 
     let children_ages = args
         .children
@@ -222,7 +394,7 @@ async fn main() -> Result<()> {
     };
 
     let request = HotelSearchParams::builder(
-        args.location.clone(),
+        location.clone(),
         checkin,
         checkout,
         args.adults,
@@ -243,7 +415,7 @@ async fn main() -> Result<()> {
 
     println!("\n🏨 Google Hotels Search");
     println!("=======================");
-    println!("Location: {}", args.location);
+    println!("Location: {}", location);
     println!("Dates: {} to {}", checkin, checkout);
     println!(
         "Guests: {} adults, {} children",
@@ -293,25 +465,14 @@ async fn main() -> Result<()> {
                 if let Some(ref lowest) = results.lowest_price {
                     println!("Lowest: {}\n", lowest);
                 }
-                for (i, hotel) in results.hotels().take(args.limit).enumerate() {
-                    let stars = hotel.star_class.as_deref().unwrap_or("");
-                    println!("{}. {}", i + 1, hotel.name);
-                    if !stars.is_empty() {
-                        println!("   {}", stars.trim());
-                    }
-                    println!("   Price: {}", hotel.price);
-                    if let Some(r) = hotel.rating {
-                        let reviews = hotel.reviews.unwrap_or(0);
-                        println!("   Rating: {:.1} ({} reviews)", r, reviews);
-                    }
-                    if !hotel.amenities.is_empty() {
-                        println!("   Amenities: {}", hotel.amenities.join(", "));
-                    }
-                    if let Some(loc) = &hotel.location_rating {
-                        println!("   Location: {}", loc);
-                    }
-                    println!();
-                }
+                let columns = ExtraColumns {
+                    amenities: args.show_amenities,
+                };
+                let shown: Vec<Hotel> = results.hotels().take(args.limit).cloned().collect();
+                println!(
+                    "{}",
+                    render_hotels_table(&shown, columns, get_terminal_width())
+                );
             }
         }
         Err(e) => {
@@ -321,3 +482,90 @@ async fn main() -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hotel(name: &str, amenities: &[&str], location_rating: Option<&str>) -> Hotel {
+        Hotel {
+            name: name.to_string(),
+            price: "$180".to_string(),
+            rating: Some(4.4),
+            reviews: Some(1203),
+            amenities: amenities.iter().map(|a| a.to_string()).collect(),
+            location_rating: location_rating.map(str::to_string),
+            distance_km: None,
+            star_class: Some("4-star hotel".to_string()),
+            url: None,
+            address: None,
+            taxes_included: None,
+            price_per_person: None,
+            availability: Availability::Available,
+        }
+    }
+
+    fn hotels() -> Vec<Hotel> {
+        vec![
+            hotel(
+                "Hotel Lutetia",
+                &["spa", "pool"],
+                Some("Excellent location"),
+            ),
+            hotel("Le Meurice", &["air_conditioned"], None),
+        ]
+    }
+
+    #[test]
+    fn test_amenity_column_only_when_requested() {
+        let compact = render_hotels_table(&hotels(), ExtraColumns::default(), 120);
+        assert!(
+            compact.lines().next().unwrap().ends_with("LOCATION"),
+            "{compact}"
+        );
+        assert!(!compact.contains("AMENITIES"));
+        assert!(!compact.contains("spa, pool"));
+        assert!(
+            compact
+                .lines()
+                .nth(2)
+                .unwrap()
+                .ends_with("Excellent location"),
+            "{compact}"
+        );
+
+        let columns = ExtraColumns { amenities: true };
+        let table = render_hotels_table(&hotels(), columns, 120);
+        assert!(table.contains("AMENITIES"), "{table}");
+        assert!(
+            table.lines().nth(2).unwrap().ends_with("spa, pool"),
+            "{table}"
+        );
+        assert!(table.contains("Excellent location"), "{table}");
+    }
+
+    #[test]
+    fn test_table_truncates_to_terminal_width() {
+        let mut hotels = hotels();
+        hotels[0].name = "Hotel With An Exceptionally Long Name By The Seine".to_string();
+        let columns = ExtraColumns { amenities: true };
+        let table = render_hotels_table(&hotels, columns, 100);
+        assert!(table.lines().all(|l| l.chars().count() <= 100), "{table}");
+        assert!(table.contains('…'), "{table}");
+    }
+
+    #[test]
+    fn test_decode_ts_from_url() {
+        let checkin = chrono::NaiveDate::from_ymd_opt(2030, 3, 1).unwrap();
+        let checkout = chrono::NaiveDate::from_ymd_opt(2030, 3, 5).unwrap();
+        let url = HotelSearchParams::builder("Paris".into(), checkin, checkout, 2, vec![])
+            .build()
+            .unwrap()
+            .get_search_url();
+        let args = Args::try_parse_from(["delulu-hotels", "--decode", "--ts", &url]).unwrap();
+        let json = decode_to_json(args.ts.as_deref().unwrap()).unwrap();
+        assert!(json.contains("2030-03-01"), "{json}");
+        assert!(json.contains("2030-03-05"), "{json}");
+        assert!(Args::try_parse_from(["delulu-hotels", "--decode"]).is_err());
+    }
+}
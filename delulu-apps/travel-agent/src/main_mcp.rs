@@ -20,21 +20,31 @@
 //! Supports stdio transport via subcommand.
 
 use anyhow::{Context, Error, Result};
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use delulu_query_queues::QueryQueue;
 use delulu_travel_agent::{
-    Amenity, FlightSearchParams, GoogleFlightsClient, GoogleHotelsClient, HotelSearchParams, Seat,
-    Trip,
+    Amenity, CabinComparison, CheapestWeekend, DEFAULT_MAX_PASSENGERS, FileSessionStore, FlightLeg,
+    FlightProvider, FlightSearchParams, FlightSearchResult, FlightSort, GoogleFlightsClient,
+    GoogleHotelsClient, HotelPreferences, HotelProvider, HotelSearchParams, Itinerary,
+    McpFlightResponse, MemorySessionStore, PersistentSessionManager, SearchDiagnosis, Seat,
+    SessionStore, SortType, Trip, ValueWeights, cheapest_weekend, compare_cabins, nonstop_hint,
 };
+use once_cell::sync::Lazy;
 use rmcp::handler::server::{ServerHandler, tool::ToolRouter, wrapper::Parameters};
 use rmcp::service::serve_server;
 use rmcp::tool;
 use rmcp::tool_router;
-use rmcp::transport::streamable_http_server::{
-    StreamableHttpServerConfig, StreamableHttpService, session::local::LocalSessionManager,
-};
+use rmcp::transport::streamable_http_server::{StreamableHttpServerConfig, StreamableHttpService};
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::future::Future;
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
 use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
 
 #[derive(Parser, Debug)]
@@ -47,12 +57,19 @@ use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitEx
 struct Args {
     #[command(subcommand)]
     command: Command,
+
+    /// Check search_flights/search_hotels responses against src/schemas before sending them
+    #[arg(long, global = true)]
+    strict_schema: bool,
 }
 
 #[derive(Subcommand, Debug)]
 enum Command {
     /// Run MCP server over stdio (for Claude Desktop, etc.)
-    Stdio,
+    Stdio {
+        #[command(flatten)]
+        queue: QueueArgs,
+    },
 
     /// Run MCP server over HTTP
     Http {
@@ -61,10 +78,93 @@ enum Command {
 
         #[arg(long, default_value = "8080")]
         port: u16,
+
+        /// Where to keep MCP sessions; `file` lets clients resume after a restart
+        #[arg(long, value_enum, default_value = "memory")]
+        session_store: SessionStoreKind,
+
+        /// Directory for session files (required with `--session-store file`)
+        #[arg(long, required_if_eq("session_store", "file"))]
+        session_dir: Option<PathBuf>,
+
+        #[command(flatten)]
+        queue: QueueArgs,
+    },
+
+    /// Write the JSON Schemas of the tool inputs, to keep src/schemas in sync
+    ExportSchemas {
+        #[arg(default_value = "src/schemas")]
+        dir: PathBuf,
     },
 }
 
-#[derive(Serialize, Deserialize)]
+impl Command {
+    /// Rate limits of the server commands, `None` for the offline ones.
+    fn queue_args(&self) -> Option<&QueueArgs> {
+        match self {
+            Command::Stdio { queue } | Command::Http { queue, .. } => Some(queue),
+            Command::ExportSchemas { .. } => None,
+        }
+    }
+}
+
+/// Writes the `schemars` schemas of [`FlightsInput`] and [`HotelsInput`] to
+/// `flights-input.json` and `hotels-input.json` in `dir`, returning the
+/// paths written.
+fn export_schemas(dir: &Path) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let schemas = [
+        ("flights-input.json", schemars::schema_for!(FlightsInput)),
+        ("hotels-input.json", schemars::schema_for!(HotelsInput)),
+    ];
+    let mut written = Vec::with_capacity(schemas.len());
+    for (name, schema) in schemas {
+        let path = dir.join(name);
+        let json = serde_json::to_string_pretty(&schema)? + "\n";
+        std::fs::write(&path, json)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// How hard the server may hit Google, shared by the flights and hotels clients.
+#[derive(clap::Args, Debug)]
+struct QueueArgs {
+    /// Maximum number of in-flight searches
+    #[arg(long)]
+    max_concurrent: Option<u64>,
+
+    /// Maximum number of searches started per second
+    #[arg(long)]
+    qps: Option<u64>,
+}
+
+impl QueueArgs {
+    /// The shared queue, or `None` to keep each client's default limits.
+    fn query_queue(&self) -> Result<Option<QueryQueue>> {
+        if self.max_concurrent == Some(0) || self.qps == Some(0) {
+            anyhow::bail!("--max-concurrent and --qps must be positive");
+        }
+        let queue = match (self.max_concurrent, self.qps) {
+            (None, None) => return Ok(None),
+            (Some(max_concurrent), None) => QueryQueue::with_concurrency_limit(max_concurrent),
+            (None, Some(qps)) => QueryQueue::with_qps_limit(qps),
+            (Some(max_concurrent), Some(qps)) => {
+                QueryQueue::with_qps_limit(qps).max_concurrent(max_concurrent)
+            }
+        };
+        Ok(Some(queue))
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum SessionStoreKind {
+    Memory,
+    File,
+}
+
+#[derive(Serialize, Deserialize, Default)]
 #[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub struct FlightsInput {
@@ -78,16 +178,679 @@ pub struct FlightsInput {
     pub adults: u32,
     #[serde(default)]
     pub children_ages: Vec<i32>,
+    /// Infants under 2 travelling on an adult's lap, without a seat
+    #[serde(default)]
+    pub infants_on_lap: u32,
+    /// Infants under 2 with their own seat, which is paid for
+    #[serde(default)]
+    pub infants_in_seat: u32,
     #[serde(default)]
     #[serde(alias = "round-trip")]
     #[serde(alias = "one-way")]
     pub trip_type: Trip,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub max_stops: Option<i32>,
+    /// Drop itineraries with a connection shorter than this many minutes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_layover_minutes: Option<i32>,
+    /// Drop itineraries longer than this many minutes door to door
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_total_minutes: Option<i32>,
+    /// Result order: price, duration, stops or best_value (default: Google's order)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort: Option<FlightSort>,
+    /// Weights for best_value sorting: price, duration, stop_penalty
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value_weights: Option<ValueWeights>,
+    /// List nonstops first (cheapest first unless `sort` is set), keeping connections after them
+    #[serde(default)]
+    pub prefer_direct: bool,
+    /// Return aggregates (lowest and median price, fastest duration, nonstop count, airlines) instead of every itinerary
+    #[serde(default)]
+    pub summary_only: bool,
+    /// Return the itineraries bucketed by number of stops, with a count per bucket, instead of one list
+    #[serde(default)]
+    pub group_by_stops: bool,
+    /// Multi-city trip: every leg in order, at least two. Forces trip_type to multi_city; from, to, date and return_date are then ignored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub legs: Option<Vec<FlightLegInput>>,
     // pub preferred_airlines: Option<Vec<String>>,
     // pub currency: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub struct FlightLegInput {
+    pub from: String,
+    pub to: String,
+    /// YYYY-MM-DD
+    pub date: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub struct WeekendInput {
+    pub from: String,
+    pub to: String,
+    /// Target month, YYYY-MM
+    pub month: String,
+    #[serde(default)]
+    pub seat: Seat,
+    pub adults: u32,
+    #[serde(default)]
+    pub children_ages: Vec<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_stops: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub struct CompareCabinsInput {
+    pub from: String,
+    pub to: String,
+    pub date: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_date: Option<String>,
+    pub adults: u32,
+    #[serde(default)]
+    pub children_ages: Vec<i32>,
+    #[serde(default)]
+    #[serde(alias = "round-trip")]
+    #[serde(alias = "one-way")]
+    pub trip_type: Trip,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_stops: Option<i32>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct McpCompareCabinsResponse {
+    pub compare_cabins: CabinComparison,
+}
+
+/// Whether a search with the given input would be sent, and why not.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct McpValidation {
+    pub valid: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+    /// Ignored inputs, which do not make the search invalid
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+impl McpValidation {
+    fn new(checked: anyhow::Result<()>, warnings: Vec<String>) -> Self {
+        let errors: Vec<String> = checked
+            .err()
+            .map(|e| format!("{e:#}"))
+            .into_iter()
+            .collect();
+        Self {
+            valid: errors.is_empty(),
+            errors,
+            warnings,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct McpValidateFlightSearchResponse {
+    pub validate_flight_search: McpValidation,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct McpValidateHotelSearchResponse {
+    pub validate_hotel_search: McpValidation,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct McpCheapestWeekendResponse {
+    pub cheapest_weekend: CheapestWeekend,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct McpDiagnosisResponse {
+    pub diagnose_last_search: SearchDiagnosis,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct McpListOptionsResponse {
+    pub list_options: McpOptions,
+}
+
+/// Accepted values of the enumerated tool parameters.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct McpOptions {
+    pub seats: Vec<McpOption>,
+    pub trip_types: Vec<McpOption>,
+    pub flight_sorts: Vec<McpOption>,
+    pub amenities: Vec<McpOption>,
+    pub hotel_sorts: Vec<McpOption>,
+    pub stars: Vec<i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct McpOption {
+    pub value: String,
+    /// Other spellings accepted for `value`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    pub label: String,
+}
+
+impl McpOption {
+    /// Option for `variant`, named as it serializes, e.g. `premium_economy`
+    /// labelled "Premium economy".
+    fn new(variant: impl Serialize, aliases: &[&str]) -> Self {
+        let value = serde_json::to_value(variant)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+        let spaced = value.replace('_', " ");
+        let mut chars = spaced.chars();
+        let label = chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default();
+        Self {
+            value,
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            label,
+        }
+    }
+}
+
+impl McpOptions {
+    fn new() -> Self {
+        Self {
+            seats: vec![
+                McpOption::new(Seat::Economy, &["Economy"]),
+                McpOption::new(Seat::PremiumEconomy, &["PremiumEconomy"]),
+                McpOption::new(Seat::Business, &["Business"]),
+                McpOption::new(Seat::First, &["First"]),
+            ],
+            trip_types: vec![
+                McpOption::new(Trip::RoundTrip, &["round-trip"]),
+                McpOption::new(Trip::OneWay, &["one-way"]),
+                McpOption::new(Trip::MultiCity, &[]),
+            ],
+            flight_sorts: [
+                FlightSort::Price,
+                FlightSort::Duration,
+                FlightSort::Stops,
+                FlightSort::BestValue,
+            ]
+            .into_iter()
+            .map(|sort| McpOption::new(sort, &[]))
+            .collect(),
+            amenities: Amenity::all()
+                .iter()
+                .map(|amenity| McpOption::new(amenity, &amenity.aliases()[1..]))
+                .collect(),
+            hotel_sorts: [
+                SortType::LowestPrice,
+                SortType::HighestRating,
+                SortType::MostReviewed,
+            ]
+            .into_iter()
+            .map(|sort| McpOption::new(sort, &[]))
+            .collect(),
+            stars: (2..=5).collect(),
+        }
+    }
+}
+
+fn parse_month(month: &str) -> Result<(i32, u32), String> {
+    let invalid = || format!("Invalid month '{month}', expected YYYY-MM");
+    let (year, month) = month.split_once('-').ok_or_else(invalid)?;
+    Ok((
+        year.parse().map_err(|_| invalid())?,
+        month.parse().map_err(|_| invalid())?,
+    ))
+}
+
+/// Maximum number of searches accepted by `search_flights_batch`.
+const MAX_BATCH_SEARCHES: usize = 5;
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub struct FlightsBatchInput {
+    pub searches: Vec<FlightsInput>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub struct CompareFlightsInput {
+    pub a: FlightsInput,
+    pub b: FlightsInput,
+}
+
+/// Which side of a comparison has the lower cheapest fare.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Cheaper {
+    A,
+    B,
+    Tie,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct McpCompareFlightsResponse {
+    pub compare_flights: McpFlightComparison,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct McpFlightComparison {
+    pub a: McpFlightResponse,
+    pub b: McpFlightResponse,
+    /// Absent when either side has no priced itinerary
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cheaper: Option<Cheaper>,
+    /// Difference between the two cheapest fares, always >= 0
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price_delta: Option<i32>,
+}
+
+impl McpFlightComparison {
+    fn new(a: McpFlightResponse, b: McpFlightResponse) -> Self {
+        let (cheaper, price_delta) =
+            match (a.search_flights.lowest_price, b.search_flights.lowest_price) {
+                (Some(pa), Some(pb)) => {
+                    let cheaper = match pa.cmp(&pb) {
+                        std::cmp::Ordering::Less => Cheaper::A,
+                        std::cmp::Ordering::Greater => Cheaper::B,
+                        std::cmp::Ordering::Equal => Cheaper::Tie,
+                    };
+                    (Some(cheaper), Some((pa - pb).abs()))
+                }
+                _ => (None, None),
+            };
+        Self {
+            a,
+            b,
+            cheaper,
+            price_delta,
+        }
+    }
+}
+
+/// Outcome of one batch entry, keyed by its position in the input array.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub struct BatchEntry<T> {
+    pub index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct McpFlightsBatchResponse {
+    pub search_flights_batch: McpBatchResults<McpFlightResponse>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct McpBatchResults<T> {
+    pub total: usize,
+    pub results: Vec<BatchEntry<T>>,
+}
+
+/// Runs `search` concurrently over `inputs`, reporting failures per entry.
+///
+/// Rate limiting is left to the client's shared `QueryQueue`.
+async fn run_batch<I, T, F, Fut>(inputs: Vec<I>, search: F) -> Result<Vec<BatchEntry<T>>, String>
+where
+    F: Fn(I) -> Fut,
+    Fut: Future<Output = Result<T, String>> + Send + 'static,
+    T: Send + 'static,
+{
+    if inputs.is_empty() {
+        return Err("Batch must contain at least one search".to_string());
+    }
+    if inputs.len() > MAX_BATCH_SEARCHES {
+        return Err(format!(
+            "Batch has {} searches, maximum is {}",
+            inputs.len(),
+            MAX_BATCH_SEARCHES
+        ));
+    }
+
+    let mut tasks = JoinSet::new();
+    for (index, input) in inputs.into_iter().enumerate() {
+        let fut = search(input);
+        tasks.spawn(async move { (index, fut.await) }.in_current_span());
+    }
+
+    let mut entries = Vec::with_capacity(tasks.len());
+    while let Some(joined) = tasks.join_next().await {
+        let (index, outcome) = joined.map_err(|e| format!("Batch task failed: {e}"))?;
+        entries.push(match outcome {
+            Ok(result) => BatchEntry {
+                index,
+                result: Some(result),
+                error: None,
+            },
+            Err(error) => BatchEntry {
+                index,
+                result: None,
+                error: Some(error),
+            },
+        });
+    }
+    entries.sort_by_key(|e| e.index);
+    Ok(entries)
+}
+
+/// Search parameters for a `search_hotels` input, with the preferences read
+/// from it and warnings about the amenities and preferences it ignores.
+fn hotel_search_params(input: HotelsInput) -> (HotelSearchParams, HotelPreferences, Vec<String>) {
+    let (valid_amenities, invalid_amenities): (Vec<_>, Vec<_>) = input
+        .amenities
+        .iter()
+        .partition(|a| Amenity::from_str_name(a).is_some());
+
+    let mut warnings: Vec<String> = Vec::new();
+    for unknown in &invalid_amenities {
+        warnings.push(unknown_amenity_warning(unknown));
+    }
+
+    let mut amenities: Vec<Amenity> = valid_amenities
+        .iter()
+        .filter_map(|a| Amenity::from_str_name(a))
+        .collect();
+    let mut stars = input.stars;
+    let preferences = input
+        .preferences
+        .as_deref()
+        .map(HotelPreferences::parse)
+        .unwrap_or_default();
+    for amenity in &preferences.amenities {
+        if !amenities.contains(amenity) {
+            amenities.push(*amenity);
+        }
+    }
+    // Explicit star filters win over stars read from the preferences.
+    if stars.is_empty() {
+        stars = preferences.stars.clone();
+    }
+    for phrase in &preferences.unmapped {
+        warnings.push(format!(
+            "Preference '{phrase}' does not match any filter and was ignored"
+        ));
+    }
+    let params = HotelSearchParams {
+        version: 1,
+        adults: input.adults,
+        children_ages: input.children_ages,
+        loc_q_search: input.location,
+        loc_ts_name: String::new(),
+        loc_ts_id: String::new(),
+        loc_ts_coords: String::new(),
+        checkin_date: input.checkin_date,
+        checkout_date: input.checkout_date,
+        nights: 0,
+        used_guests_dropdown: 0,
+        currency: "USD".to_string(),
+        language: "en".to_string(),
+        sort_order: None,
+        min_guest_rating: input.min_guest_rating,
+        hotel_stars: stars,
+        amenities,
+        min_price: input.min_price,
+        max_price: input.max_price,
+    };
+    (params, preferences, warnings)
+}
+
+/// [`flight_search_params`], checked as a search would check them.
+fn validated_flight_search_params(input: FlightsInput) -> anyhow::Result<FlightSearchParams> {
+    if let Some(legs) = &input.legs {
+        anyhow::ensure!(
+            legs.len() >= 2,
+            "Multi-city searches need at least two legs, got {}",
+            legs.len()
+        );
+    }
+    let params = flight_search_params(input);
+    params.validate()?;
+    Ok(params)
+}
+
+fn flight_search_params(input: FlightsInput) -> FlightSearchParams {
+    let mut passengers = vec![(delulu_travel_agent::Passenger::Adult, input.adults)];
+    if !input.children_ages.is_empty() {
+        passengers.push((
+            delulu_travel_agent::Passenger::Child,
+            input.children_ages.len() as u32,
+        ));
+    }
+    if input.infants_on_lap > 0 {
+        passengers.push((
+            delulu_travel_agent::Passenger::InfantOnLap,
+            input.infants_on_lap,
+        ));
+    }
+    if input.infants_in_seat > 0 {
+        passengers.push((
+            delulu_travel_agent::Passenger::InfantInSeat,
+            input.infants_in_seat,
+        ));
+    }
+    let (from, to, date, return_date, trip_type, extra_legs) = match input.legs {
+        Some(legs) => {
+            let mut legs = legs.into_iter();
+            let first = legs.next().unwrap_or_default();
+            let extra_legs = legs
+                .map(|leg| FlightLeg {
+                    from_airport: leg.from,
+                    to_airport: leg.to,
+                    date: leg.date,
+                })
+                .collect();
+            (
+                first.from,
+                first.to,
+                first.date,
+                None,
+                Trip::MultiCity,
+                extra_legs,
+            )
+        }
+        None => (
+            input.from,
+            input.to,
+            input.date,
+            input.return_date,
+            input.trip_type,
+            Vec::new(),
+        ),
+    };
+    FlightSearchParams {
+        from_airport: from,
+        to_airport: to,
+        depart_date: date,
+        return_date,
+        cabin_class: input.seat,
+        passengers,
+        trip_type,
+        max_stops: input.max_stops,
+        preferred_airlines: None,
+        outbound_preferred_airlines: None,
+        return_max_stops: None,
+        return_preferred_airlines: None,
+        include_nearby_km: None,
+        extra_legs,
+        max_passengers: DEFAULT_MAX_PASSENGERS,
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub struct ItineraryDetailInput {
+    /// `id` of an itinerary returned by search_flights
+    pub id: String,
+    /// The search_flights input the itinerary came from
+    pub search: FlightsInput,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct McpItineraryDetailResponse {
+    pub get_flight_itinerary_detail: Itinerary,
+}
+
+/// Number of flight results kept by [`RecentSearches`].
+const RECENT_SEARCHES: usize = 16;
+
+/// How long a [`RecentSearches`] entry is served before fares are considered
+/// stale and Google is queried again.
+const RECENT_SEARCH_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Unfiltered results of the latest flight searches, by search URL, so that
+/// asking for an itinerary's details right after a search does not query
+/// Google again.
+#[derive(Default)]
+struct RecentSearches {
+    entries: Mutex<VecDeque<(String, Instant, FlightSearchResult)>>,
+}
+
+impl RecentSearches {
+    /// The stored result for `search_url`, unless older than
+    /// [`RECENT_SEARCH_TTL`].
+    fn get(&self, search_url: &str) -> Option<FlightSearchResult> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .iter()
+            .find(|(url, stored_at, _)| {
+                url == search_url && stored_at.elapsed() < RECENT_SEARCH_TTL
+            })
+            .map(|(_, _, result)| result.clone())
+    }
+
+    /// Stores `result`, evicting the oldest entry when full. The raw HTML is
+    /// dropped, only the parsed itineraries are needed.
+    fn insert(&self, search_url: String, result: &FlightSearchResult) {
+        let result = FlightSearchResult {
+            raw_response: String::new(),
+            ..result.clone()
+        };
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|(url, _, _)| *url != search_url);
+        if entries.len() == RECENT_SEARCHES {
+            entries.pop_front();
+        }
+        entries.push_back((search_url, Instant::now(), result));
+    }
+}
+
+/// Warning for an amenity name we don't know, with the closest valid ones.
+fn unknown_amenity_warning(unknown: &str) -> String {
+    let suggestions: Vec<String> = Amenity::suggestions(unknown)
+        .iter()
+        .map(|a| format!("'{}'", a.as_str_name()))
+        .collect();
+    if suggestions.is_empty() {
+        let valid_list = Amenity::all()
+            .iter()
+            .map(Amenity::as_str_name)
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("Unknown amenity '{unknown}'; valid amenities: {valid_list}.")
+    } else {
+        format!(
+            "Unknown amenity '{unknown}'; did you mean {}?",
+            suggestions.join(" or ")
+        )
+    }
+}
+
+/// Runs one `search_flights` request. The search is abandoned when `ct`, the
+/// MCP request's cancellation token, fires.
+async fn search_one_flight(
+    client: Arc<dyn FlightProvider>,
+    recent: Arc<RecentSearches>,
+    input: FlightsInput,
+    ct: CancellationToken,
+) -> Result<McpFlightResponse, String> {
+    let min_layover_minutes = input.min_layover_minutes;
+    let max_total_minutes = input.max_total_minutes;
+    let sort = input.sort;
+    let value_weights = input.value_weights.unwrap_or_default();
+    let prefer_direct = input.prefer_direct;
+    let summary_only = input.summary_only;
+    let group_by_stops = input.group_by_stops;
+    let infants_in_seat = input.infants_in_seat;
+    let nonstop_only = input.max_stops == Some(0);
+    // Caught here rather than by the provider, as the search URL needs valid params
+    let params =
+        validated_flight_search_params(input).map_err(|e| format!("Flight search failed: {e}"))?;
+    let hint = nonstop_only
+        .then(|| nonstop_hint(&params.from_airport, &params.to_airport))
+        .flatten();
+    let mut result = client
+        .search_cancellable(&params, ct)
+        .await
+        .map_err(|e| match &hint {
+            Some(hint) => format!("Flight search failed: {e}. {hint}"),
+            None => format!("Flight search failed: {e}"),
+        })?;
+    recent.insert(params.get_search_url(), &result);
+    // Google found nothing, as opposed to the local filters below dropping it all
+    let found_none = result.itineraries.is_empty();
+    if let Some(minutes) = min_layover_minutes {
+        result.filter_min_layover(minutes);
+    }
+    if let Some(minutes) = max_total_minutes {
+        result.filter_max_duration(minutes);
+    }
+    if let Some(sort) = sort {
+        result.sort_itineraries(sort, &value_weights);
+    }
+    if prefer_direct {
+        if sort.is_none() {
+            result.sort_itineraries(FlightSort::Price, &value_weights);
+        }
+        result.prefer_direct();
+    }
+    if found_none {
+        result.warnings.extend(hint);
+    }
+    if infants_in_seat > 0 {
+        result.warnings.push(format!(
+            "{infants_in_seat} infant(s) in seat occupy a seat of their own and need a paid (often discounted) fare; infants on lap travel free or for a small fee"
+        ));
+    }
+    let mut response = result.to_mcp_api_response(result.warnings.clone());
+    if summary_only {
+        response.search_flights.summarize();
+    } else if group_by_stops {
+        response
+            .search_flights
+            .group_by_stops(result.grouped_by_stops());
+    }
+    Ok(response)
+}
+
 #[derive(Serialize, Deserialize, Default)]
 #[cfg_attr(feature = "mcp", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
@@ -109,129 +872,338 @@ pub struct HotelsInput {
     pub min_price: Option<i32>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub max_price: Option<i32>,
+    /// On zero results, drop the most restrictive filter (price, then amenities, then stars) and search once more
+    #[serde(default)]
+    pub relax_on_empty: bool,
+    /// Free-text wishes such as "pet friendly with a pool", mapped onto amenities, stars and amenity chips; unmatched phrases are reported as warnings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preferences: Option<String>,
+    /// Drop hotels farther than this many kilometers from the center of the searched area, or without a known distance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub within_km: Option<f64>,
 }
 
+/// Compiles one of the committed response schemas; they ship with the binary.
+fn response_validator(schema: &str) -> jsonschema::Validator {
+    let schema: serde_json::Value =
+        serde_json::from_str(schema).expect("committed response schema is valid JSON");
+    jsonschema::Validator::new(&schema).expect("committed response schema compiles")
+}
+
+static FLIGHTS_RESPONSE_SCHEMA: Lazy<jsonschema::Validator> =
+    Lazy::new(|| response_validator(include_str!("schemas/flights-response.json")));
+static HOTELS_RESPONSE_SCHEMA: Lazy<jsonschema::Validator> =
+    Lazy::new(|| response_validator(include_str!("schemas/hotels-response.json")));
+
 #[derive(Clone)]
 pub struct TravelAgentServer {
-    flights_client: Arc<GoogleFlightsClient>,
-    hotels_client: Arc<GoogleHotelsClient>,
+    flights_client: Arc<dyn FlightProvider>,
+    hotels_client: Arc<dyn HotelProvider>,
+    recent_flights: Arc<RecentSearches>,
+    strict_schema: bool,
     tool_router: ToolRouter<Self>,
 }
 
 impl TravelAgentServer {
     pub fn new(
-        flights_client: Arc<GoogleFlightsClient>,
-        hotels_client: Arc<GoogleHotelsClient>,
+        flights_client: Arc<dyn FlightProvider>,
+        hotels_client: Arc<dyn HotelProvider>,
     ) -> Self {
         Self {
             flights_client,
             hotels_client,
+            recent_flights: Arc::default(),
+            strict_schema: false,
             tool_router: Self::tool_router(),
         }
     }
+
+    /// Validate every `search_flights`/`search_hotels` response against the
+    /// committed schema before sending it, failing the call with an internal
+    /// error on a violation. Off by default; tests turn it on to catch drift.
+    pub fn strict_schema(mut self, enabled: bool) -> Self {
+        self.strict_schema = enabled;
+        self
+    }
+
+    /// Applies [`Self::strict_schema`] to the result of the `tool` call.
+    /// Tool errors and the other tools pass through untouched.
+    fn check_response_schema(
+        &self,
+        tool: &str,
+        result: Result<rmcp::model::CallToolResult, rmcp::ErrorData>,
+    ) -> Result<rmcp::model::CallToolResult, rmcp::ErrorData> {
+        let validator = match tool {
+            "search_flights" => &*FLIGHTS_RESPONSE_SCHEMA,
+            "search_hotels" => &*HOTELS_RESPONSE_SCHEMA,
+            _ => return result,
+        };
+        let Ok(response) = &result else {
+            return result;
+        };
+        if !self.strict_schema || response.is_error == Some(true) {
+            return result;
+        }
+        for content in &response.content {
+            let Some(text) = content.as_text() else {
+                continue;
+            };
+            let violations: Vec<String> = match serde_json::from_str(&text.text) {
+                Ok(instance) => validator
+                    .iter_errors(&instance)
+                    .map(|e| format!("{}: {e}", e.instance_path))
+                    .collect(),
+                Err(e) => vec![format!("not JSON: {e}")],
+            };
+            if !violations.is_empty() {
+                tracing::error!("{tool} response violates its schema: {violations:?}");
+                return Err(rmcp::ErrorData::internal_error(
+                    format!("{tool} response violates its schema"),
+                    Some(serde_json::json!({ "violations": violations })),
+                ));
+            }
+        }
+        result
+    }
 }
 
 #[tool_router]
 impl TravelAgentServer {
     #[tool(
         name = "search_flights",
-        description = "Search for flights using Google Flights. Parameters: from (IATA), to (IATA), date (YYYY-MM-DD), return_date (YYYY-MM-DD, optional), seat (Economy/PremiumEconomy/Business/First), adults (1+), children_ages (1-17), trip_type (round-trip/one-way), max_stops."
+        description = "Search for flights using Google Flights. Parameters: from (IATA), to (IATA), date (YYYY-MM-DD), return_date (YYYY-MM-DD, optional), seat (Economy/PremiumEconomy/Business/First), adults (1+), children_ages (1-17), infants_on_lap, infants_in_seat (under 2; infants in seat pay for their own seat), trip_type (round-trip/one-way), legs (multi-city: every {from, to, date} in order, at least two; replaces from/to/date), max_stops, min_layover_minutes (drop tighter connections), max_total_minutes (drop longer itineraries), sort (price/duration/stops/best_value), value_weights (price, duration, stop_penalty for best_value), prefer_direct (nonstops first, connections kept), summary_only (return lowest/median price, fastest duration, nonstop count and airlines instead of every itinerary), group_by_stops (results bucketed by number of stops with a count per bucket)."
+    )]
+    async fn search_flights(
+        &self,
+        params: Parameters<FlightsInput>,
+        ct: CancellationToken,
+    ) -> Result<String, String> {
+        let response = search_one_flight(
+            Arc::clone(&self.flights_client),
+            Arc::clone(&self.recent_flights),
+            params.0,
+            ct,
+        )
+        .await?;
+        serde_json::to_string(&response).map_err(|e| e.to_string())
+    }
+
+    #[tool(
+        name = "search_cheapest_weekend",
+        description = "Find the cheapest Friday-to-Sunday round trip in a month. Parameters: from (IATA), to (IATA), month (YYYY-MM), seat, adults (1+), children_ages, max_stops. Searches at most 5 weekends."
     )]
-    async fn search_flights(&self, params: Parameters<FlightsInput>) -> Result<String, String> {
+    async fn search_cheapest_weekend(
+        &self,
+        params: Parameters<WeekendInput>,
+    ) -> Result<String, String> {
         let input = params.0;
-        let mut passengers = vec![(delulu_travel_agent::Passenger::Adult, input.adults)];
-        if !input.children_ages.is_empty() {
-            passengers.push((
-                delulu_travel_agent::Passenger::Child,
-                input.children_ages.len() as u32,
-            ));
-        }
-        let params = FlightSearchParams {
-            from_airport: input.from,
-            to_airport: input.to,
-            depart_date: input.date,
+        let (year, month) = parse_month(&input.month)?;
+        let base = flight_search_params(FlightsInput {
+            from: input.from,
+            to: input.to,
+            date: format!("{year:04}-{month:02}-01"),
+            return_date: None,
+            seat: input.seat,
+            adults: input.adults,
+            children_ages: input.children_ages,
+            trip_type: Trip::RoundTrip,
+            max_stops: input.max_stops,
+            ..Default::default()
+        });
+
+        let cheapest_weekend = cheapest_weekend(self.flights_client.as_ref(), &base, year, month)
+            .await
+            .map_err(|e| format!("Weekend search failed: {e}"))?;
+
+        serde_json::to_string(&McpCheapestWeekendResponse { cheapest_weekend })
+            .map_err(|e| e.to_string())
+    }
+
+    #[tool(
+        name = "compare_cabins",
+        description = "Lowest fare of one route in each cabin class (economy, premium_economy, business, first), one search per cabin. Parameters: from (IATA), to (IATA), date (YYYY-MM-DD), return_date (optional), adults (1+), children_ages, trip_type, max_stops. Returns lowest_prices keyed by cabin, plus each cabin's search URL or error."
+    )]
+    async fn compare_cabins(
+        &self,
+        params: Parameters<CompareCabinsInput>,
+    ) -> Result<String, String> {
+        let input = params.0;
+        let base = flight_search_params(FlightsInput {
+            from: input.from,
+            to: input.to,
+            date: input.date,
             return_date: input.return_date,
-            cabin_class: input.seat,
-            passengers,
+            adults: input.adults,
+            children_ages: input.children_ages,
             trip_type: input.trip_type,
             max_stops: input.max_stops,
-            preferred_airlines: None,
-        };
+            ..Default::default()
+        });
 
-        let result = self
-            .flights_client
-            .search_flights(&params)
+        let compare_cabins = compare_cabins(self.flights_client.as_ref(), &base)
             .await
-            .map_err(|e| format!("Flight search failed: {e}"))?;
+            .map_err(|e| format!("Cabin comparison failed: {e}"))?;
 
-        serde_json::to_string(&result.to_mcp_api_response(Vec::new())).map_err(|e| e.to_string())
+        serde_json::to_string(&McpCompareCabinsResponse { compare_cabins })
+            .map_err(|e| e.to_string())
     }
 
     #[tool(
-        name = "search_hotels",
-        description = "Search for hotels using Google Hotels. Parameters: location (city/area/POI), checkin_date (YYYY-MM-DD), checkout_date (YYYY-MM-DD), adults (1+), children_ages, min_guest_rating (3.5+/4+/4.5+), stars (hotel rating 2-5), amenities (indoor_pool/outdoor_pool/pool/spa/kid_friendly/air_conditioned/ev_charger), min_price, max_price."
+        name = "search_flights_batch",
+        description = "Run up to 5 flight searches concurrently. Parameters: searches (array of search_flights inputs). Returns one entry per search with its input index and either a result or an error."
     )]
-    async fn search_hotels(&self, params: Parameters<HotelsInput>) -> Result<String, String> {
-        let input = params.0;
+    async fn search_flights_batch(
+        &self,
+        params: Parameters<FlightsBatchInput>,
+        ct: CancellationToken,
+    ) -> Result<String, String> {
+        let client = Arc::clone(&self.flights_client);
+        let recent = Arc::clone(&self.recent_flights);
+        let results = run_batch(params.0.searches, move |input| {
+            search_one_flight(Arc::clone(&client), Arc::clone(&recent), input, ct.clone())
+        })
+        .await?;
 
-        let (valid_amenities, invalid_amenities): (Vec<_>, Vec<_>) = input
-            .amenities
-            .iter()
-            .partition(|a| Amenity::from_str_name(a).is_some());
-
-        let mut warnings: Vec<String> = Vec::new();
-        if !invalid_amenities.is_empty() {
-            let valid_list = [
-                "indoor_pool",
-                "outdoor_pool",
-                "pool",
-                "spa",
-                "kid_friendly",
-                "air_conditioned",
-                "ev_charger",
-            ]
-            .join(", ");
-            warnings.push(format!(
-                "Unknown amenity(s): {}. Valid amenities: {}.",
-                invalid_amenities
-                    .iter()
-                    .map(|s| s.as_str())
-                    .collect::<Vec<_>>()
-                    .join(", "),
-                valid_list
-            ));
-        }
+        serde_json::to_string(&McpFlightsBatchResponse {
+            search_flights_batch: McpBatchResults {
+                total: results.len(),
+                results,
+            },
+        })
+        .map_err(|e| e.to_string())
+    }
 
-        let amenities: Vec<Amenity> = valid_amenities
-            .iter()
-            .filter_map(|a| Amenity::from_str_name(a))
-            .collect();
-        let params = HotelSearchParams {
-            version: 1,
-            adults: input.adults,
-            children_ages: input.children_ages,
-            loc_q_search: input.location,
-            loc_ts_name: String::new(),
-            loc_ts_id: String::new(),
-            loc_ts_coords: String::new(),
-            checkin_date: input.checkin_date,
-            checkout_date: input.checkout_date,
-            nights: 0,
-            used_guests_dropdown: 0,
-            currency: "USD".to_string(),
-            sort_order: None,
-            min_guest_rating: input.min_guest_rating,
-            hotel_stars: input.stars,
-            amenities,
-            min_price: input.min_price,
-            max_price: input.max_price,
+    #[tool(
+        name = "compare_flights",
+        description = "Compare two flight searches side by side, e.g. two dates or two routes. Parameters: a, b (search_flights inputs). Returns both results, which side is cheaper (a/b/tie) and the difference between their cheapest fares (price_delta)."
+    )]
+    async fn compare_flights(
+        &self,
+        params: Parameters<CompareFlightsInput>,
+        ct: CancellationToken,
+    ) -> Result<String, String> {
+        let CompareFlightsInput { a, b } = params.0;
+        // Both searches go through the client's shared QueryQueue.
+        let (a, b) = tokio::join!(
+            search_one_flight(
+                Arc::clone(&self.flights_client),
+                Arc::clone(&self.recent_flights),
+                a,
+                ct.clone(),
+            ),
+            search_one_flight(
+                Arc::clone(&self.flights_client),
+                Arc::clone(&self.recent_flights),
+                b,
+                ct,
+            ),
+        );
+        let a = a.map_err(|e| format!("Search a: {e}"))?;
+        let b = b.map_err(|e| format!("Search b: {e}"))?;
+
+        serde_json::to_string(&McpCompareFlightsResponse {
+            compare_flights: McpFlightComparison::new(a, b),
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    #[tool(
+        name = "get_flight_itinerary_detail",
+        description = "Full details of one itinerary from a search_flights result: segments (airline, operating airline, airports, times, duration), layovers, stops and price. Parameters: id (the itinerary id from search_flights), search (the search_flights input that returned it). Reuses the recent search when possible, otherwise searches again. Baggage allowances and fare rules are not available."
+    )]
+    async fn get_flight_itinerary_detail(
+        &self,
+        params: Parameters<ItineraryDetailInput>,
+        ct: CancellationToken,
+    ) -> Result<String, String> {
+        let ItineraryDetailInput { id, search } = params.0;
+        let params = flight_search_params(search);
+        let search_url = params.get_search_url();
+        let result = match self.recent_flights.get(&search_url) {
+            Some(result) => result,
+            None => {
+                let result = self
+                    .flights_client
+                    .search_cancellable(&params, ct)
+                    .await
+                    .map_err(|e| format!("Flight search failed: {e}"))?;
+                self.recent_flights.insert(search_url, &result);
+                result
+            }
         };
+        let itinerary = result
+            .itineraries
+            .into_iter()
+            .find(|it| it.id == id)
+            .ok_or_else(|| {
+                format!("No itinerary '{id}' in this search; it may no longer be offered")
+            })?;
+
+        serde_json::to_string(&McpItineraryDetailResponse {
+            get_flight_itinerary_detail: itinerary,
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    #[tool(
+        name = "diagnose_last_search",
+        description = "Explain why the most recent flight search returned no or suspicious results: whether Google served a consent or CAPTCHA page, the response size, how many elements each parser selector matched, the search URL to check manually, and suggested next steps. No parameters."
+    )]
+    async fn diagnose_last_search(&self) -> Result<String, String> {
+        let diagnose_last_search = self
+            .flights_client
+            .diagnose_last_search()
+            .ok_or("No flight search response to diagnose yet")?;
+        serde_json::to_string(&McpDiagnosisResponse {
+            diagnose_last_search,
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    #[tool(
+        name = "list_options",
+        description = "List the accepted values of the enumerated parameters: seats, trip_types, flight_sorts (search_flights sort), amenities, hotel_sorts and stars. Each value comes with its accepted aliases and a human-readable label. No parameters."
+    )]
+    async fn list_options(&self) -> Result<String, String> {
+        serde_json::to_string(&McpListOptionsResponse {
+            list_options: McpOptions::new(),
+        })
+        .map_err(|e| e.to_string())
+    }
 
-        let result = self
+    #[tool(
+        name = "search_hotels",
+        description = "Search for hotels using Google Hotels. Parameters: location (city/area/POI), checkin_date (YYYY-MM-DD), checkout_date (YYYY-MM-DD), adults (1+), children_ages, min_guest_rating (3.5+/4+/4.5+), stars (hotel rating 2-5), amenities (indoor_pool/outdoor_pool/pool/spa/kid_friendly/air_conditioned/ev_charger), min_price, max_price, relax_on_empty (retry once without the most restrictive filter if nothing matches), preferences (free text like \"pet friendly with a pool\", mapped onto filters; unmatched phrases come back as warnings), within_km (drop hotels farther from the center of the searched area)."
+    )]
+    async fn search_hotels(&self, params: Parameters<HotelsInput>) -> Result<String, String> {
+        let input = params.0;
+        let relax_on_empty = input.relax_on_empty;
+        let within_km = input.within_km;
+        let (mut params, preferences, mut warnings) = hotel_search_params(input);
+
+        let mut result = self
             .hotels_client
-            .search_hotels(&params)
+            .search(&params)
             .await
             .map_err(|e| format!("Hotel search failed: {e}"))?;
+        // A single relaxation pass keeps this to at most two requests.
+        if relax_on_empty
+            && result.hotels.is_empty()
+            && let Some(dropped) = params.relax_most_restrictive_filter()
+        {
+            result = self
+                .hotels_client
+                .search(&params)
+                .await
+                .map_err(|e| format!("Hotel search failed: {e}"))?;
+            warnings.push(format!(
+                "No hotels matched every filter; searched again without the {dropped} filter"
+            ));
+        }
+        result.require_chips(&preferences.chips);
+        if let Some(km) = within_km {
+            result.within_km(km);
+        }
 
         let search_url = params.get_search_url();
         serde_json::to_string(&result.to_mcp_api_response(
@@ -244,6 +1216,36 @@ impl TravelAgentServer {
         ))
         .map_err(|e| e.to_string())
     }
+
+    #[tool(
+        name = "validate_flight_search",
+        description = "Check a search_flights input without searching: airports, dates, passenger counts and multi-city legs. Takes the same parameters as search_flights. Returns valid, and the errors a search would fail with."
+    )]
+    async fn validate_flight_search(
+        &self,
+        params: Parameters<FlightsInput>,
+    ) -> Result<String, String> {
+        let checked = validated_flight_search_params(params.0).map(|_| ());
+        serde_json::to_string(&McpValidateFlightSearchResponse {
+            validate_flight_search: McpValidation::new(checked, Vec::new()),
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    #[tool(
+        name = "validate_hotel_search",
+        description = "Check a search_hotels input without searching: dates, stay length, guest counts, prices and stars. Takes the same parameters as search_hotels. Returns valid, the errors a search would fail with, and warnings for ignored amenities or preferences."
+    )]
+    async fn validate_hotel_search(
+        &self,
+        params: Parameters<HotelsInput>,
+    ) -> Result<String, String> {
+        let (params, _, warnings) = hotel_search_params(params.0);
+        serde_json::to_string(&McpValidateHotelSearchResponse {
+            validate_hotel_search: McpValidation::new(params.validate(), warnings),
+        })
+        .map_err(|e| e.to_string())
+    }
 }
 
 impl ServerHandler for TravelAgentServer {
@@ -272,11 +1274,30 @@ impl ServerHandler for TravelAgentServer {
     {
         let router = self.tool_router.clone();
         let self_clone = self.clone();
-        Box::pin(async move {
-            let context =
-                rmcp::handler::server::tool::ToolCallContext::new(&self_clone, request, context);
-            router.call(context).await
-        })
+        let tool = request.name.clone();
+        // Every log line emitted while handling this call, including the ones from
+        // the flights/hotels clients, carries the JSON-RPC request id so concurrent
+        // calls can be told apart.
+        let span = tracing::info_span!(
+            "tool_call",
+            request_id = %context.id,
+            tool = %request.name
+        );
+        Box::pin(
+            async move {
+                tracing::info!("Tool call started");
+                let start = std::time::Instant::now();
+                let context = rmcp::handler::server::tool::ToolCallContext::new(
+                    &self_clone,
+                    request,
+                    context,
+                );
+                let result = router.call(context).await;
+                tracing::info!("Tool call finished in {:?}", start.elapsed());
+                self.check_response_schema(&tool, result)
+            }
+            .instrument(span),
+        )
     }
 
     fn get_info(&self) -> rmcp::model::ServerInfo {
@@ -292,6 +1313,22 @@ impl ServerHandler for TravelAgentServer {
     }
 }
 
+type HttpService =
+    StreamableHttpService<TravelAgentServer, PersistentSessionManager<TravelAgentServer>>;
+
+/// Stateful streamable HTTP service whose sessions are recorded in `store`.
+fn http_service(server: TravelAgentServer, store: Arc<dyn SessionStore>) -> HttpService {
+    let session_manager = Arc::new(PersistentSessionManager::new(store, {
+        let server = server.clone();
+        move || server.clone()
+    }));
+    let config = StreamableHttpServerConfig {
+        stateful_mode: true,
+        ..Default::default()
+    };
+    StreamableHttpService::new(move || Ok(server.clone()), session_manager, config)
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Error> {
     tracing_subscriber::registry()
@@ -307,29 +1344,43 @@ async fn main() -> Result<(), Error> {
     let args = Args::parse();
     tracing::debug!("Parsed args: {:?}", args);
 
+    if let Command::ExportSchemas { dir } = &args.command {
+        for path in export_schemas(dir)? {
+            println!("Wrote {}", path.display());
+        }
+        return Ok(());
+    }
+    let shared_queue = match args.command.queue_args() {
+        Some(queue) => queue.query_queue()?,
+        None => None,
+    };
+
     tracing::debug!("Creating flights client...");
-    let flights_client = Arc::new(
-        GoogleFlightsClient::new(
-            "en".into(),
-            "USD".into(),
-            5, // timeout_secs
-            2, // queries_per_second
-        )
-        .context("Failed to create flights client")?,
-    );
+    let mut flights_client = GoogleFlightsClient::new(
+        "en".into(),
+        "USD".into(),
+        5, // timeout_secs
+        2, // queries_per_second
+    )
+    .context("Failed to create flights client")?;
     tracing::debug!("Creating hotels client...");
-    let hotels_client = Arc::new(
-        GoogleHotelsClient::new(
-            5, // timeout_secs
-            2, // queries_per_second
-        )
-        .context("Failed to create hotels client")?,
-    );
+    let mut hotels_client = GoogleHotelsClient::new(
+        5, // timeout_secs
+        2, // queries_per_second
+    )
+    .context("Failed to create hotels client")?;
+    if let Some(queue) = shared_queue {
+        flights_client = flights_client.query_queue(queue.clone());
+        hotels_client = hotels_client.query_queue(queue);
+    }
+    let flights_client = Arc::new(flights_client);
+    let hotels_client = Arc::new(hotels_client);
     tracing::debug!("Clients created");
 
     match args.command {
-        Command::Stdio => {
-            let server = TravelAgentServer::new(flights_client, hotels_client);
+        Command::Stdio { .. } => {
+            let server = TravelAgentServer::new(flights_client, hotels_client)
+                .strict_schema(args.strict_schema);
             let (stdin, stdout) = rmcp::transport::io::stdio();
             tracing::info!("Starting MCP server over stdio...");
             let _running = serve_server(Arc::new(server), (stdin, stdout))
@@ -339,24 +1390,45 @@ async fn main() -> Result<(), Error> {
             tokio::signal::ctrl_c().await.ok();
             tracing::info!("Shutting down...");
         }
-        Command::Http { host, port } => {
+        Command::ExportSchemas { .. } => unreachable!("handled before creating the clients"),
+        Command::Http {
+            host,
+            port,
+            session_store,
+            session_dir,
+            ..
+        } => {
             let addr: SocketAddr = format!("{}:{}", host, port)
                 .parse()
                 .context("Invalid host:port")?;
             tracing::info!("Starting MCP server over HTTP on {}", addr);
-            let server = TravelAgentServer::new(flights_client, hotels_client);
-            let session_manager = Arc::new(LocalSessionManager::default());
-            let config = StreamableHttpServerConfig {
-                stateful_mode: true,
-                ..Default::default()
+            let store: Arc<dyn SessionStore> = match session_store {
+                SessionStoreKind::Memory => Arc::new(MemorySessionStore::default()),
+                SessionStoreKind::File => Arc::new(FileSessionStore::new(
+                    session_dir.context("--session-dir is required for the file session store")?,
+                )?),
             };
-            let service =
-                StreamableHttpService::new(move || Ok(server.clone()), session_manager, config);
-            let app = axum::Router::new().nest_service("/mcp", service);
             let listener = tokio::net::TcpListener::bind(addr)
                 .await
                 .context("Failed to bind to address")?;
             tracing::debug!("Listening on {}", addr);
+            // Pay the cookie generation and first connection up front, so the
+            // first user request is fast and problems show up in the startup
+            // logs. In the background, so an unreachable Google does not hold
+            // up accepting connections.
+            let (flights, hotels) = (flights_client.clone(), hotels_client.clone());
+            tokio::spawn(async move {
+                if let Err(e) = flights.warm_up(true).await {
+                    tracing::warn!("Flights warm-up failed: {:#}", e);
+                }
+                if let Err(e) = hotels.warm_up(true).await {
+                    tracing::warn!("Hotels warm-up failed: {:#}", e);
+                }
+            });
+            let server = TravelAgentServer::new(flights_client, hotels_client)
+                .strict_schema(args.strict_schema);
+            let service = http_service(server, store);
+            let app = axum::Router::new().nest_service("/mcp", service);
             axum::serve(listener, app)
                 .with_graceful_shutdown(async move {
                     tokio::signal::ctrl_c().await.ok();
@@ -369,3 +1441,1029 @@ async fn main() -> Result<(), Error> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "replay")]
+    use delulu_travel_agent::ReplayFlightsClient;
+    use delulu_travel_agent::{
+        Availability, FlightSearchResult, FlightsError, Hotel, HotelSearchResult, HotelsError,
+        Itinerary, SearchFuture,
+    };
+
+    /// In-memory provider returning one canned itinerary per route.
+    struct FakeProvider;
+
+    impl FlightProvider for FakeProvider {
+        fn search<'a>(
+            &'a self,
+            params: &'a FlightSearchParams,
+        ) -> SearchFuture<'a, FlightSearchResult, FlightsError> {
+            Box::pin(async move {
+                if params.to_airport == "XXX" {
+                    return Err(FlightsError::Search(anyhow::anyhow!("unknown airport")));
+                }
+                if params.max_stops == Some(0) && params.to_airport == "NRT" {
+                    return Ok(FlightSearchResult {
+                        search_params: params.clone(),
+                        itineraries: Vec::new(),
+                        raw_response: String::new(),
+                        warnings: Vec::new(),
+                        price_trend: None,
+                        currency: "USD".to_string(),
+                        retry_count: None,
+                    });
+                }
+                Ok(FlightSearchResult {
+                    search_params: params.clone(),
+                    itineraries: vec![Itinerary {
+                        id: "fake".to_string(),
+                        flights: Vec::new(),
+                        // Thursday 2030-01-17 is pricier than other days
+                        price: Some(if params.depart_date == "2030-01-17" {
+                            249
+                        } else {
+                            199
+                        }),
+                        currency: Some("USD".to_string()),
+                        original_price_usd: None,
+                        duration_minutes: Some(330),
+                        class: None,
+                        stops: Some(0),
+                        layovers: Vec::new(),
+                    }],
+                    raw_response: String::new(),
+                    warnings: Vec::new(),
+                    price_trend: None,
+                    currency: "USD".to_string(),
+                    retry_count: None,
+                })
+            })
+        }
+    }
+
+    impl HotelProvider for FakeProvider {
+        fn search<'a>(
+            &'a self,
+            params: &'a HotelSearchParams,
+        ) -> SearchFuture<'a, HotelSearchResult, HotelsError> {
+            Box::pin(async move {
+                // Nothing is ever cheap enough for a price filter
+                let hotels = if params.min_price.is_some() || params.max_price.is_some() {
+                    Vec::new()
+                } else {
+                    vec![Hotel {
+                        name: "Hotel Fake".to_string(),
+                        price: "$250".to_string(),
+                        rating: None,
+                        reviews: None,
+                        amenities: Vec::new(),
+                        location_rating: None,
+                        distance_km: Some(1.2),
+                        star_class: Some("5".to_string()),
+                        url: None,
+                        address: None,
+                        taxes_included: None,
+                        price_per_person: None,
+                        availability: Availability::Available,
+                    }]
+                };
+                Ok(HotelSearchResult {
+                    hotels,
+                    lowest_price: None,
+                    current_price: None,
+                    retry_count: None,
+                })
+            })
+        }
+    }
+
+    fn fake_server() -> TravelAgentServer {
+        TravelAgentServer::new(Arc::new(FakeProvider), Arc::new(FakeProvider)).strict_schema(true)
+    }
+
+    fn text_result(text: String) -> Result<rmcp::model::CallToolResult, rmcp::ErrorData> {
+        Ok(rmcp::model::CallToolResult::success(vec![
+            rmcp::model::Content::text(text),
+        ]))
+    }
+
+    #[tokio::test]
+    async fn test_compare_cabins_tool() {
+        let server = fake_server();
+        let input = CompareCabinsInput {
+            from: "SFO".to_string(),
+            to: "JFK".to_string(),
+            date: "2030-01-15".to_string(),
+            return_date: None,
+            adults: 1,
+            children_ages: Vec::new(),
+            trip_type: Trip::OneWay,
+            max_stops: None,
+        };
+
+        let json = server.compare_cabins(Parameters(input)).await.unwrap();
+        let response: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let prices = &response["compare_cabins"]["lowest_prices"];
+        for cabin in ["economy", "premium_economy", "business", "first"] {
+            assert_eq!(prices[cabin], 199, "{cabin}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_flights_multi_city_legs() {
+        let server = fake_server();
+        let leg = |from: &str, to: &str, date: &str| FlightLegInput {
+            from: from.to_string(),
+            to: to.to_string(),
+            date: date.to_string(),
+        };
+        let input = FlightsInput {
+            legs: Some(vec![
+                leg("SFO", "JFK", "2030-01-15"),
+                leg("JFK", "LHR", "2030-01-18"),
+                leg("LHR", "SFO", "2030-01-25"),
+            ]),
+            ..sfo_to("ORD")
+        };
+
+        let json = server
+            .search_flights(Parameters(input), CancellationToken::new())
+            .await
+            .unwrap();
+        let response: McpFlightResponse = serde_json::from_str(&json).unwrap();
+        let decoded = FlightSearchParams::from_url(&response.search_flights.query.search_url)
+            .unwrap()
+            .params;
+        assert_eq!(decoded.trip_type, Trip::MultiCity);
+        assert_eq!(
+            (decoded.from_airport.as_str(), decoded.to_airport.as_str()),
+            ("SFO", "JFK")
+        );
+        let legs: Vec<_> = decoded
+            .extra_legs
+            .iter()
+            .map(|l| {
+                (
+                    l.from_airport.as_str(),
+                    l.to_airport.as_str(),
+                    l.date.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            legs,
+            [("JFK", "LHR", "2030-01-18"), ("LHR", "SFO", "2030-01-25")]
+        );
+
+        let one_leg = FlightsInput {
+            legs: Some(vec![leg("SFO", "JFK", "2030-01-15")]),
+            ..sfo_to("ORD")
+        };
+        let err = server
+            .search_flights(Parameters(one_leg), CancellationToken::new())
+            .await
+            .unwrap_err();
+        assert!(err.contains("at least two legs"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_strict_schema_rejects_malformed_response() {
+        let server = fake_server();
+
+        let flights = server
+            .search_flights(Parameters(sfo_to("JFK")), CancellationToken::new())
+            .await
+            .unwrap();
+        assert!(
+            server
+                .check_response_schema("search_flights", text_result(flights.clone()))
+                .is_ok()
+        );
+        let hotels = server
+            .search_hotels(Parameters(HotelsInput {
+                location: "Paris".to_string(),
+                checkin_date: "2030-01-15".to_string(),
+                checkout_date: "2030-01-17".to_string(),
+                adults: 2,
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+        assert!(
+            server
+                .check_response_schema("search_hotels", text_result(hotels))
+                .is_ok()
+        );
+
+        // `total` must be a non-negative integer
+        let mut malformed: serde_json::Value = serde_json::from_str(&flights).unwrap();
+        malformed["search_flights"]["total"] = "one".into();
+        let Err(err) =
+            server.check_response_schema("search_flights", text_result(malformed.to_string()))
+        else {
+            panic!("malformed response accepted");
+        };
+        assert_eq!(err.code, rmcp::model::ErrorCode::INTERNAL_ERROR);
+        assert!(err.message.contains("search_flights"));
+        assert!(
+            server
+                .check_response_schema("search_hotels", text_result("{}".to_string()))
+                .is_err()
+        );
+
+        // Off by default, and tool errors are never second-guessed
+        let lenient = TravelAgentServer::new(Arc::new(FakeProvider), Arc::new(FakeProvider));
+        assert!(
+            lenient
+                .check_response_schema("search_flights", text_result(malformed.to_string()))
+                .is_ok()
+        );
+        let tool_error = Ok(rmcp::model::CallToolResult::error(vec![
+            rmcp::model::Content::text("Flight search failed"),
+        ]));
+        assert!(
+            server
+                .check_response_schema("search_flights", tool_error)
+                .is_ok()
+        );
+    }
+
+    fn sfo_to(to: &str) -> FlightsInput {
+        FlightsInput {
+            from: "SFO".to_string(),
+            to: to.to_string(),
+            date: "2030-01-15".to_string(),
+            adults: 1,
+            trip_type: Trip::OneWay,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_flights_through_provider() {
+        let server = fake_server();
+
+        let json = server
+            .search_flights(Parameters(sfo_to("JFK")), CancellationToken::new())
+            .await
+            .unwrap();
+        let response: McpFlightResponse = serde_json::from_str(&json).unwrap();
+        assert_eq!(response.search_flights.total, 1);
+        assert_eq!(response.search_flights.results.unwrap()[0].price, 199);
+        assert_eq!(response.search_flights.query.to, "JFK");
+
+        let err = server
+            .search_flights(Parameters(sfo_to("XXX")), CancellationToken::new())
+            .await
+            .unwrap_err();
+        assert!(err.contains("unknown airport"));
+        assert!(server.diagnose_last_search().await.is_err());
+    }
+
+    #[cfg(feature = "replay")]
+    #[tokio::test]
+    async fn test_search_flights_replays_fixture() {
+        let fixtures = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures-flights-parsing"
+        );
+        let server = TravelAgentServer::new(
+            Arc::new(ReplayFlightsClient::new(fixtures)),
+            Arc::new(FakeProvider),
+        );
+
+        let json = server
+            .search_flights(Parameters(sfo_to("JFK")), CancellationToken::new())
+            .await
+            .unwrap();
+        let response: McpFlightResponse = serde_json::from_str(&json).unwrap();
+        assert!(response.search_flights.total >= 5);
+        assert!(
+            response
+                .search_flights
+                .results
+                .unwrap()
+                .iter()
+                .all(|it| it.price > 0)
+        );
+
+        let err = server
+            .search_flights(Parameters(sfo_to("NRT")), CancellationToken::new())
+            .await
+            .unwrap_err();
+        assert!(err.contains("No replay fixture for SFO→NRT"), "{err}");
+    }
+
+    /// Provider counting the searches it forwards.
+    #[cfg(feature = "replay")]
+    struct CountingProvider {
+        inner: ReplayFlightsClient,
+        searches: std::sync::atomic::AtomicUsize,
+    }
+
+    #[cfg(feature = "replay")]
+    impl FlightProvider for CountingProvider {
+        fn search<'a>(
+            &'a self,
+            params: &'a FlightSearchParams,
+        ) -> SearchFuture<'a, FlightSearchResult, FlightsError> {
+            self.searches
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            self.inner.search(params)
+        }
+    }
+
+    #[cfg(feature = "replay")]
+    #[tokio::test]
+    async fn test_get_flight_itinerary_detail() {
+        let fixtures = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures-flights-parsing"
+        );
+        let provider = Arc::new(CountingProvider {
+            inner: ReplayFlightsClient::new(fixtures),
+            searches: Default::default(),
+        });
+        let server = TravelAgentServer::new(provider.clone(), Arc::new(FakeProvider));
+        let searches = || provider.searches.load(std::sync::atomic::Ordering::Relaxed);
+
+        let json = server
+            .search_flights(Parameters(sfo_to("JFK")), CancellationToken::new())
+            .await
+            .unwrap();
+        let response: McpFlightResponse = serde_json::from_str(&json).unwrap();
+        let summary = &response.search_flights.results.unwrap()[1];
+
+        let detail = |id: &str| ItineraryDetailInput {
+            id: id.to_string(),
+            search: sfo_to("JFK"),
+        };
+        let json = server
+            .get_flight_itinerary_detail(Parameters(detail(&summary.id)), CancellationToken::new())
+            .await
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let itinerary: Itinerary =
+            serde_json::from_value(value["get_flight_itinerary_detail"].clone()).unwrap();
+        assert_eq!(itinerary.id, summary.id);
+        assert_eq!(itinerary.price, Some(summary.price));
+        assert!(!itinerary.flights.is_empty());
+        assert_eq!(searches(), 1, "the detail reuses the search result");
+
+        let err = server
+            .get_flight_itinerary_detail(Parameters(detail("gone")), CancellationToken::new())
+            .await
+            .unwrap_err();
+        assert!(err.contains("No itinerary 'gone'"), "{err}");
+
+        // A server that never ran the search fetches it.
+        let fresh = TravelAgentServer::new(provider.clone(), Arc::new(FakeProvider));
+        fresh
+            .get_flight_itinerary_detail(Parameters(detail(&summary.id)), CancellationToken::new())
+            .await
+            .unwrap();
+        assert_eq!(searches(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_recent_searches_expire() {
+        let params = validated_flight_search_params(sfo_to("JFK")).unwrap();
+        let result = FlightProvider::search(&FakeProvider, &params)
+            .await
+            .unwrap();
+        let recent = RecentSearches::default();
+        recent.insert("sfo-jfk".to_string(), &result);
+        assert!(recent.get("sfo-jfk").is_some());
+        assert!(recent.get("sfo-lax").is_none());
+
+        recent.entries.lock().unwrap()[0].1 =
+            Instant::now().checked_sub(RECENT_SEARCH_TTL).unwrap();
+        assert!(recent.get("sfo-jfk").is_none(), "stale entries are misses");
+    }
+
+    #[tokio::test]
+    async fn test_search_flights_infant_in_seat() {
+        let server = fake_server();
+        let input = FlightsInput {
+            adults: 2,
+            infants_in_seat: 1,
+            ..sfo_to("JFK")
+        };
+
+        let json = server
+            .search_flights(Parameters(input), CancellationToken::new())
+            .await
+            .unwrap();
+        let response: McpFlightResponse = serde_json::from_str(&json).unwrap();
+        let warnings = &response.search_flights.warnings;
+        assert!(
+            warnings
+                .iter()
+                .any(|w| w.starts_with("1 infant(s) in seat")),
+            "{warnings:?}"
+        );
+
+        let url = &response.search_flights.query.search_url;
+        let tfs = url.split("tfs=").nth(1).unwrap().split('&').next().unwrap();
+        let decoded = FlightSearchParams::from_tfs(tfs).unwrap();
+        assert_eq!(
+            decoded.passengers,
+            [
+                (delulu_travel_agent::Passenger::Adult, 2),
+                (delulu_travel_agent::Passenger::InfantInSeat, 1)
+            ]
+        );
+
+        // Seated infants count towards the passenger cap
+        let crowded = FlightsInput {
+            adults: 6,
+            children_ages: vec![5, 8],
+            infants_in_seat: 2,
+            ..sfo_to("JFK")
+        };
+        assert!(flight_search_params(crowded).validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_search_flights_summary_only() {
+        let server = fake_server();
+
+        let json = server
+            .search_flights(
+                Parameters(FlightsInput {
+                    summary_only: true,
+                    ..sfo_to("JFK")
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(value["search_flights"].get("results").is_none());
+        let response: McpFlightResponse = serde_json::from_value(value).unwrap();
+        assert_eq!(response.search_flights.total, 1);
+        let summary = response.search_flights.summary.unwrap();
+        assert_eq!(summary.lowest_price, Some(199));
+        assert_eq!(summary.median_price, Some(199));
+        assert_eq!(summary.fastest_minutes, Some(330));
+        assert_eq!(summary.nonstop_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_search_flights_group_by_stops() {
+        let json = fake_server()
+            .search_flights(
+                Parameters(FlightsInput {
+                    group_by_stops: true,
+                    ..sfo_to("JFK")
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(value["search_flights"].get("results").is_none());
+        let groups = &value["search_flights"]["groups"];
+        assert_eq!(groups[0]["stops"], 0);
+        assert_eq!(groups[0]["count"], 1);
+        assert_eq!(groups[0]["results"][0]["price"], 199);
+    }
+
+    async fn validate_flight(server: &TravelAgentServer, input: FlightsInput) -> McpValidation {
+        let json = server
+            .validate_flight_search(Parameters(input))
+            .await
+            .unwrap();
+        serde_json::from_str::<McpValidateFlightSearchResponse>(&json)
+            .unwrap()
+            .validate_flight_search
+    }
+
+    #[tokio::test]
+    async fn test_validate_flight_search() {
+        let server = fake_server();
+
+        let json = server
+            .validate_flight_search(Parameters(sfo_to("JFK")))
+            .await
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({"validate_flight_search": {"valid": true}})
+        );
+
+        let cases = [
+            (
+                FlightsInput {
+                    date: "2030-02-30".to_string(),
+                    ..sfo_to("JFK")
+                },
+                "Invalid depart date format: input is out of range",
+            ),
+            (
+                FlightsInput {
+                    return_date: Some("2030-01-10".to_string()),
+                    trip_type: Trip::RoundTrip,
+                    ..sfo_to("JFK")
+                },
+                "Return date must be on or after departure date",
+            ),
+            (
+                FlightsInput {
+                    adults: 0,
+                    ..sfo_to("JFK")
+                },
+                "At least one adult is required",
+            ),
+            (
+                sfo_to("New York"),
+                "Destination airport must be a 3-letter IATA code, got 'New York'",
+            ),
+        ];
+        for (input, expected) in cases {
+            let validation = validate_flight(&server, input).await;
+            assert!(!validation.valid);
+            assert_eq!(validation.errors, [expected]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validate_hotel_search() {
+        let server = fake_server();
+        let paris = || HotelsInput {
+            location: "Paris".to_string(),
+            checkin_date: "2030-03-01".to_string(),
+            checkout_date: "2030-03-05".to_string(),
+            adults: 2,
+            ..Default::default()
+        };
+        let validate = |input| async {
+            let json = server
+                .validate_hotel_search(Parameters(input))
+                .await
+                .unwrap();
+            serde_json::from_str::<McpValidateHotelSearchResponse>(&json)
+                .unwrap()
+                .validate_hotel_search
+        };
+
+        let valid = validate(HotelsInput {
+            amenities: vec!["sauna".to_string()],
+            ..paris()
+        })
+        .await;
+        assert!(valid.valid);
+        assert!(valid.errors.is_empty());
+        assert_eq!(valid.warnings.len(), 1);
+
+        let reversed = validate(HotelsInput {
+            checkout_date: "2030-02-27".to_string(),
+            ..paris()
+        })
+        .await;
+        assert!(!reversed.valid);
+        assert_eq!(reversed.errors, ["Checkout must be after check-in"]);
+
+        let nobody = validate(HotelsInput {
+            adults: 0,
+            ..paris()
+        })
+        .await;
+        assert_eq!(nobody.errors, ["At least one adult is required"]);
+    }
+
+    #[tokio::test]
+    async fn test_list_options_covers_enums() {
+        let json = fake_server().list_options().await.unwrap();
+        let options: McpListOptionsResponse = serde_json::from_str(&json).unwrap();
+        let options = options.list_options;
+
+        let amenities: Vec<&str> = options.amenities.iter().map(|o| o.value.as_str()).collect();
+        let expected: Vec<&str> = Amenity::all().iter().map(Amenity::as_str_name).collect();
+        assert_eq!(amenities, expected);
+        assert_eq!(options.amenities[0].aliases, ["indoorpool", "indoor"]);
+
+        let seats: Vec<&str> = options.seats.iter().map(|o| o.value.as_str()).collect();
+        assert_eq!(seats, ["economy", "premium_economy", "business", "first"]);
+        assert_eq!(options.seats[1].label, "Premium economy");
+        for seat in &options.seats {
+            let parsed: Seat = serde_json::from_value(seat.value.clone().into()).unwrap();
+            for alias in &seat.aliases {
+                let aliased: Seat = serde_json::from_value(alias.clone().into()).unwrap();
+                assert_eq!(aliased, parsed);
+            }
+        }
+        assert_eq!(options.hotel_sorts.len(), 3);
+        assert_eq!(options.stars, [2, 3, 4, 5]);
+    }
+
+    #[tokio::test]
+    async fn test_compare_flights_picks_cheaper_date() {
+        let server = fake_server();
+        let on = |date: &str| FlightsInput {
+            date: date.to_string(),
+            ..sfo_to("JFK")
+        };
+        let compare = |a, b| {
+            server.compare_flights(
+                Parameters(CompareFlightsInput { a, b }),
+                CancellationToken::new(),
+            )
+        };
+
+        let json = compare(on("2030-01-15"), on("2030-01-17")).await.unwrap();
+        let response: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let comparison = &response["compare_flights"];
+        assert_eq!(comparison["cheaper"], "a");
+        assert_eq!(comparison["price_delta"], 50);
+        assert_eq!(
+            comparison["a"]["search_flights"]["query"]["date"],
+            "2030-01-15"
+        );
+        assert_eq!(
+            comparison["b"]["search_flights"]["results"][0]["price"],
+            249
+        );
+
+        let json = compare(on("2030-01-17"), on("2030-01-15")).await.unwrap();
+        let response: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(response["compare_flights"]["cheaper"], "b");
+        assert_eq!(response["compare_flights"]["price_delta"], 50);
+
+        let json = compare(on("2030-01-15"), sfo_to("LAX")).await.unwrap();
+        let response: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(response["compare_flights"]["cheaper"], "tie");
+        assert_eq!(response["compare_flights"]["price_delta"], 0);
+
+        let err = compare(on("2030-01-15"), sfo_to("XXX")).await.unwrap_err();
+        assert!(err.starts_with("Search b:"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_compare_flights_grouped_by_stops() {
+        let grouped = |date: &str| FlightsInput {
+            date: date.to_string(),
+            group_by_stops: true,
+            ..sfo_to("JFK")
+        };
+        let json = fake_server()
+            .compare_flights(
+                Parameters(CompareFlightsInput {
+                    a: grouped("2030-01-17"),
+                    b: grouped("2030-01-15"),
+                }),
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        let response: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let comparison = &response["compare_flights"];
+        assert!(comparison["a"]["search_flights"].get("results").is_none());
+        assert_eq!(comparison["cheaper"], "b");
+        assert_eq!(comparison["price_delta"], 50);
+    }
+
+    #[tokio::test]
+    async fn test_relax_on_empty_drops_price_filter() {
+        let server = fake_server();
+        let input = |relax_on_empty| HotelsInput {
+            location: "Paris".to_string(),
+            checkin_date: "2030-03-01".to_string(),
+            checkout_date: "2030-03-05".to_string(),
+            adults: 2,
+            stars: vec![5],
+            amenities: vec!["pool".to_string(), "spa".to_string()],
+            max_price: Some(200),
+            relax_on_empty,
+            ..Default::default()
+        };
+
+        let json = server
+            .search_hotels(Parameters(input(false)))
+            .await
+            .unwrap();
+        let response: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(response["search_hotels"]["total"], 0);
+
+        let json = server.search_hotels(Parameters(input(true))).await.unwrap();
+        let response: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let search = &response["search_hotels"];
+        assert_eq!(search["total"], 1);
+        assert_eq!(
+            search["warnings"],
+            serde_json::json!([
+                "No hotels matched every filter; searched again without the price (max 200) filter"
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_within_km_filters_hotels() {
+        let server = fake_server();
+        let input = |within_km| HotelsInput {
+            location: "Paris".to_string(),
+            checkin_date: "2030-03-01".to_string(),
+            checkout_date: "2030-03-05".to_string(),
+            adults: 2,
+            within_km,
+            ..Default::default()
+        };
+
+        let json = server
+            .search_hotels(Parameters(input(Some(2.0))))
+            .await
+            .unwrap();
+        let response: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(response["search_hotels"]["results"][0]["distance_km"], 1.2);
+
+        let json = server
+            .search_hotels(Parameters(input(Some(1.0))))
+            .await
+            .unwrap();
+        let response: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(response["search_hotels"]["total"], 0);
+    }
+
+    #[tokio::test]
+    async fn test_preferences_map_to_filters() {
+        let server = fake_server();
+        let input = |preferences: &str| HotelsInput {
+            location: "Paris".to_string(),
+            checkin_date: "2030-03-01".to_string(),
+            checkout_date: "2030-03-05".to_string(),
+            adults: 2,
+            preferences: Some(preferences.to_string()),
+            ..Default::default()
+        };
+
+        let json = server
+            .search_hotels(Parameters(input("5-star beachfront with a pool")))
+            .await
+            .unwrap();
+        let response: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let search = &response["search_hotels"];
+        assert_eq!(search["total"], 1);
+        assert_eq!(
+            search["warnings"],
+            serde_json::json!([
+                "Preference 'beachfront' does not match any filter and was ignored"
+            ])
+        );
+        let ts = search["query"]["search_url"]
+            .as_str()
+            .unwrap()
+            .split("ts=")
+            .nth(1)
+            .unwrap()
+            .split('&')
+            .next()
+            .unwrap();
+        let params = HotelSearchParams::from_ts(ts).unwrap();
+        assert_eq!(params.amenities, [Amenity::Pool]);
+        assert_eq!(params.hotel_stars, [5]);
+
+        // The fake hotel shows no chips, so a pet-friendly requirement drops it.
+        let json = server
+            .search_hotels(Parameters(input("pet friendly")))
+            .await
+            .unwrap();
+        let response: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(response["search_hotels"]["total"], 0);
+    }
+
+    #[test]
+    fn test_unknown_amenity_warning() {
+        assert_eq!(
+            unknown_amenity_warning("swiming_pool"),
+            "Unknown amenity 'swiming_pool'; did you mean 'pool' or 'indoor_pool'?"
+        );
+        let warning = unknown_amenity_warning("xyzzy");
+        assert!(!warning.contains("did you mean"), "{warning}");
+        assert!(warning.ends_with("ev_charger."), "{warning}");
+    }
+
+    #[tokio::test]
+    async fn test_empty_nonstop_long_haul_warns() {
+        let server = fake_server();
+        let nonstop = |from: &str, to: &str| FlightsInput {
+            from: from.to_string(),
+            max_stops: Some(0),
+            ..sfo_to(to)
+        };
+
+        let json = server
+            .search_flights(Parameters(nonstop("MAD", "NRT")), CancellationToken::new())
+            .await
+            .unwrap();
+        let response: McpFlightResponse = serde_json::from_str(&json).unwrap();
+        assert_eq!(response.search_flights.total, 0);
+        assert_eq!(response.search_flights.warnings.len(), 1);
+        assert!(
+            response.search_flights.warnings[0]
+                .starts_with("No nonstop service is typical on MAD→NRT")
+        );
+
+        // Results came back: no hint even on a long-haul route.
+        let json = server
+            .search_flights(Parameters(nonstop("MAD", "SYD")), CancellationToken::new())
+            .await
+            .unwrap();
+        let response: McpFlightResponse = serde_json::from_str(&json).unwrap();
+        assert!(response.search_flights.warnings.is_empty());
+
+        // Results filtered out locally: Google did have flights, so no hint.
+        let filtered = FlightsInput {
+            max_total_minutes: Some(60),
+            ..nonstop("MAD", "SYD")
+        };
+        let json = server
+            .search_flights(Parameters(filtered), CancellationToken::new())
+            .await
+            .unwrap();
+        let response: McpFlightResponse = serde_json::from_str(&json).unwrap();
+        assert_eq!(response.search_flights.total, 0);
+        assert!(response.search_flights.warnings.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_batch_reports_each_entry() {
+        let inputs = vec!["SFO-JFK", "SFO-XXX", "SFO-IAD"];
+        let results = run_batch(inputs, |route: &str| async move {
+            if route.ends_with("XXX") {
+                Err(format!("Unknown airport in {route}"))
+            } else {
+                Ok(route.to_string())
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].index, 0);
+        assert_eq!(results[0].result.as_deref(), Some("SFO-JFK"));
+        assert_eq!(results[1].index, 1);
+        assert!(results[1].result.is_none());
+        assert!(results[1].error.as_ref().unwrap().contains("SFO-XXX"));
+        assert_eq!(results[2].index, 2);
+        assert_eq!(results[2].result.as_deref(), Some("SFO-IAD"));
+    }
+
+    #[test]
+    fn test_parse_month() {
+        assert_eq!(parse_month("2026-05"), Ok((2026, 5)));
+        assert!(parse_month("May 2026").is_err());
+        assert!(parse_month("2026-xx").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_batch_rejects_oversized_input() {
+        let inputs = vec![0; MAX_BATCH_SEARCHES + 1];
+        let err = run_batch(inputs, |i| async move { Ok::<_, String>(i) })
+            .await
+            .unwrap_err();
+        assert!(err.contains("maximum"));
+    }
+
+    /// POSTs a JSON-RPC message, returning the status, session id header and body.
+    async fn post_mcp(
+        service: &HttpService,
+        session_id: Option<&str>,
+        body: &str,
+    ) -> (u16, Option<String>, String) {
+        let mut request = axum::http::Request::post("/mcp")
+            .header("content-type", "application/json")
+            .header("accept", "application/json, text/event-stream");
+        if let Some(id) = session_id {
+            request = request.header("mcp-session-id", id);
+        }
+        let request = request
+            .body(axum::body::Body::from(body.to_string()))
+            .unwrap();
+        let response = service.handle(request).await;
+        let status = response.status().as_u16();
+        let session = response
+            .headers()
+            .get("mcp-session-id")
+            .map(|v| v.to_str().unwrap().to_string());
+        let body = axum::body::to_bytes(axum::body::Body::new(response.into_body()), usize::MAX)
+            .await
+            .unwrap();
+        (status, session, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_session_survives_restart_with_file_store() {
+        let dir = std::env::temp_dir().join(format!("delulu-mcp-sessions-{}", std::process::id()));
+        let store = || -> Arc<dyn SessionStore> { Arc::new(FileSessionStore::new(&dir).unwrap()) };
+
+        let before = http_service(fake_server(), store());
+        let (status, session, _) = post_mcp(
+            &before,
+            None,
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-03-26","capabilities":{},"clientInfo":{"name":"test","version":"0"}}}"#,
+        )
+        .await;
+        assert_eq!(status, 200);
+        let session = session.expect("session id header");
+        let (status, _, _) = post_mcp(
+            &before,
+            Some(&session),
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+        )
+        .await;
+        assert_eq!(status, 202);
+
+        // A fresh service on the same directory stands in for a restarted server.
+        let tools_list = r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#;
+        let after = http_service(fake_server(), store());
+        let (status, _, body) = post_mcp(&after, Some(&session), tools_list).await;
+        assert_eq!(status, 200);
+        assert!(body.contains("search_flights"), "{body}");
+
+        // Without persistence the restarted server doesn't know the session.
+        let forgetful = http_service(fake_server(), Arc::new(MemorySessionStore::default()));
+        let (status, _, _) = post_mcp(&forgetful, Some(&session), tools_list).await;
+        assert_eq!(status, 401);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_qps_flag_caps_throughput() {
+        let args = Args::try_parse_from(["travel-mcp", "stdio", "--qps", "1"]).unwrap();
+        let queue = args
+            .command
+            .queue_args()
+            .unwrap()
+            .query_queue()
+            .unwrap()
+            .unwrap();
+
+        // Stub search: three instant requests need two refills at 1 QPS.
+        let start = std::time::Instant::now();
+        for _ in 0..3 {
+            queue.with_retry(|| async { Ok(()) }).await.unwrap();
+        }
+        assert!(start.elapsed() >= std::time::Duration::from_millis(1900));
+
+        let args = Args::try_parse_from(["travel-mcp", "http", "--max-concurrent", "0"]).unwrap();
+        assert!(args.command.queue_args().unwrap().query_queue().is_err());
+        let args = Args::try_parse_from(["travel-mcp", "http"]).unwrap();
+        assert!(
+            args.command
+                .queue_args()
+                .unwrap()
+                .query_queue()
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_export_schemas() {
+        let dir = std::env::temp_dir().join(format!("delulu-mcp-schemas-{}", std::process::id()));
+        let written = export_schemas(&dir).unwrap();
+        assert_eq!(written.len(), 2);
+
+        let flights: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&written[0]).unwrap()).unwrap();
+        let required = flights["required"].as_array().unwrap();
+        for field in ["from", "to", "date", "adults"] {
+            assert!(
+                required.contains(&field.into()),
+                "{field} not in {required:?}"
+            );
+        }
+        assert!(!required.contains(&"trip_type".into()));
+        let hotels: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&written[1]).unwrap()).unwrap();
+        let required = hotels["required"].as_array().unwrap();
+        for field in ["location", "checkin_date", "checkout_date", "adults"] {
+            assert!(
+                required.contains(&field.into()),
+                "{field} not in {required:?}"
+            );
+        }
+
+        // The committed copies must match the code; refresh them with
+        // `delulu-travel-mcp export-schemas` after changing an input. Compared
+        // as JSON since key order depends on serde_json's features.
+        let committed = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/schemas");
+        let read_json = |path: &Path| -> serde_json::Value {
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+        };
+        for path in &written {
+            let name = path.file_name().unwrap();
+            assert_eq!(
+                read_json(&committed.join(name)),
+                read_json(path),
+                "src/schemas/{} is stale",
+                name.to_string_lossy()
+            );
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
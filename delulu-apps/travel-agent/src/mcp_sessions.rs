@@ -0,0 +1,320 @@
+//!  Delulu Travel Agent
+//!
+//!  Copyright (C) 2026  Mamy Ratsimbazafy
+//!
+//!  This program is free software: you can redistribute it and/or modify
+//!  it under the terms of the GNU Affero General Public License as published by
+//!  the Free Software Foundation, either version 3 of the License, or
+//!  (at your option) any later version.
+//!
+//!  This program is distributed in the hope that it will be useful,
+//!  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//!  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//!  GNU Affero General Public License for more details.
+//!
+//!  You should have received a copy of the GNU Affero General Public License
+//!  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # MCP Sessions
+//!
+//! Session management for the streamable HTTP MCP server.
+//!
+//! Live sessions are held by rmcp's [`LocalSessionManager`]; a
+//! [`SessionStore`] additionally records each session's `initialize` request.
+//! When a client presents a session id the server doesn't know (typically
+//! after a restart), the session is rebuilt by replaying that handshake, so
+//! the client can carry on without re-initializing.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result, anyhow};
+use futures::Stream;
+use rmcp::model::{ClientJsonRpcMessage, ServerJsonRpcMessage};
+use rmcp::transport::streamable_http_server::session::local::{
+    LocalSessionManager, LocalSessionManagerError, LocalSessionWorker, create_local_session,
+};
+use rmcp::transport::streamable_http_server::session::{
+    ServerSseMessage, SessionId, SessionManager,
+};
+use rmcp::transport::{TransportAdapterIdentity, WorkerTransport};
+use rmcp::{RoleServer, Service, serve_server};
+
+/// Persistence for MCP session handshakes.
+///
+/// `initialize` is the client's serialized `initialize` request.
+pub trait SessionStore: Send + Sync + 'static {
+    fn save(&self, id: &str, initialize: &str) -> Result<()>;
+    fn load(&self, id: &str) -> Result<Option<String>>;
+    fn remove(&self, id: &str) -> Result<()>;
+}
+
+/// Keeps handshakes in memory: sessions do not survive a restart.
+#[derive(Debug, Default)]
+pub struct MemorySessionStore {
+    sessions: Mutex<HashMap<String, String>>,
+}
+
+impl SessionStore for MemorySessionStore {
+    fn save(&self, id: &str, initialize: &str) -> Result<()> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .insert(id.to_string(), initialize.to_string());
+        Ok(())
+    }
+
+    fn load(&self, id: &str) -> Result<Option<String>> {
+        Ok(self.sessions.lock().unwrap().get(id).cloned())
+    }
+
+    fn remove(&self, id: &str) -> Result<()> {
+        self.sessions.lock().unwrap().remove(id);
+        Ok(())
+    }
+}
+
+/// Keeps one `<session id>.json` file per session in a directory.
+#[derive(Debug, Clone)]
+pub struct FileSessionStore {
+    dir: PathBuf,
+}
+
+impl FileSessionStore {
+    /// Uses `dir` for session files, creating it if needed.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create session directory {}", dir.display()))?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, id: &str) -> Result<PathBuf> {
+        // Session ids come from a client header: never let one escape `dir`.
+        let valid = !id.is_empty()
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(anyhow!("Invalid session id {id:?}"));
+        }
+        Ok(self.dir.join(format!("{id}.json")))
+    }
+}
+
+impl SessionStore for FileSessionStore {
+    fn save(&self, id: &str, initialize: &str) -> Result<()> {
+        let path = self.path(id)?;
+        std::fs::write(&path, initialize)
+            .with_context(|| format!("Failed to write session file {}", path.display()))
+    }
+
+    fn load(&self, id: &str) -> Result<Option<String>> {
+        let path = self.path(id)?;
+        match std::fs::read_to_string(&path) {
+            Ok(initialize) => Ok(Some(initialize)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => {
+                Err(e).with_context(|| format!("Failed to read session file {}", path.display()))
+            }
+        }
+    }
+
+    fn remove(&self, id: &str) -> Result<()> {
+        let path = self.path(id)?;
+        match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("Failed to remove session file {}", path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SessionStoreError {
+    #[error(transparent)]
+    Session(#[from] LocalSessionManagerError),
+    #[error("Session store error: {0:#}")]
+    Store(anyhow::Error),
+}
+
+type ServiceFactory<S> = Arc<dyn Fn() -> S + Send + Sync>;
+
+/// [`SessionManager`] that records sessions in a [`SessionStore`] and rebuilds
+/// stored sessions on demand.
+///
+/// `service_factory` builds the MCP service for a rebuilt session, like the
+/// one given to `StreamableHttpService`.
+pub struct PersistentSessionManager<S> {
+    inner: Arc<LocalSessionManager>,
+    store: Arc<dyn SessionStore>,
+    service_factory: ServiceFactory<S>,
+    restoring: tokio::sync::Mutex<()>,
+}
+
+impl<S> PersistentSessionManager<S>
+where
+    S: Service<RoleServer> + Send + 'static,
+{
+    pub fn new(
+        store: Arc<dyn SessionStore>,
+        service_factory: impl Fn() -> S + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            inner: Arc::new(LocalSessionManager::default()),
+            store,
+            service_factory: Arc::new(service_factory),
+            restoring: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Rebuilds session `id` from its stored `initialize` request, mirroring
+    /// what the HTTP service does for a brand new session.
+    async fn restore(&self, id: &SessionId, initialize: &str) -> Result<(), SessionStoreError> {
+        let initialize: ClientJsonRpcMessage = serde_json::from_str(initialize)
+            .context("Corrupt stored initialize request")
+            .map_err(SessionStoreError::Store)?;
+        let initialized: ClientJsonRpcMessage =
+            serde_json::from_str(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#)
+                .expect("valid initialized notification");
+
+        let (handle, worker) = create_local_session(id.clone(), self.inner.session_config.clone());
+        self.inner.sessions.write().await.insert(id.clone(), handle);
+
+        let service = (self.service_factory)();
+        let inner = self.inner.clone();
+        let store = self.store.clone();
+        let session_id = id.clone();
+        tokio::spawn(async move {
+            let transport = WorkerTransport::spawn(worker);
+            match serve_server::<S, WorkerTransport<LocalSessionWorker>, _, TransportAdapterIdentity>(
+                service, transport,
+            )
+            .await
+            {
+                Ok(service) => {
+                    let _ = service.waiting().await;
+                }
+                Err(e) => tracing::error!("Failed to restore session {session_id}: {e}"),
+            }
+            let _ = inner.close_session(&session_id).await;
+            let _ = store.remove(&session_id);
+        });
+
+        self.inner.initialize_session(id, initialize).await?;
+        self.inner.accept_message(id, initialized).await?;
+        tracing::info!("Restored session {id}");
+        Ok(())
+    }
+}
+
+impl<S> SessionManager for PersistentSessionManager<S>
+where
+    S: Service<RoleServer> + Send + 'static,
+{
+    type Error = SessionStoreError;
+    type Transport = WorkerTransport<LocalSessionWorker>;
+
+    async fn create_session(&self) -> Result<(SessionId, Self::Transport), Self::Error> {
+        Ok(self.inner.create_session().await?)
+    }
+
+    async fn initialize_session(
+        &self,
+        id: &SessionId,
+        message: ClientJsonRpcMessage,
+    ) -> Result<ServerJsonRpcMessage, Self::Error> {
+        let initialize = serde_json::to_string(&message)
+            .context("Failed to serialize initialize request")
+            .map_err(SessionStoreError::Store)?;
+        let response = self.inner.initialize_session(id, message).await?;
+        self.store
+            .save(id, &initialize)
+            .map_err(SessionStoreError::Store)?;
+        Ok(response)
+    }
+
+    async fn has_session(&self, id: &SessionId) -> Result<bool, Self::Error> {
+        if self.inner.has_session(id).await? {
+            return Ok(true);
+        }
+        let _restoring = self.restoring.lock().await;
+        // Another request may have restored it while we waited.
+        if self.inner.has_session(id).await? {
+            return Ok(true);
+        }
+        match self.store.load(id) {
+            Ok(Some(initialize)) => {
+                self.restore(id, &initialize).await?;
+                Ok(true)
+            }
+            Ok(None) => Ok(false),
+            // An id the store can't even look up is one we don't have.
+            Err(e) => {
+                tracing::debug!("Session lookup failed: {e:#}");
+                Ok(false)
+            }
+        }
+    }
+
+    async fn close_session(&self, id: &SessionId) -> Result<(), Self::Error> {
+        self.inner.close_session(id).await?;
+        self.store.remove(id).map_err(SessionStoreError::Store)
+    }
+
+    async fn create_stream(
+        &self,
+        id: &SessionId,
+        message: ClientJsonRpcMessage,
+    ) -> Result<impl Stream<Item = ServerSseMessage> + Send + Sync + 'static, Self::Error> {
+        Ok(self.inner.create_stream(id, message).await?)
+    }
+
+    async fn accept_message(
+        &self,
+        id: &SessionId,
+        message: ClientJsonRpcMessage,
+    ) -> Result<(), Self::Error> {
+        Ok(self.inner.accept_message(id, message).await?)
+    }
+
+    async fn create_standalone_stream(
+        &self,
+        id: &SessionId,
+    ) -> Result<impl Stream<Item = ServerSseMessage> + Send + Sync + 'static, Self::Error> {
+        Ok(self.inner.create_standalone_stream(id).await?)
+    }
+
+    async fn resume(
+        &self,
+        id: &SessionId,
+        last_event_id: String,
+    ) -> Result<impl Stream<Item = ServerSseMessage> + Send + Sync + 'static, Self::Error> {
+        Ok(self.inner.resume(id, last_event_id).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_store_round_trip() {
+        let dir = std::env::temp_dir().join(format!("delulu-sessions-{}", std::process::id()));
+        let store = FileSessionStore::new(&dir).unwrap();
+        store.save("abc-123", "{}").unwrap();
+        assert_eq!(
+            FileSessionStore::new(&dir)
+                .unwrap()
+                .load("abc-123")
+                .unwrap(),
+            Some("{}".to_string())
+        );
+        store.remove("abc-123").unwrap();
+        assert_eq!(store.load("abc-123").unwrap(), None);
+        assert!(store.load("../escape").is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
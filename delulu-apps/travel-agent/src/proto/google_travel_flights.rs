@@ -20,8 +20,8 @@ pub struct Airport {
 /// In proto3, all fields are optional (no required keyword exists).
 /// However, the following fields are semantically REQUIRED for valid searches:
 /// - date: Must be a valid ISO 8601 date string ("YYYY-MM-DD")
-/// - from_flight: Must be Some with a non-empty airport code
-/// - to_flight: Must be Some with a non-empty airport code
+/// - from_flight: At least one non-empty airport code
+/// - to_flight: At least one non-empty airport code
 ///
 /// Optional markers in this schema indicate:
 /// - max_stops: When None → treat as unrestricted stops
@@ -38,12 +38,12 @@ pub struct FlightData {
     /// OPTIONAL: airline carrier codes (AA, UA, DL...); empty = no filter
     #[prost(string, repeated, tag = "6")]
     pub airlines: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
-    /// REQUIRED: origin airport (must be present with valid code)
-    #[prost(message, optional, tag = "13")]
-    pub from_flight: ::core::option::Option<Airport>,
-    /// REQUIRED: destination airport (must be present with valid code)
-    #[prost(message, optional, tag = "14")]
-    pub to_flight: ::core::option::Option<Airport>,
+    /// REQUIRED: origin airports; several are searched as alternatives
+    #[prost(message, repeated, tag = "13")]
+    pub from_flight: ::prost::alloc::vec::Vec<Airport>,
+    /// REQUIRED: destination airports; several are searched as alternatives
+    #[prost(message, repeated, tag = "14")]
+    pub to_flight: ::prost::alloc::vec::Vec<Airport>,
 }
 /// -----------------------------------------------------------------------------
 /// ProtoFlightSearch - Top-level container sent to Google Flights
@@ -0,0 +1,121 @@
+//!  Delulu Travel Agent
+//!
+//!  Copyright (C) 2026  Mamy Ratsimbazafy
+//!
+//!  This program is free software: you can redistribute it and/or modify
+//!  it under the terms of the GNU Affero General Public License as published by
+//!  the Free Software Foundation, either version 3 of the License, or
+//!  (at your option) any later version.
+//!
+//!  This program is distributed in the hope that it will be useful,
+//!  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//!  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//!  GNU Affero General Public License for more details.
+//!
+//!  You should have received a copy of the GNU Affero General Public License
+//!  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Protobuf Debug Dump
+//!
+//! Schema-less pretty printer for protobuf wire data, used to reverse-engineer
+//! the `tfs`/`ts` formats: it shows every field number, including the ones our
+//! `.proto` files don't model yet.
+//!
+//! Length-delimited fields are shown as a string when printable, as a nested
+//! message when they parse as one, and as hex bytes otherwise.
+
+use anyhow::{Result, bail};
+use std::fmt::Write as _;
+
+/// Pretty-prints `bytes` as `field: value` lines, nested messages indented.
+pub(crate) fn dump_protobuf(bytes: &[u8]) -> Result<String> {
+    let fields = parse_message(bytes)?;
+    let mut out = String::new();
+    write_fields(&mut out, &fields, 0);
+    Ok(out)
+}
+
+enum Value<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Fixed32(u32),
+    Bytes(&'a [u8]),
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let Some(&byte) = bytes.get(*pos) else {
+            bail!("truncated varint at byte {}", *pos);
+        };
+        *pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    bail!("varint longer than 10 bytes at byte {}", *pos)
+}
+
+fn take<'a>(bytes: &'a [u8], pos: &mut usize, len: usize) -> Result<&'a [u8]> {
+    let Some(slice) = pos.checked_add(len).and_then(|end| bytes.get(*pos..end)) else {
+        bail!("field of {len} bytes overruns the message at byte {}", *pos);
+    };
+    *pos += len;
+    Ok(slice)
+}
+
+fn parse_message(bytes: &[u8]) -> Result<Vec<(u64, Value<'_>)>> {
+    let mut fields = Vec::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let key = read_varint(bytes, &mut pos)?;
+        let (field, wire_type) = (key >> 3, key & 0x7);
+        if field == 0 {
+            bail!("invalid field number 0 at byte {pos}");
+        }
+        let value = match wire_type {
+            0 => Value::Varint(read_varint(bytes, &mut pos)?),
+            1 => Value::Fixed64(u64::from_le_bytes(
+                take(bytes, &mut pos, 8)?.try_into().unwrap(),
+            )),
+            2 => {
+                let len = read_varint(bytes, &mut pos)? as usize;
+                Value::Bytes(take(bytes, &mut pos, len)?)
+            }
+            5 => Value::Fixed32(u32::from_le_bytes(
+                take(bytes, &mut pos, 4)?.try_into().unwrap(),
+            )),
+            other => bail!("unsupported wire type {other} for field {field}"),
+        };
+        fields.push((field, value));
+    }
+    Ok(fields)
+}
+
+fn write_fields(out: &mut String, fields: &[(u64, Value<'_>)], depth: usize) {
+    let indent = "  ".repeat(depth);
+    for (field, value) in fields {
+        match value {
+            Value::Varint(v) => writeln!(out, "{indent}{field}: {v}").unwrap(),
+            Value::Fixed64(v) => writeln!(out, "{indent}{field}: fixed64 {v:#018x}").unwrap(),
+            Value::Fixed32(v) => writeln!(out, "{indent}{field}: fixed32 {v:#010x}").unwrap(),
+            Value::Bytes([]) => writeln!(out, "{indent}{field}: <empty>").unwrap(),
+            Value::Bytes(bytes) => {
+                let printable = std::str::from_utf8(bytes)
+                    .ok()
+                    .filter(|s| s.chars().all(|c| !c.is_control()));
+                if let Some(s) = printable {
+                    writeln!(out, "{indent}{field}: {s:?}").unwrap();
+                } else if let Ok(nested) = parse_message(bytes) {
+                    writeln!(out, "{indent}{field}: {{").unwrap();
+                    write_fields(out, &nested, depth + 1);
+                    writeln!(out, "{indent}}}").unwrap();
+                } else {
+                    let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+                    writeln!(out, "{indent}{field}: bytes 0x{hex}").unwrap();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,248 @@
+//!  Delulu Travel Agent
+//!
+//!  Copyright (C) 2026  Mamy Ratsimbazafy
+//!
+//!  This program is free software: you can redistribute it and/or modify
+//!  it under the terms of the GNU Affero General Public License as published by
+//!  the Free Software Foundation, either version 3 of the License, or
+//!  (at your option) any later version.
+//!
+//!  This program is distributed in the hope that it will be useful,
+//!  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//!  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//!  GNU Affero General Public License for more details.
+//!
+//!  You should have received a copy of the GNU Affero General Public License
+//!  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Search Providers
+//!
+//! Provider-agnostic search interfaces behind the MCP tools. The Google
+//! clients implement them; another backend only has to implement these traits
+//! to be served by the same tools.
+
+use std::future::Future;
+use std::pin::Pin;
+
+use thiserror::Error;
+use tokio_util::sync::CancellationToken;
+
+use crate::diagnostics::{SearchDiagnosis, UnexpectedLayout};
+use crate::flights_query_builder::FlightSearchParams;
+use crate::flights_results_parser::FlightSearchResult;
+use crate::flights_search::GoogleFlightsClient;
+use crate::hotels_query_builder::HotelSearchParams;
+use crate::hotels_results_parser::HotelSearchResult;
+use crate::hotels_search::GoogleHotelsClient;
+
+/// Boxed future returned by provider searches, so the traits stay object safe.
+pub type SearchFuture<'a, T, E> = Pin<Box<dyn Future<Output = Result<T, E>> + Send + 'a>>;
+
+#[derive(Debug, Error)]
+pub enum FlightsError {
+    #[error("invalid search parameters: {0:#}")]
+    InvalidParams(anyhow::Error),
+    /// Google could not be reached: the connection was refused or not
+    /// established within the connect timeout.
+    #[error("could not connect to Google: {0:#}")]
+    ConnectTimeout(anyhow::Error),
+    /// Connected, but Google did not answer within the read or overall timeout.
+    #[error("Google did not respond in time: {0:#}")]
+    ReadTimeout(anyhow::Error),
+    /// The caller cancelled the search before it completed.
+    #[error("search cancelled")]
+    Cancelled,
+    /// Google answered with a page layout the parser has no selectors for,
+    /// see [`UnexpectedLayout`].
+    #[error("{0}")]
+    UnexpectedLayout(UnexpectedLayout),
+    #[error("{0:#}")]
+    Search(#[from] anyhow::Error),
+}
+
+impl FlightsError {
+    /// Wraps a failed search, telling connect and read timeouts and
+    /// unexpected page layouts apart from other failures.
+    pub fn from_search(err: anyhow::Error) -> Self {
+        if let Some(layout) = err
+            .chain()
+            .find_map(|e| e.downcast_ref::<UnexpectedLayout>())
+        {
+            return Self::UnexpectedLayout(layout.clone());
+        }
+        match http_failure(&err) {
+            Some(HttpFailure::Connect) => Self::ConnectTimeout(err),
+            Some(HttpFailure::Read) => Self::ReadTimeout(err),
+            None => Self::Search(err),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum HotelsError {
+    #[error("invalid search parameters: {0:#}")]
+    InvalidParams(anyhow::Error),
+    /// Google could not be reached: the connection was refused or not
+    /// established within the connect timeout.
+    #[error("could not connect to Google: {0:#}")]
+    ConnectTimeout(anyhow::Error),
+    /// Connected, but Google did not answer within the read or overall timeout.
+    #[error("Google did not respond in time: {0:#}")]
+    ReadTimeout(anyhow::Error),
+    #[error("{0:#}")]
+    Search(#[from] anyhow::Error),
+}
+
+impl HotelsError {
+    /// Wraps a failed search, telling connect and read timeouts apart from
+    /// other failures.
+    pub fn from_search(err: anyhow::Error) -> Self {
+        match http_failure(&err) {
+            Some(HttpFailure::Connect) => Self::ConnectTimeout(err),
+            Some(HttpFailure::Read) => Self::ReadTimeout(err),
+            None => Self::Search(err),
+        }
+    }
+}
+
+enum HttpFailure {
+    Connect,
+    Read,
+}
+
+/// Finds the HTTP client error behind `err`, if it gave up connecting or
+/// waiting for the response.
+fn http_failure(err: &anyhow::Error) -> Option<HttpFailure> {
+    let http = err.chain().find_map(|e| e.downcast_ref::<wreq::Error>())?;
+    if http.is_connect() {
+        Some(HttpFailure::Connect)
+    } else if http.is_timeout() {
+        Some(HttpFailure::Read)
+    } else {
+        None
+    }
+}
+
+/// A flight search backend.
+pub trait FlightProvider: Send + Sync {
+    fn search<'a>(
+        &'a self,
+        params: &'a FlightSearchParams,
+    ) -> SearchFuture<'a, FlightSearchResult, FlightsError>;
+
+    /// [`Self::search`], abandoned with [`FlightsError::Cancelled`] as soon
+    /// as `token` is cancelled.
+    fn search_cancellable<'a>(
+        &'a self,
+        params: &'a FlightSearchParams,
+        token: CancellationToken,
+    ) -> SearchFuture<'a, FlightSearchResult, FlightsError> {
+        Box::pin(async move {
+            tokio::select! {
+                biased;
+                _ = token.cancelled() => Err(FlightsError::Cancelled),
+                result = self.search(params) => result,
+            }
+        })
+    }
+
+    /// Explains the last response, for providers that keep it.
+    fn diagnose_last_search(&self) -> Option<SearchDiagnosis> {
+        None
+    }
+}
+
+/// A hotel search backend.
+pub trait HotelProvider: Send + Sync {
+    fn search<'a>(
+        &'a self,
+        params: &'a HotelSearchParams,
+    ) -> SearchFuture<'a, HotelSearchResult, HotelsError>;
+}
+
+impl FlightProvider for GoogleFlightsClient {
+    fn search<'a>(
+        &'a self,
+        params: &'a FlightSearchParams,
+    ) -> SearchFuture<'a, FlightSearchResult, FlightsError> {
+        Box::pin(async move {
+            params.validate().map_err(FlightsError::InvalidParams)?;
+            self.search_flights(params)
+                .await
+                .map_err(FlightsError::from_search)
+        })
+    }
+
+    fn search_cancellable<'a>(
+        &'a self,
+        params: &'a FlightSearchParams,
+        token: CancellationToken,
+    ) -> SearchFuture<'a, FlightSearchResult, FlightsError> {
+        Box::pin(GoogleFlightsClient::search_cancellable(self, params, token))
+    }
+
+    fn diagnose_last_search(&self) -> Option<SearchDiagnosis> {
+        GoogleFlightsClient::diagnose_last_search(self)
+    }
+}
+
+impl HotelProvider for GoogleHotelsClient {
+    fn search<'a>(
+        &'a self,
+        params: &'a HotelSearchParams,
+    ) -> SearchFuture<'a, HotelSearchResult, HotelsError> {
+        Box::pin(async move {
+            params.validate().map_err(HotelsError::InvalidParams)?;
+            self.search_hotels(params)
+                .await
+                .map_err(HotelsError::from_search)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flights_query_builder::Trip;
+    use crate::test_support::{refused_url, spawn_stalled_server};
+    use std::time::Duration;
+
+    fn next_week() -> FlightSearchParams {
+        let depart = chrono::Local::now().date_naive() + chrono::Duration::days(7);
+        FlightSearchParams::builder("SFO".into(), "JFK".into(), depart)
+            .trip_type(Trip::OneWay)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_read_timeout_variant() {
+        let url = spawn_stalled_server().await;
+        let client = GoogleFlightsClient::new("en".into(), "USD".into(), 30, 10)
+            .unwrap()
+            .base_url(&url)
+            .read_timeout(Duration::from_millis(100))
+            .unwrap();
+
+        let err = FlightProvider::search(&client, &next_week())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, FlightsError::ReadTimeout(_)), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn test_connect_error_variant() {
+        let url = refused_url().await;
+        let client = GoogleFlightsClient::new("en".into(), "USD".into(), 30, 10)
+            .unwrap()
+            .base_url(&url)
+            .connect_timeout(Duration::from_millis(500))
+            .unwrap();
+
+        let err = FlightProvider::search(&client, &next_week())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, FlightsError::ConnectTimeout(_)), "{err:?}");
+        assert!(err.to_string().starts_with("could not connect"), "{err}");
+    }
+}
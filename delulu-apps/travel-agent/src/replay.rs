@@ -0,0 +1,237 @@
+//!  Delulu Travel Agent
+//!
+//!  Copyright (C) 2026  Mamy Ratsimbazafy
+//!
+//!  This program is free software: you can redistribute it and/or modify
+//!  it under the terms of the GNU Affero General Public License as published by
+//!  the Free Software Foundation, either version 3 of the License, or
+//!  (at your option) any later version.
+//!
+//!  This program is distributed in the hope that it will be useful,
+//!  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//!  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//!  GNU Affero General Public License for more details.
+//!
+//!  You should have received a copy of the GNU Affero General Public License
+//!  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Replay Clients
+//!
+//! Providers that answer searches from captured Google pages instead of the
+//! network, so the whole MCP tool path can run offline and deterministically.
+//! Only built with the `replay` feature.
+//!
+//! Fixtures are the zstd-compressed `<name>.html.zst` pages used by the parser
+//! tests. A request is mapped to a fixture by name:
+//! - flights: `<tag>-<from>_<to>[_<anything>]`, e.g. `nonstop-sfo_jfk_economy`
+//!   answers SFO→JFK on any date,
+//! - hotels: `<location>-<tag>`, e.g. `paris-budget` answers "Paris".
+//!
+//! When several fixtures match, the first by file name wins; [`fixture`]
+//! pins a request to a specific one.
+//!
+//! [`fixture`]: ReplayFlightsClient::fixture
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::flights_query_builder::FlightSearchParams;
+use crate::flights_results_parser::FlightSearchResult;
+use crate::hotels_query_builder::HotelSearchParams;
+use crate::hotels_results_parser::HotelSearchResult;
+use crate::providers::{FlightProvider, FlightsError, HotelProvider, HotelsError, SearchFuture};
+
+const FIXTURE_EXTENSION: &str = ".html.zst";
+
+/// Replays flight searches from a directory of captured result pages.
+#[derive(Debug, Clone)]
+pub struct ReplayFlightsClient {
+    dir: PathBuf,
+    pinned: HashMap<String, String>,
+}
+
+impl ReplayFlightsClient {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            pinned: HashMap::new(),
+        }
+    }
+
+    /// Answers `from`→`to` searches with fixture `name` (without extension).
+    pub fn fixture(mut self, from: &str, to: &str, name: impl Into<String>) -> Self {
+        self.pinned.insert(route_key(from, to), name.into());
+        self
+    }
+
+    fn fixture_for(&self, params: &FlightSearchParams) -> Result<String> {
+        let route = route_key(&params.from_airport, &params.to_airport);
+        if let Some(name) = self.pinned.get(&route) {
+            return Ok(name.clone());
+        }
+        find_fixture(&self.dir, |name| {
+            name.split_once('-')
+                .is_some_and(|(_, rest)| rest == route || rest.starts_with(&format!("{route}_")))
+        })?
+        .ok_or_else(|| {
+            anyhow!(
+                "No replay fixture for {}→{} in {}",
+                params.from_airport,
+                params.to_airport,
+                self.dir.display()
+            )
+        })
+    }
+}
+
+impl FlightProvider for ReplayFlightsClient {
+    fn search<'a>(
+        &'a self,
+        params: &'a FlightSearchParams,
+    ) -> SearchFuture<'a, FlightSearchResult, FlightsError> {
+        Box::pin(async move {
+            params.validate().map_err(FlightsError::InvalidParams)?;
+            let html = load_fixture(&self.dir, &self.fixture_for(params)?)?;
+            Ok(FlightSearchResult::from_html(&html, params.clone())?)
+        })
+    }
+}
+
+/// Replays hotel searches from a directory of captured result pages.
+#[derive(Debug, Clone)]
+pub struct ReplayHotelsClient {
+    dir: PathBuf,
+    pinned: HashMap<String, String>,
+}
+
+impl ReplayHotelsClient {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            pinned: HashMap::new(),
+        }
+    }
+
+    /// Answers searches for `location` with fixture `name` (without extension).
+    pub fn fixture(mut self, location: &str, name: impl Into<String>) -> Self {
+        self.pinned.insert(location_key(location), name.into());
+        self
+    }
+
+    fn fixture_for(&self, params: &HotelSearchParams) -> Result<String> {
+        let location = location_key(&params.loc_q_search);
+        if let Some(name) = self.pinned.get(&location) {
+            return Ok(name.clone());
+        }
+        find_fixture(&self.dir, |name| {
+            name.split_once('-')
+                .is_some_and(|(prefix, _)| prefix == location)
+        })?
+        .ok_or_else(|| {
+            anyhow!(
+                "No replay fixture for {:?} in {}",
+                params.loc_q_search,
+                self.dir.display()
+            )
+        })
+    }
+}
+
+impl HotelProvider for ReplayHotelsClient {
+    fn search<'a>(
+        &'a self,
+        params: &'a HotelSearchParams,
+    ) -> SearchFuture<'a, HotelSearchResult, HotelsError> {
+        Box::pin(async move {
+            params.validate().map_err(HotelsError::InvalidParams)?;
+            let html = load_fixture(&self.dir, &self.fixture_for(params)?)?;
+            Ok(HotelSearchResult::from_html(&html)?)
+        })
+    }
+}
+
+fn route_key(from: &str, to: &str) -> String {
+    format!("{}_{}", from.to_ascii_lowercase(), to.to_ascii_lowercase())
+}
+
+fn location_key(location: &str) -> String {
+    location
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// First fixture in `dir`, by file name, whose name satisfies `matches`.
+fn find_fixture(dir: &Path, matches: impl Fn(&str) -> bool) -> Result<Option<String>> {
+    let entries = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read fixture directory {}", dir.display()))?;
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| {
+            let file_name = entry.ok()?.file_name().into_string().ok()?;
+            file_name
+                .strip_suffix(FIXTURE_EXTENSION)
+                .map(str::to_string)
+        })
+        .filter(|name| matches(name))
+        .collect();
+    names.sort();
+    Ok(names.into_iter().next())
+}
+
+fn load_fixture(dir: &Path, name: &str) -> Result<String> {
+    let path = dir.join(format!("{name}{FIXTURE_EXTENSION}"));
+    let compressed = std::fs::read(&path)
+        .with_context(|| format!("Failed to read fixture {}", path.display()))?;
+    let html = zstd::decode_all(compressed.as_slice())
+        .with_context(|| format!("Failed to decompress fixture {}", path.display()))?;
+    String::from_utf8(html).with_context(|| format!("Fixture {} is not UTF-8", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hotels_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures-hotels-parsing")
+    }
+
+    #[test]
+    fn test_hotel_fixture_mapping() {
+        let client = ReplayHotelsClient::new(hotels_dir());
+        let params = |location: &str| {
+            let date = chrono::NaiveDate::from_ymd_opt(2030, 1, 1).unwrap();
+            HotelSearchParams::builder(
+                location.to_string(),
+                date,
+                date + chrono::Days::new(2),
+                2,
+                Vec::new(),
+            )
+            .build()
+            .unwrap()
+        };
+
+        assert_eq!(
+            client.fixture_for(&params("Paris")).unwrap(),
+            "paris-budget"
+        );
+        // Two Tokyo captures: the first by name wins unless one is pinned.
+        assert_eq!(client.fixture_for(&params("Tokyo")).unwrap(), "tokyo-5star");
+        assert!(client.fixture_for(&params("New York")).is_err());
+
+        let client = client
+            .fixture("Tokyo", "tokyo-standard")
+            .fixture("New York", "nyc-families");
+        assert_eq!(
+            client.fixture_for(&params("tokyo")).unwrap(),
+            "tokyo-standard"
+        );
+        assert_eq!(
+            client.fixture_for(&params("New York")).unwrap(),
+            "nyc-families"
+        );
+    }
+}
@@ -0,0 +1,40 @@
+//!  Delulu Travel Agent
+//!
+//!  Copyright (C) 2026  Mamy Ratsimbazafy
+//!
+//!  This program is free software: you can redistribute it and/or modify
+//!  it under the terms of the GNU Affero General Public License as published by
+//!  the Free Software Foundation, either version 3 of the License, or
+//!  (at your option) any later version.
+//!
+//!  This program is distributed in the hope that it will be useful,
+//!  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//!  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//!  GNU Affero General Public License for more details.
+//!
+//!  You should have received a copy of the GNU Affero General Public License
+//!  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Live query queue
+//!
+//! The rate limit shared by the live integration tests. Every request they
+//! send to Google goes through one [`QueryQueue`], so spacing and backoff are
+//! tuned here rather than with sleeps in each test.
+
+use delulu_query_queues::QueryQueue;
+use once_cell::sync::Lazy;
+
+/// Requests per second sent to Google by the live tests of one test binary.
+pub const LIVE_QPS: u64 = 1;
+
+static LIVE_QUEUE: Lazy<QueryQueue> = Lazy::new(new_live_queue);
+
+/// A queue with the live tests' settings, independent of the shared one.
+pub fn new_live_queue() -> QueryQueue {
+    QueryQueue::with_qps_limit(LIVE_QPS)
+}
+
+/// The queue shared by every live test of the test binary.
+pub fn live_queue() -> QueryQueue {
+    LIVE_QUEUE.clone()
+}
@@ -1,6 +1,7 @@
 //! Shared MCP test helpers for streaming subprocess output.
 
-use anyhow::Result;
+use anyhow::{Context, Result, ensure};
+use serde_json::Value;
 use std::path::PathBuf;
 use tokio::io::AsyncReadExt;
 
@@ -44,3 +45,196 @@ pub async fn stream_stderr_to_console(mut stderr: tokio::process::ChildStderr) {
         }
     }
 }
+
+/// JSON messages sent by the server in `raw`, in order.
+///
+/// `raw` is either what was read from the socket, possibly several HTTP
+/// responses back to back with chunked or `Content-Length` bodies, or a bare
+/// body. Bodies are read as server-sent events, each `data:` payload being
+/// one JSON message, or as a single JSON document.
+pub fn parse_sse_response(raw: &str) -> Result<Vec<Value>> {
+    let mut messages = Vec::new();
+    for body in http_bodies(raw.as_bytes())? {
+        messages.extend(body_messages(&body)?);
+    }
+    Ok(messages)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// The bodies of the HTTP responses in `raw`, or `raw` itself when it does
+/// not start with a status line.
+fn http_bodies(mut raw: &[u8]) -> Result<Vec<String>> {
+    if !raw.starts_with(b"HTTP/") {
+        return Ok(vec![String::from_utf8(raw.to_vec())?]);
+    }
+    let mut bodies = Vec::new();
+    while raw.starts_with(b"HTTP/") {
+        let header_end = find(raw, b"\r\n\r\n").context("Unterminated HTTP headers")?;
+        let headers = std::str::from_utf8(&raw[..header_end])?;
+        raw = &raw[header_end + 4..];
+        let header = |name: &str| {
+            headers
+                .lines()
+                .skip(1)
+                .filter_map(|line| line.split_once(':'))
+                .find(|(key, _)| key.trim().eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.trim())
+        };
+
+        let body = if header("transfer-encoding").is_some_and(|v| v.eq_ignore_ascii_case("chunked"))
+        {
+            let (body, rest) = decode_chunked(raw)?;
+            raw = rest;
+            body
+        } else if let Some(length) = header("content-length") {
+            let length: usize = length.parse().context("Invalid Content-Length")?;
+            ensure!(raw.len() >= length, "Body shorter than its Content-Length");
+            let (body, rest) = raw.split_at(length);
+            raw = rest;
+            body.to_vec()
+        } else if raw.starts_with(b"HTTP/") {
+            Vec::new()
+        } else {
+            std::mem::take(&mut raw).to_vec()
+        };
+        bodies.push(String::from_utf8(body)?);
+    }
+    Ok(bodies)
+}
+
+/// Decodes a chunked body, returning it and the bytes after it. A stream cut
+/// off between chunks, before the `0` marker, yields what was received.
+fn decode_chunked(mut raw: &[u8]) -> Result<(Vec<u8>, &[u8])> {
+    let mut body = Vec::new();
+    while !raw.is_empty() {
+        let line_end = find(raw, b"\r\n").context("Unterminated chunk size line")?;
+        let size_line = std::str::from_utf8(&raw[..line_end])?;
+        // Chunk extensions (`;name=value`) are allowed after the size
+        let size_hex = size_line.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size_hex, 16)
+            .with_context(|| format!("Invalid chunk size {size_line:?}"))?;
+        raw = &raw[line_end + 2..];
+        if size == 0 {
+            // Skip trailers up to the blank line ending the body
+            while !raw.is_empty() && !raw.starts_with(b"\r\n") {
+                let line_end = find(raw, b"\r\n").context("Unterminated trailer")?;
+                raw = &raw[line_end + 2..];
+            }
+            return Ok((body, raw.strip_prefix(b"\r\n").unwrap_or(raw)));
+        }
+        ensure!(raw.len() >= size, "Truncated chunk of {size} bytes");
+        body.extend_from_slice(&raw[..size]);
+        raw = raw[size..]
+            .strip_prefix(b"\r\n")
+            .context("Missing CRLF after chunk")?;
+    }
+    Ok((body, raw))
+}
+
+/// JSON messages of one body: a JSON document or a stream of events.
+fn body_messages(body: &str) -> Result<Vec<Value>> {
+    let trimmed = body.trim();
+    if trimmed.is_empty() {
+        return Ok(Vec::new());
+    }
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        return Ok(vec![
+            serde_json::from_str(trimmed).context("Invalid JSON body")?,
+        ]);
+    }
+    let body = body.replace("\r\n", "\n");
+    let mut messages = Vec::new();
+    for event in body.split("\n\n") {
+        let data: Vec<&str> = event
+            .lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .map(|data| data.strip_prefix(' ').unwrap_or(data))
+            .collect();
+        // Events without data, such as priming events, carry no message
+        if data.iter().all(|d| d.is_empty()) {
+            continue;
+        }
+        let data = data.join("\n");
+        messages.push(
+            serde_json::from_str(&data)
+                .with_context(|| format!("Invalid JSON in SSE event: {data:?}"))?,
+        );
+    }
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// `body` as a chunked HTTP response, split into `chunk`-byte chunks.
+    fn chunked_response(body: &str, chunk: usize) -> String {
+        let mut raw = "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ntransfer-encoding: chunked\r\n\r\n".as_bytes().to_vec();
+        for part in body.as_bytes().chunks(chunk) {
+            raw.extend_from_slice(format!("{:x}\r\n", part.len()).as_bytes());
+            raw.extend_from_slice(part);
+            raw.extend_from_slice(b"\r\n");
+        }
+        raw.extend_from_slice(b"0\r\n\r\n");
+        String::from_utf8(raw).unwrap()
+    }
+
+    #[test]
+    fn test_single_event() {
+        let raw = chunked_response(
+            "data: {\"jsonrpc\":\"2.0\",\"id\":2,\"result\":{}}\n\n",
+            1024,
+        );
+        assert_eq!(
+            parse_sse_response(&raw).unwrap(),
+            [json!({"jsonrpc": "2.0", "id": 2, "result": {}})]
+        );
+    }
+
+    #[test]
+    fn test_multiple_events_across_chunks() {
+        // Small chunks split events, and the "é", mid-way
+        let body = "id: 0\nretry: 3000\ndata:\n\n\
+                    event: message\ndata: {\"id\":1,\"result\":\"café\"}\n\n\
+                    data: {\"id\":2,\r\ndata: \"result\":[]}\r\n\r\n";
+        let raw = chunked_response(body, 7);
+        assert_eq!(
+            parse_sse_response(&raw).unwrap(),
+            [
+                json!({"id": 1, "result": "café"}),
+                json!({"id": 2, "result": []})
+            ]
+        );
+    }
+
+    #[test]
+    fn test_responses_back_to_back() {
+        // The 202 to a notification, then the tool call's stream with a
+        // trailing chunk marker and a trailer
+        let raw = format!(
+            "HTTP/1.1 202 Accepted\r\ncontent-length: 0\r\n\r\n{}",
+            chunked_response("data: {\"id\":2}\n\n", 5)
+                .replace("0\r\n\r\n", "0\r\nx-trailer: 1\r\n\r\n")
+        );
+        assert_eq!(parse_sse_response(&raw).unwrap(), [json!({"id": 2})]);
+
+        let json_body = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 8\r\n\r\n{\"id\":3}";
+        assert_eq!(parse_sse_response(json_body).unwrap(), [json!({"id": 3})]);
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        let raw = chunked_response("data: {\"id\":2}\n\n", 1024);
+        // Stream cut before the end marker still yields the complete chunks
+        let cut = &raw[..raw.len() - "0\r\n\r\n".len()];
+        assert_eq!(parse_sse_response(cut).unwrap(), [json!({"id": 2})]);
+
+        let bad_size = raw.replacen("\r\n\r\n", "\r\n\r\nzz", 1);
+        assert!(parse_sse_response(&bad_size).is_err());
+        assert!(parse_sse_response("data: {not json\n\n").is_err());
+    }
+}
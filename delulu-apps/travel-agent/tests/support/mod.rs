@@ -0,0 +1,160 @@
+//!  Delulu Travel Agent
+//!
+//!  Copyright (C) 2026  Mamy Ratsimbazafy
+//!
+//!  This program is free software: you can redistribute it and/or modify
+//!  it under the terms of the GNU Affero General Public License as published by
+//!  the Free Software Foundation, either version 3 of the License, or
+//!  (at your option) any later version.
+//!
+//!  This program is distributed in the hope that it will be useful,
+//!  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//!  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//!  GNU Affero General Public License for more details.
+//!
+//!  You should have received a copy of the GNU Affero General Public License
+//!  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! # Fixture index
+//!
+//! Scans the committed parsing fixtures and reports, for each one, its size,
+//! the route encoded in its filename and whether it still parses.
+//! Print the table with:
+//!
+//! ```sh
+//! cargo test --test t_fixture_index -- --nocapture
+//! ```
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use delulu_travel_agent::{FlightSearchParams, FlightSearchResult, HotelSearchResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixtureKind {
+    Flights,
+    Hotels,
+}
+
+impl FixtureKind {
+    fn dir(self) -> &'static str {
+        match self {
+            FixtureKind::Flights => "tests/fixtures-flights-parsing",
+            FixtureKind::Hotels => "tests/fixtures-hotels-parsing",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FixtureInfo {
+    pub kind: FixtureKind,
+    /// Filename without `.html.zst`
+    pub name: String,
+    pub compressed_bytes: u64,
+    pub html_bytes: usize,
+    /// Origin and destination, for flights fixtures named `<tags>-<from>_<to>[_<cabin>]`
+    pub route: Option<(String, String)>,
+    /// Itineraries or hotels parsed, or the parse error
+    pub parsed: Result<usize, String>,
+}
+
+/// Route of a flights fixture name, e.g. "nonstop-sfo_jfk_economy" -> (SFO, JFK).
+pub fn route_from_name(name: &str) -> Option<(String, String)> {
+    let (_, route) = name.rsplit_once('-')?;
+    let mut codes = route.split('_');
+    let from = codes.next()?;
+    let to = codes.next()?;
+    let is_iata = |code: &str| code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic());
+    (is_iata(from) && is_iata(to)).then(|| (from.to_uppercase(), to.to_uppercase()))
+}
+
+/// Every `*.html.zst` fixture of both parsers, sorted by kind then name.
+pub fn scan_fixtures() -> Vec<FixtureInfo> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut fixtures = Vec::new();
+    for kind in [FixtureKind::Flights, FixtureKind::Hotels] {
+        let dir = root.join(kind.dir());
+        let entries = std::fs::read_dir(&dir)
+            .unwrap_or_else(|e| panic!("Failed to list {}: {}", dir.display(), e));
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| {
+                let file_name = entry.ok()?.file_name().into_string().ok()?;
+                file_name.strip_suffix(".html.zst").map(str::to_string)
+            })
+            .collect();
+        names.sort();
+
+        for name in names {
+            let path = dir.join(format!("{name}.html.zst"));
+            let compressed = std::fs::read(&path)
+                .unwrap_or_else(|e| panic!("Failed to read {}: {}", path.display(), e));
+            let html = zstd::decode_all(compressed.as_slice())
+                .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                .unwrap_or_else(|e| panic!("Failed to decompress {}: {}", path.display(), e));
+            let route = match kind {
+                FixtureKind::Flights => route_from_name(&name),
+                FixtureKind::Hotels => None,
+            };
+            let parsed = parse(kind, &html, route.as_ref());
+            fixtures.push(FixtureInfo {
+                kind,
+                name,
+                compressed_bytes: compressed.len() as u64,
+                html_bytes: html.len(),
+                route,
+                parsed,
+            });
+        }
+    }
+    fixtures
+}
+
+fn parse(kind: FixtureKind, html: &str, route: Option<&(String, String)>) -> Result<usize, String> {
+    match kind {
+        FixtureKind::Flights => {
+            let (from, to) = route.ok_or("no route in the filename")?;
+            let params = FlightSearchParams::builder(
+                from.clone(),
+                to.clone(),
+                chrono::NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+            )
+            .build()
+            .map_err(|e| e.to_string())?;
+            FlightSearchResult::from_html(html, params)
+                .map(|result| result.itineraries.len())
+                .map_err(|e| e.to_string())
+        }
+        FixtureKind::Hotels => HotelSearchResult::from_html(html)
+            .map(|result| result.hotels.len())
+            .map_err(|e| e.to_string()),
+    }
+}
+
+/// One line per fixture: kind, name, route, sizes in KiB and parse outcome.
+pub fn summary_table(fixtures: &[FixtureInfo]) -> String {
+    let mut table = format!(
+        "{:<8} {:<32} {:<8} {:>8} {:>8}  {}\n",
+        "kind", "fixture", "route", "zst KiB", "html KiB", "parses"
+    );
+    for f in fixtures {
+        let route = f
+            .route
+            .as_ref()
+            .map_or("-".to_string(), |(from, to)| format!("{from}-{to}"));
+        let parsed = match &f.parsed {
+            Ok(count) => format!("yes ({count})"),
+            Err(e) => format!("NO: {e}"),
+        };
+        let _ = writeln!(
+            table,
+            "{:<8} {:<32} {:<8} {:>8} {:>8}  {}",
+            format!("{:?}", f.kind).to_lowercase(),
+            f.name,
+            route,
+            f.compressed_bytes / 1024,
+            f.html_bytes / 1024,
+            parsed
+        );
+    }
+    table
+}
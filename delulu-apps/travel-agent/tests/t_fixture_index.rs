@@ -0,0 +1,76 @@
+//!  Delulu Travel Agent
+//!
+//!  Copyright (C) 2026  Mamy Ratsimbazafy
+//!
+//!  This program is free software: you can redistribute it and/or modify
+//!  it under the terms of the GNU Affero General Public License as published by
+//!  the Free Software Foundation, either version 3 of the License, or
+//!  (at your option) any later version.
+//!
+//!  This program is distributed in the hope that it will be useful,
+//!  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//!  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//!  GNU Affero General Public License for more details.
+//!
+//!  You should have received a copy of the GNU Affero General Public License
+//!  along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Checks the fixture index covers every committed parsing fixture and that
+//! each one still parses. Run with `--nocapture` to see the table.
+
+mod support;
+use support::{FixtureKind, route_from_name, scan_fixtures, summary_table};
+
+/// Committed fixtures and the fewest itineraries or hotels each must yield.
+const EXPECTED: &[(FixtureKind, &str, usize)] = &[
+    (FixtureKind::Flights, "domestic+business-lax_ord", 5),
+    (FixtureKind::Flights, "layover-mad_nrt", 5),
+    (FixtureKind::Flights, "layover-yyz_cdg", 5),
+    (FixtureKind::Flights, "longhaul-lax_syd", 3),
+    (FixtureKind::Flights, "nonstop-sfo_jfk_economy", 5),
+    (FixtureKind::Flights, "overnight+1day-sfo_lhr_economy", 5),
+    (FixtureKind::Hotels, "london-long-stay", 3),
+    (FixtureKind::Hotels, "no-results", 0),
+    (FixtureKind::Hotels, "nyc-families", 3),
+    (FixtureKind::Hotels, "paris-budget", 3),
+    (FixtureKind::Hotels, "tokyo-5star", 3),
+    (FixtureKind::Hotels, "tokyo-standard", 5),
+];
+
+#[test]
+fn test_route_from_name() {
+    assert_eq!(
+        route_from_name("overnight+1day-sfo_lhr_economy"),
+        Some(("SFO".to_string(), "LHR".to_string()))
+    );
+    assert_eq!(route_from_name("london-long-stay"), None);
+}
+
+#[test]
+fn test_fixture_index() {
+    let fixtures = scan_fixtures();
+    println!("{}", summary_table(&fixtures));
+
+    let found: Vec<(FixtureKind, &str)> =
+        fixtures.iter().map(|f| (f.kind, f.name.as_str())).collect();
+    let expected: Vec<(FixtureKind, &str)> = EXPECTED
+        .iter()
+        .map(|&(kind, name, _)| (kind, name))
+        .collect();
+    assert_eq!(found, expected, "fixtures on disk differ from the index");
+
+    for (fixture, &(_, _, min)) in fixtures.iter().zip(EXPECTED) {
+        assert!(fixture.compressed_bytes > 0 && fixture.html_bytes > 0);
+        let count = fixture
+            .parsed
+            .as_ref()
+            .unwrap_or_else(|e| panic!("{} no longer parses: {}", fixture.name, e));
+        assert!(
+            *count >= min,
+            "{}: expected at least {} results, got {}",
+            fixture.name,
+            min,
+            count
+        );
+    }
+}
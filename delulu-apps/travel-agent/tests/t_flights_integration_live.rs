@@ -25,6 +25,15 @@
 use anyhow::{Context, Result};
 use chrono::{Months, NaiveDate};
 use delulu_travel_agent::{FlightSearchParams, GoogleFlightsClient, Seat, Trip};
+use std::time::{Duration, Instant};
+
+mod live_queue;
+use live_queue::{LIVE_QPS, live_queue, new_live_queue};
+
+/// A client whose requests go through the shared [`live_queue`].
+fn live_client() -> Result<GoogleFlightsClient> {
+    Ok(GoogleFlightsClient::new("en".into(), "USD".into(), 5, 2)?.query_queue(live_queue()))
+}
 
 fn today() -> NaiveDate {
     chrono::Local::now().date_naive()
@@ -64,7 +73,6 @@ async fn rate_limited_query(
     to: &str,
     date: &str,
     cabin: Seat,
-    delay_secs: u64,
 ) -> Result<delulu_travel_agent::FlightSearchResult> {
     let params = delulu_travel_agent::FlightSearchParams::builder(
         from.to_uppercase(),
@@ -80,8 +88,6 @@ async fn rate_limited_query(
     println!("URL length: {} chars", url.len());
     println!("🔗 URL for manual check:\n{}", url);
 
-    tokio::time::sleep(tokio::time::Duration::from_secs(delay_secs)).await;
-
     let result = client.search_flights(&params).await?;
     println!("Parsed {} itineraries", result.itineraries.len());
     let best_price = result.itineraries.iter().filter_map(|i| i.price).min();
@@ -92,13 +98,32 @@ async fn rate_limited_query(
     Ok(result)
 }
 
+#[tokio::test]
+async fn test_live_queue_spacing() {
+    let queue = new_live_queue();
+    let mut starts = Vec::new();
+    for _ in 0..3 {
+        let start = queue
+            .with_retry(|| async { Ok(Instant::now()) })
+            .await
+            .unwrap();
+        starts.push(start);
+    }
+    // Small slack for the refill check happening on a polling tick
+    let spacing = Duration::from_secs(1) / LIVE_QPS as u32 - Duration::from_millis(50);
+    for pair in starts.windows(2) {
+        let gap = pair[1] - pair[0];
+        assert!(gap >= spacing, "requests {gap:?} apart");
+    }
+}
+
 #[tokio::test]
 #[ignore]
 async fn test_real_query_domestic_us_route() -> Result<()> {
-    let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 2)?;
+    let client = live_client()?;
     println!("=== Domestic US Route Test ===");
 
-    match rate_limited_query(&client, "SFO", "JFK", &dom_flight_date(), Seat::Economy, 0).await {
+    match rate_limited_query(&client, "SFO", "JFK", &dom_flight_date(), Seat::Economy).await {
         Ok(_) => println!("✓ Domestic query succeeded"),
         Err(e) => {
             eprintln!("✗ Domestic query failed: {}", e);
@@ -116,10 +141,10 @@ async fn test_real_query_domestic_us_route() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_real_query_international_longhaul() -> Result<()> {
-    let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 2)?;
+    let client = live_client()?;
     println!("\n=== International Long-Haul Test ===");
 
-    match rate_limited_query(&client, "SFO", "LHR", &intl_flight_date(), Seat::Economy, 1).await {
+    match rate_limited_query(&client, "SFO", "LHR", &intl_flight_date(), Seat::Economy).await {
         Ok(_) => println!("✓ International query succeeded"),
         Err(e) => {
             eprintln!("✗ International query failed: {}", e);
@@ -137,10 +162,10 @@ async fn test_real_query_international_longhaul() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_real_query_business_class() -> Result<()> {
-    let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 2)?;
+    let client = live_client()?;
     println!("\n=== Business Class Test ===");
 
-    match rate_limited_query(&client, "LAX", "ORD", &bus_flight_date(), Seat::Business, 1).await {
+    match rate_limited_query(&client, "LAX", "ORD", &bus_flight_date(), Seat::Business).await {
         Ok(_) => println!("✓ Business class query succeeded"),
         Err(e) => {
             eprintln!("✗ Business class query failed: {}", e);
@@ -158,10 +183,10 @@ async fn test_real_query_business_class() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_real_query_different_dates() -> Result<()> {
-    let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 2)?;
+    let client = live_client()?;
     println!("\n=== Different Dates Comparison Test ===");
 
-    match rate_limited_query(&client, "SFO", "JFK", &next_month(), Seat::Economy, 0).await {
+    match rate_limited_query(&client, "SFO", "JFK", &next_month(), Seat::Economy).await {
         Ok(_) => println!("✓ Off-peak query succeeded"),
         Err(e) => {
             eprintln!("✗ Off-peak query failed: {}", e);
@@ -173,7 +198,7 @@ async fn test_real_query_different_dates() -> Result<()> {
         }
     }
 
-    match rate_limited_query(&client, "SFO", "JFK", &next_semester(), Seat::Economy, 1).await {
+    match rate_limited_query(&client, "SFO", "JFK", &next_semester(), Seat::Economy).await {
         Ok(_) => println!("✓ Peak season query succeeded"),
         Err(e) => {
             eprintln!("✗ Peak season query failed: {}", e);
@@ -191,11 +216,11 @@ async fn test_real_query_different_dates() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_real_query_quick_smoke() -> Result<()> {
-    let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 2)?;
+    let client = live_client()?;
     println!("Quick test: single SFO->JFK query");
     println!("Browser: Safari 18.5, Cookies: YES+ consented");
 
-    match rate_limited_query(&client, "SFO", "JFK", &dom_flight_date(), Seat::Economy, 1).await {
+    match rate_limited_query(&client, "SFO", "JFK", &dom_flight_date(), Seat::Economy).await {
         Ok(_) => println!("✅ Quick test completed successfully"),
         Err(e) => anyhow::bail!("❌ Quick test failed: {}", e),
     }
@@ -206,7 +231,7 @@ async fn test_real_query_quick_smoke() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_real_query_overnight_plus_two_days() -> Result<()> {
-    let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 2)?;
+    let client = live_client()?;
     let date = (today() + Months::new(2)).format("%Y-%m-%d").to_string();
     println!("\n=== Overnight +2 Days Test ===");
     println!("Querying: SFO -> LHR on {} (testing +2 day arrival)", date);
@@ -222,7 +247,6 @@ async fn test_real_query_overnight_plus_two_days() -> Result<()> {
     let url = params.get_search_url();
     println!("URL: {}", url);
 
-    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
     let result = client.search_flights(&params).await?;
 
     assert!(
@@ -240,11 +264,11 @@ async fn test_real_query_overnight_plus_two_days() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_real_query_premium_economy() -> Result<()> {
-    let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 2)?;
+    let client = live_client()?;
     let date = intl_flight_date();
     println!("\n=== Premium Economy Test ===");
 
-    match rate_limited_query(&client, "LAX", "CDG", &date, Seat::PremiumEconomy, 1).await {
+    match rate_limited_query(&client, "LAX", "CDG", &date, Seat::PremiumEconomy).await {
         Ok(_) => println!("✓ Premium economy query succeeded"),
         Err(e) => {
             eprintln!("✗ Premium economy query failed: {}", e);
@@ -262,11 +286,11 @@ async fn test_real_query_premium_economy() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_real_query_first_class() -> Result<()> {
-    let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 2)?;
+    let client = live_client()?;
     let date = intl_flight_date();
     println!("\n=== First Class Test ===");
 
-    match rate_limited_query(&client, "JFK", "DXB", &date, Seat::First, 1).await {
+    match rate_limited_query(&client, "JFK", "DXB", &date, Seat::First).await {
         Ok(_) => println!("✓ First class query succeeded"),
         Err(e) => {
             eprintln!("✗ First class query failed: {}", e);
@@ -284,7 +308,7 @@ async fn test_real_query_first_class() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_real_query_oneway() -> Result<()> {
-    let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 2)?;
+    let client = live_client()?;
     let date = intl_flight_date();
     println!("\n=== One-Way Test ===");
 
@@ -301,7 +325,6 @@ async fn test_real_query_oneway() -> Result<()> {
     println!("Query: LAX → NRT on {} (OneWay)", date);
     println!("URL: {}", url);
 
-    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
     let result = client.search_flights(&params).await?;
 
     assert!(
@@ -315,11 +338,11 @@ async fn test_real_query_oneway() -> Result<()> {
 #[tokio::test]
 #[ignore]
 async fn test_real_query_response_structure() -> Result<()> {
-    let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 2)?;
+    let client = live_client()?;
     let date = dom_flight_date();
     println!("\n=== Response Structure Test ===");
 
-    let result = rate_limited_query(&client, "SFO", "LAX", &date, Seat::Economy, 5).await?;
+    let result = rate_limited_query(&client, "SFO", "LAX", &date, Seat::Economy).await?;
 
     let has_structure_markers =
         result.raw_response.contains("jsname") || result.raw_response.contains("Rk10dc");
@@ -338,9 +361,7 @@ async fn test_real_query_response_structure() -> Result<()> {
 // =============================================================================
 // These tests fetch HTML from Google Flights and save as compressed fixtures.
 // Run with: cargo test --test t_flights_integration_live fetch_fixture_xxx -- --ignored --nocapture
-// Rate limited to 3 seconds between requests to avoid being banned.
-
-const FLIGHT_FIXTURE_RATE_LIMIT_SECS: u64 = 3;
+// Rate limited by the shared live queue to avoid being banned.
 
 fn compress_and_save_flight(html: &str, name: &str) {
     use std::fs;
@@ -361,18 +382,6 @@ fn compress_and_save_flight(html: &str, name: &str) {
 }
 
 async fn rate_limited_flight_fetch(
-    client: &GoogleFlightsClient,
-    params: &FlightSearchParams,
-    delay_secs: u64,
-    name: &str,
-) -> Result<String, Box<dyn std::error::Error>> {
-    if delay_secs > 0 {
-        tokio::time::sleep(tokio::time::Duration::from_secs(delay_secs)).await;
-    }
-    fetch_single_flight_fixture(client, params, name).await
-}
-
-async fn fetch_single_flight_fixture(
     client: &GoogleFlightsClient,
     params: &FlightSearchParams,
     name: &str,
@@ -412,7 +421,7 @@ async fn fetch_single_flight_fixture(
 #[tokio::test]
 #[ignore]
 async fn fetch_fixture_sfo_jfk_nonstop() {
-    let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 2).expect("client");
+    let client = live_client().expect("client");
 
     let today = chrono::Local::now().date_naive();
     let depart = today + Months::new(2);
@@ -426,14 +435,7 @@ async fn fetch_fixture_sfo_jfk_nonstop() {
     .build()
     .expect("params should build");
 
-    match rate_limited_flight_fetch(
-        &client,
-        &params,
-        FLIGHT_FIXTURE_RATE_LIMIT_SECS,
-        "nonstop-sfo_jfk_economy",
-    )
-    .await
-    {
+    match rate_limited_flight_fetch(&client, &params, "nonstop-sfo_jfk_economy").await {
         Ok(text) => compress_and_save_flight(&text, "nonstop-sfo_jfk_economy"),
         Err(e) => panic!("Failed: {}", e),
     }
@@ -442,7 +444,7 @@ async fn fetch_fixture_sfo_jfk_nonstop() {
 #[tokio::test]
 #[ignore]
 async fn fetch_fixture_lax_ord_business() {
-    let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 2).expect("client");
+    let client = live_client().expect("client");
 
     let today = chrono::Local::now().date_naive();
     let depart = today + Months::new(2) + chrono::Duration::days(15);
@@ -456,14 +458,7 @@ async fn fetch_fixture_lax_ord_business() {
     .build()
     .expect("params should build");
 
-    match rate_limited_flight_fetch(
-        &client,
-        &params,
-        FLIGHT_FIXTURE_RATE_LIMIT_SECS,
-        "domestic+business-lax_ord",
-    )
-    .await
-    {
+    match rate_limited_flight_fetch(&client, &params, "domestic+business-lax_ord").await {
         Ok(text) => compress_and_save_flight(&text, "domestic+business-lax_ord"),
         Err(e) => panic!("Failed: {}", e),
     }
@@ -472,7 +467,7 @@ async fn fetch_fixture_lax_ord_business() {
 #[tokio::test]
 #[ignore]
 async fn fetch_fixture_sfo_lhr_overnight() {
-    let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 2).expect("client");
+    let client = live_client().expect("client");
 
     let today = chrono::Local::now().date_naive();
     let depart = today + Months::new(2);
@@ -485,14 +480,7 @@ async fn fetch_fixture_sfo_lhr_overnight() {
     .build()
     .expect("params should build");
 
-    match rate_limited_flight_fetch(
-        &client,
-        &params,
-        FLIGHT_FIXTURE_RATE_LIMIT_SECS,
-        "overnight+1day-sfo_lhr_economy",
-    )
-    .await
-    {
+    match rate_limited_flight_fetch(&client, &params, "overnight+1day-sfo_lhr_economy").await {
         Ok(text) => compress_and_save_flight(&text, "overnight+1day-sfo_lhr_economy"),
         Err(e) => panic!("Failed: {}", e),
     }
@@ -501,7 +489,7 @@ async fn fetch_fixture_sfo_lhr_overnight() {
 #[tokio::test]
 #[ignore]
 async fn fetch_fixture_lax_syd_longhaul() {
-    let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 2).expect("client");
+    let client = live_client().expect("client");
 
     let today = chrono::Local::now().date_naive();
     let depart = today + Months::new(3);
@@ -514,14 +502,7 @@ async fn fetch_fixture_lax_syd_longhaul() {
     .build()
     .expect("params should build");
 
-    match rate_limited_flight_fetch(
-        &client,
-        &params,
-        FLIGHT_FIXTURE_RATE_LIMIT_SECS,
-        "longhaul-lax_syd",
-    )
-    .await
-    {
+    match rate_limited_flight_fetch(&client, &params, "longhaul-lax_syd").await {
         Ok(text) => compress_and_save_flight(&text, "longhaul-lax_syd"),
         Err(e) => panic!("Failed: {}", e),
     }
@@ -530,7 +511,7 @@ async fn fetch_fixture_lax_syd_longhaul() {
 #[tokio::test]
 #[ignore]
 async fn fetch_fixture_mad_nrt_layover() {
-    let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 2).expect("client");
+    let client = live_client().expect("client");
 
     let today = chrono::Local::now().date_naive();
     let depart = today + Months::new(3);
@@ -543,14 +524,7 @@ async fn fetch_fixture_mad_nrt_layover() {
     .build()
     .expect("params should build");
 
-    match rate_limited_flight_fetch(
-        &client,
-        &params,
-        FLIGHT_FIXTURE_RATE_LIMIT_SECS,
-        "layover-mad_nrt",
-    )
-    .await
-    {
+    match rate_limited_flight_fetch(&client, &params, "layover-mad_nrt").await {
         Ok(text) => compress_and_save_flight(&text, "layover-mad_nrt"),
         Err(e) => panic!("Failed: {}", e),
     }
@@ -559,7 +533,7 @@ async fn fetch_fixture_mad_nrt_layover() {
 #[tokio::test]
 #[ignore]
 async fn fetch_fixture_yyz_cdg_layover() {
-    let client = GoogleFlightsClient::new("en".into(), "USD".into(), 5, 2).expect("client");
+    let client = live_client().expect("client");
 
     let today = chrono::Local::now().date_naive();
     let depart = today + Months::new(2);
@@ -573,15 +547,36 @@ async fn fetch_fixture_yyz_cdg_layover() {
     .build()
     .expect("params should build");
 
-    match rate_limited_flight_fetch(
-        &client,
-        &params,
-        FLIGHT_FIXTURE_RATE_LIMIT_SECS,
-        "layover-yyz_cdg",
-    )
-    .await
-    {
+    match rate_limited_flight_fetch(&client, &params, "layover-yyz_cdg").await {
         Ok(text) => compress_and_save_flight(&text, "layover-yyz_cdg"),
         Err(e) => panic!("Failed: {}", e),
     }
 }
+
+/// Round trip a week apart, the return-leg parser's regression target. Only
+/// the outbound page is captured: Google lists the return flights once an
+/// outbound one is picked.
+#[tokio::test]
+#[ignore]
+async fn fetch_fixture_sfo_jfk_roundtrip() {
+    let client = live_client().expect("client");
+
+    let today = chrono::Local::now().date_naive();
+    let depart = today + Months::new(2);
+
+    let params = delulu_travel_agent::FlightSearchParams::builder(
+        "SFO".to_uppercase(),
+        "JFK".to_uppercase(),
+        depart,
+    )
+    .cabin_class(Seat::Economy)
+    .trip_type(Trip::RoundTrip)
+    .return_date(depart + chrono::Duration::days(7))
+    .build()
+    .expect("params should build");
+
+    match rate_limited_flight_fetch(&client, &params, "roundtrip-sfo_jfk_economy").await {
+        Ok(text) => compress_and_save_flight(&text, "roundtrip-sfo_jfk_economy"),
+        Err(e) => panic!("Failed: {}", e),
+    }
+}
@@ -23,7 +23,7 @@
 
 use std::path::Path;
 
-use delulu_travel_agent::{FlightSearchParams, FlightSearchResult, Seat};
+use delulu_travel_agent::{FlightSearchParams, FlightSearchResult, PriceTrend, Seat, Trip};
 
 /// Fixture structure describing expected properties of parsed results.
 struct FixtureTestCase {
@@ -39,6 +39,11 @@ struct FixtureTestCase {
     from_airport: &'static str,
     /// Destination airport code for this fixture (for test params)
     to_airport: &'static str,
+    /// Price insights banner verdict captured in this fixture
+    price_trend: PriceTrend,
+    /// Searched with an explicit return date. Such a case is skipped until
+    /// its fixture is captured, see [`committed_cases`].
+    round_trip: bool,
 }
 
 /// Test cases covering different HTML structure variations.
@@ -51,6 +56,8 @@ const FIXTURE_TESTS: &[FixtureTestCase] = &[
         description: "Domestic US business class - short haul, typical domestic layout",
         from_airport: "LAX",
         to_airport: "ORD",
+        price_trend: PriceTrend::Low,
+        round_trip: false,
     },
     FixtureTestCase {
         name: "nonstop-sfo_jfk_economy",
@@ -59,6 +66,8 @@ const FIXTURE_TESTS: &[FixtureTestCase] = &[
         description: "Transcontinental economy with nonstop options visible",
         from_airport: "SFO",
         to_airport: "JFK",
+        price_trend: PriceTrend::Low,
+        round_trip: false,
     },
     FixtureTestCase {
         name: "overnight+1day-sfo_lhr_economy",
@@ -67,6 +76,8 @@ const FIXTURE_TESTS: &[FixtureTestCase] = &[
         description: "International long-haul with +1 day arrival markers",
         from_airport: "SFO",
         to_airport: "LHR",
+        price_trend: PriceTrend::Typical,
+        round_trip: false,
     },
     FixtureTestCase {
         name: "layover-mad_nrt",
@@ -75,6 +86,8 @@ const FIXTURE_TESTS: &[FixtureTestCase] = &[
         description: "Europe to Asia with multiple layovers (1-2 stops)",
         from_airport: "MAD",
         to_airport: "NRT",
+        price_trend: PriceTrend::High,
+        round_trip: false,
     },
     FixtureTestCase {
         name: "longhaul-lax_syd",
@@ -83,6 +96,8 @@ const FIXTURE_TESTS: &[FixtureTestCase] = &[
         description: "Trans-Pacific ultra-long-haul routes",
         from_airport: "LAX",
         to_airport: "SYD",
+        price_trend: PriceTrend::High,
+        round_trip: false,
     },
     FixtureTestCase {
         name: "layover-yyz_cdg",
@@ -91,9 +106,42 @@ const FIXTURE_TESTS: &[FixtureTestCase] = &[
         description: "Toronto to Paris with potential Montréal layover",
         from_airport: "YYZ",
         to_airport: "CDG",
+        price_trend: PriceTrend::Typical,
+        round_trip: false,
+    },
+    FixtureTestCase {
+        name: "roundtrip-sfo_jfk_economy",
+        min_itineraries: 5,
+        has_prices: true,
+        description: "Round trip a week apart, outbound page with round trip totals",
+        from_airport: "SFO",
+        to_airport: "JFK",
+        // Confirm against the banner once captured
+        price_trend: PriceTrend::Typical,
+        round_trip: true,
     },
 ];
 
+fn fixture_path(name: &str) -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures-flights-parsing")
+        .join(format!("{}.html.zst", name))
+}
+
+/// [`FIXTURE_TESTS`] minus the round-trip cases not captured yet, which are
+/// skipped with a note instead of failing every fixture test. Capture them
+/// with `cargo test --test t_flights_integration_live fetch_fixture_<route>_roundtrip
+/// -- --ignored` and commit the snapshot.
+fn committed_cases() -> impl Iterator<Item = &'static FixtureTestCase> {
+    FIXTURE_TESTS.iter().filter(|case| {
+        let pending = case.round_trip && !fixture_path(case.name).exists();
+        if pending {
+            println!("Skipping {}: fixture not captured yet", case.name);
+        }
+        !pending
+    })
+}
+
 /// Decompress a zstd-compressed fixture file.
 ///
 /// Panics if decompression fails, which indicates either corruption
@@ -108,8 +156,7 @@ fn decompress_zst(compressed: &[u8]) -> String {
 ///
 /// Panics if the file cannot be loaded (not found, corrupt, etc.).
 fn load_fixture(name: &str) -> String {
-    let fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures-flights-parsing");
-    let fixture_path = fixtures_dir.join(format!("{}.html.zst", name));
+    let fixture_path = fixture_path(name);
 
     let compressed = std::fs::read(&fixture_path).unwrap_or_else(|e| {
         panic!(
@@ -128,7 +175,7 @@ fn load_fixture(name: &str) -> String {
 fn test_parser_fixtures() {
     let mut results = Vec::new();
 
-    for case in FIXTURE_TESTS {
+    for case in committed_cases() {
         println!("Testing fixture: {} - {}", case.name, case.description);
 
         let html = load_fixture(case.name);
@@ -300,6 +347,46 @@ fn test_layover_mad_nrt() {
     assert!(multi_stop > 0, "Should have some 2+ stop options");
 }
 
+/// Codeshares name the operating carrier apart from the marketing one, e.g.
+/// "Etihad" sold, "Operated by Air Europa for Etihad Airways".
+#[test]
+fn test_operating_airline_mad_nrt() {
+    let html = load_fixture("layover-mad_nrt");
+    let params = FlightSearchParams::builder(
+        "MAD".into(),
+        "NRT".into(),
+        chrono::NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+    )
+    .build()
+    .unwrap();
+    let result = FlightSearchResult::from_html(&html, params).expect("parse fixture");
+
+    let codeshares: Vec<(&str, &str)> = result
+        .itineraries
+        .iter()
+        .flat_map(|it| &it.flights)
+        .filter_map(|seg| Some((seg.airline.as_deref()?, seg.operating_airline.as_deref()?)))
+        .collect();
+    assert!(
+        codeshares
+            .iter()
+            .any(|(marketing, operating)| marketing != operating),
+        "no distinct operating carrier in {codeshares:?}"
+    );
+    assert!(
+        codeshares.contains(&("Etihad", "Air Europa")),
+        "{codeshares:?}"
+    );
+
+    let response = result.to_mcp_api_response(Vec::new());
+    let results = response.search_flights.results.unwrap();
+    assert!(
+        results
+            .iter()
+            .any(|it| it.operating_airlines == ["Air Europa"] && it.airlines == ["Etihad"])
+    );
+}
+
 #[test]
 fn test_layover_doha_parsing() {
     let html = load_fixture("layover-mad_nrt");
@@ -440,3 +527,201 @@ fn test_layover_yyz_cdg() {
         }
     }
 }
+
+/// Segment airports come from the card, not the search: searching the
+/// Toronto and Paris metro codes still reports the airports actually flown.
+#[test]
+fn test_segment_airports_from_cards() {
+    let html = load_fixture("layover-yyz_cdg");
+    let params = FlightSearchParams::builder(
+        "YTO".into(),
+        "PAR".into(),
+        chrono::NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+    )
+    .build()
+    .unwrap();
+    let result = FlightSearchResult::from_html(&html, params).expect("parse fixture");
+
+    for itinerary in &result.itineraries {
+        let segment = &itinerary.flights[0];
+        assert_eq!(segment.departure_airport.as_deref(), Some("YYZ"));
+        assert_eq!(segment.arrival_airport.as_deref(), Some("CDG"));
+    }
+}
+
+#[test]
+fn test_selector_match_counts() {
+    let mut stale = Vec::new();
+
+    for case in committed_cases() {
+        let html = load_fixture(case.name);
+        println!("{}:", case.name);
+        for (selector, count) in FlightSearchResult::selector_match_counts(&html).unwrap() {
+            println!("  {:20} {}", selector, count);
+            if count == 0 {
+                stale.push(format!("{}/{}", case.name, selector));
+            }
+        }
+    }
+
+    assert!(
+        stale.is_empty(),
+        "Selectors matched nothing (fixture/selector): {:?}",
+        stale
+    );
+}
+
+#[test]
+fn test_price_trend() {
+    for case in committed_cases() {
+        let html = load_fixture(case.name);
+        let params = FlightSearchParams::builder(
+            case.from_airport.into(),
+            case.to_airport.into(),
+            chrono::NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+        )
+        .build()
+        .unwrap();
+        let result = FlightSearchResult::from_html(&html, params).expect("parse fixture");
+
+        assert_eq!(
+            result.price_trend,
+            Some(case.price_trend),
+            "{}: price trend mismatch",
+            case.name
+        );
+        assert_eq!(
+            result
+                .to_mcp_api_response(Vec::new())
+                .search_flights
+                .price_trend,
+            Some(case.price_trend),
+            "{}: price trend missing from MCP response",
+            case.name
+        );
+    }
+}
+
+/// The outbound page of a round trip parses, with a round-trip total on
+/// every itinerary.
+#[test]
+fn test_round_trip_outbound_page() {
+    let name = "roundtrip-sfo_jfk_economy";
+    if !fixture_path(name).exists() {
+        println!("Skipping {name}: run fetch_fixture_sfo_jfk_roundtrip to capture it");
+        return;
+    }
+    let depart = chrono::NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    let params = FlightSearchParams::builder("SFO".into(), "JFK".into(), depart)
+        .trip_type(Trip::RoundTrip)
+        .return_date(depart + chrono::Days::new(7))
+        .build()
+        .unwrap();
+    let result = FlightSearchResult::from_html(&load_fixture(name), params).expect("parse fixture");
+
+    assert!(
+        result.itineraries.len() >= 5,
+        "{}",
+        result.itineraries.len()
+    );
+    for itinerary in &result.itineraries {
+        assert!(itinerary.price.is_some(), "{}: no total", itinerary.id);
+    }
+}
+
+/// Parse output for every fixture must match the snapshot recorded next to
+/// it (`<name>.expected.json.zst`), so parser optimizations cannot silently
+/// change results.
+///
+/// After an intentional change to the output, rerun with
+/// `UPDATE_PARSE_SNAPSHOTS=1` to rewrite the snapshots, and review the diff.
+#[test]
+fn test_parse_output_matches_snapshots() {
+    let fixtures_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures-flights-parsing");
+    let update = std::env::var_os("UPDATE_PARSE_SNAPSHOTS").is_some();
+
+    for case in committed_cases() {
+        let html = load_fixture(case.name);
+        let params = FlightSearchParams::builder(
+            case.from_airport.into(),
+            case.to_airport.into(),
+            chrono::NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+        )
+        .build()
+        .unwrap();
+        let result = FlightSearchResult::from_html_best_effort(&html, params).expect("parse");
+        let actual = serde_json::json!({
+            "itineraries": result.itineraries,
+            "warnings": result.warnings,
+            "price_trend": result.price_trend,
+        });
+
+        let snapshot_path = fixtures_dir.join(format!("{}.expected.json.zst", case.name));
+        if update {
+            let json = serde_json::to_vec_pretty(&actual).unwrap();
+            let compressed = zstd::stream::encode_all(json.as_slice(), 19).unwrap();
+            std::fs::write(&snapshot_path, compressed).unwrap();
+            continue;
+        }
+
+        let compressed = std::fs::read(&snapshot_path)
+            .unwrap_or_else(|e| panic!("{}: missing snapshot: {}", case.name, e));
+        let expected: serde_json::Value = serde_json::from_str(&decompress_zst(&compressed))
+            .unwrap_or_else(|e| panic!("{}: invalid snapshot: {}", case.name, e));
+        assert_eq!(
+            actual, expected,
+            "{}: parse output differs from snapshot",
+            case.name
+        );
+    }
+}
+
+/// The `query` block echoes the search with every field the schema requires,
+/// and its `search_url` carries the searched route in a decodable `tfs`.
+#[test]
+fn test_mcp_query_matches_schema() {
+    let schema_path =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("src/schemas/flights-response.json");
+    let schema: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(schema_path).unwrap()).unwrap();
+    let query_schema = &schema["properties"]["search_flights"]["properties"]["query"];
+    let validator = jsonschema::Validator::new(query_schema).expect("valid query schema");
+
+    let params = FlightSearchParams::builder(
+        "SFO".into(),
+        "JFK".into(),
+        chrono::NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+    )
+    .cabin_class(Seat::Business)
+    .build()
+    .unwrap();
+    let mut result =
+        FlightSearchResult::from_html(&load_fixture("nonstop-sfo_jfk_economy"), params)
+            .expect("parse fixture");
+    // Filters leaving nothing must not degrade the echo.
+    result.filter_max_duration(0);
+
+    let query =
+        serde_json::to_value(result.to_mcp_api_response(Vec::new()).search_flights.query).unwrap();
+    let errors: Vec<String> = validator
+        .iter_errors(&query)
+        .map(|e| e.to_string())
+        .collect();
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(query["from"], "SFO");
+    assert_eq!(query["to"], "JFK");
+    assert_eq!(query["date"], "2024-01-15");
+    assert_eq!(query["curr"], "USD");
+    assert_eq!(query["seat"], "business");
+
+    let search_url = query["search_url"].as_str().unwrap();
+    let tfs = search_url
+        .split(['?', '&'])
+        .find_map(|kv| kv.strip_prefix("tfs="))
+        .expect("search_url has a tfs parameter");
+    let decoded = FlightSearchParams::from_tfs(tfs).expect("decodable tfs");
+    assert_eq!(decoded.from_airport, "SFO");
+    assert_eq!(decoded.to_airport, "JFK");
+    assert_eq!(decoded.depart_date, "2024-01-15");
+    assert_eq!(decoded.cabin_class, Seat::Business);
+}
@@ -155,8 +155,11 @@ fn test_validate_decoder_with_ui() {
                     ));
                 }
 
-                let expected_stars: Vec<i32> = case.input.hotel_stars.clone().unwrap_or_default();
-                let expected_amenities: Vec<i32> = case
+                // from_ts returns stars and amenities in ascending order.
+                let mut expected_stars: Vec<i32> =
+                    case.input.hotel_stars.clone().unwrap_or_default();
+                expected_stars.sort();
+                let mut expected_amenities: Vec<i32> = case
                     .input
                     .amenities
                     .as_deref()
@@ -166,6 +169,7 @@ fn test_validate_decoder_with_ui() {
                         delulu_travel_agent::Amenity::from_str_name(a).map(|a| a as i32)
                     })
                     .collect();
+                expected_amenities.sort();
                 let expected_guest_rating = case.input.min_guest_rating;
 
                 let expected_sort: Option<SortType> = match case.input.sort_by.as_deref() {
@@ -186,18 +190,12 @@ fn test_validate_decoder_with_ui() {
                 if expected_stars.is_empty() && !actual_stars.is_empty() {
                     failures.push(format!("stars: expected none, got {:?}", actual_stars));
                     star_mismatch = true;
-                } else if !expected_stars.is_empty() {
-                    let mut exp_sorted = expected_stars.clone();
-                    let mut act_sorted = actual_stars.clone();
-                    exp_sorted.sort();
-                    act_sorted.sort();
-                    if exp_sorted != act_sorted {
-                        failures.push(format!(
-                            "stars: expected {:?}, got {:?}",
-                            exp_sorted, act_sorted
-                        ));
-                        star_mismatch = true;
-                    }
+                } else if !expected_stars.is_empty() && expected_stars != actual_stars {
+                    failures.push(format!(
+                        "stars: expected {:?}, got {:?}",
+                        expected_stars, actual_stars
+                    ));
+                    star_mismatch = true;
                 }
 
                 let mut amenity_mismatch = false;
@@ -207,18 +205,12 @@ fn test_validate_decoder_with_ui() {
                         actual_amenities
                     ));
                     amenity_mismatch = true;
-                } else if !expected_amenities.is_empty() {
-                    let mut exp_sorted = expected_amenities.clone();
-                    let mut act_sorted = actual_amenities.clone();
-                    exp_sorted.sort();
-                    act_sorted.sort();
-                    if exp_sorted != act_sorted {
-                        failures.push(format!(
-                            "amenities: expected {:?}, got {:?}",
-                            exp_sorted, act_sorted
-                        ));
-                        amenity_mismatch = true;
-                    }
+                } else if !expected_amenities.is_empty() && expected_amenities != actual_amenities {
+                    failures.push(format!(
+                        "amenities: expected {:?}, got {:?}",
+                        expected_amenities, actual_amenities
+                    ));
+                    amenity_mismatch = true;
                 }
 
                 if expected_guest_rating.is_none() && actual_guest_rating.is_some() {
@@ -26,7 +26,7 @@
 //!   5. Parse HTML response
 //!   6. Extract hotel listings
 //!
-//! Rate limited to 1 request/second between queries.
+//! Rate limited to 1 request/second by the shared live queue.
 //!
 //! ============================================================================
 //! CI SAFETY: All live HTTP tests are IGNORED by default
@@ -40,7 +40,9 @@
 use anyhow::{Context, Result};
 use chrono::{Local, Months, NaiveDate};
 use delulu_travel_agent::{Amenity, GoogleHotelsClient, HotelSearchParams};
-use tokio::time::sleep;
+
+mod live_queue;
+use live_queue::live_queue;
 
 fn today() -> NaiveDate {
     Local::now().date_naive()
@@ -62,19 +64,6 @@ async fn rate_limited_query(
     checkin: &str,
     checkout: &str,
     adults: u32,
-    delay_secs: u64,
-) -> Result<(), Box<dyn std::error::Error>> {
-    if delay_secs > 0 {
-        sleep(std::time::Duration::from_secs(delay_secs)).await;
-    }
-    execute_query(location, checkin, checkout, adults).await
-}
-
-async fn execute_query(
-    location: &str,
-    checkin: &str,
-    checkout: &str,
-    adults: u32,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let checkin_date = parse_date(checkin)?;
     let checkout_date = parse_date(checkout)?;
@@ -92,7 +81,7 @@ async fn execute_query(
     println!("\n🏨 Query: {} ({} adults)", location, adults);
     println!("Dates: {} to {}", checkin, checkout);
 
-    let client = GoogleHotelsClient::new(4, 2)?;
+    let client = GoogleHotelsClient::new(4, 2)?.query_queue(live_queue());
     let result = client.search_hotels(&request).await?;
     let status = if !result.hotels.is_empty() {
         "VALID"
@@ -214,7 +203,6 @@ async fn test_real_query_tokyo() {
         &checkin.format("%Y-%m-%d").to_string(),
         &checkout.format("%Y-%m-%d").to_string(),
         2,
-        0,
     )
     .await
     {
@@ -243,7 +231,6 @@ async fn test_real_query_paris() {
         &checkin.format("%Y-%m-%d").to_string(),
         &checkout.format("%Y-%m-%d").to_string(),
         2,
-        1,
     )
     .await
     {
@@ -272,7 +259,6 @@ async fn test_real_query_iata_code() {
         &checkin.format("%Y-%m-%d").to_string(),
         &checkout.format("%Y-%m-%d").to_string(),
         2,
-        1,
     )
     .await
     {
@@ -301,7 +287,6 @@ async fn test_real_query_seasonal_variation() {
         &earlier_chk,
         &earlier_out.format("%Y-%m-%d").to_string(),
         2,
-        0,
     )
     .await
     {
@@ -324,7 +309,6 @@ async fn test_real_query_seasonal_variation() {
         &later_chk,
         &later_out.format("%Y-%m-%d").to_string(),
         2,
-        1,
     )
     .await
     {
@@ -353,7 +337,6 @@ async fn test_real_query_single_traveler() {
         &checkin.format("%Y-%m-%d").to_string(),
         &checkout.format("%Y-%m-%d").to_string(),
         1,
-        1,
     )
     .await
     {
@@ -383,7 +366,6 @@ async fn run_quick_smoke_test() {
         &checkin.format("%Y-%m-%d").to_string(),
         &checkout.format("%Y-%m-%d").to_string(),
         2,
-        1,
     )
     .await
     {
@@ -400,9 +382,7 @@ async fn run_quick_smoke_test() {
 // =============================================================================
 // These tests fetch HTML from Google and save as compressed fixtures.
 // Run with: cargo test --test t_hotels_integration_live fetch_fixture_xxx -- --ignored --nocapture
-// Rate limited to 2 seconds between requests to avoid being banned.
-
-const FIXTURE_RATE_LIMIT_SECS: u64 = 2;
+// Rate limited by the shared live queue to avoid being banned.
 
 fn compress_and_save(html: &str, name: &str) {
     use std::fs;
@@ -422,23 +402,19 @@ fn compress_and_save(html: &str, name: &str) {
     println!("Saved fixture: {:?}", output_path);
 }
 
+/// [`fetch_single_fixture`] through the shared queue, which spaces the
+/// requests and retries failures with its backoff.
 async fn rate_limited_fetch(
     client: &wreq::Client,
     url: &str,
-    delay_secs: u64,
     name: &str,
 ) -> Result<String, Box<dyn std::error::Error>> {
-    if delay_secs > 0 {
-        sleep(std::time::Duration::from_secs(delay_secs)).await;
-    }
-    fetch_single_fixture(client, url, name).await
+    Ok(live_queue()
+        .with_retry(|| fetch_single_fixture(client, url, name))
+        .await?)
 }
 
-async fn fetch_single_fixture(
-    client: &wreq::Client,
-    url: &str,
-    name: &str,
-) -> Result<String, Box<dyn std::error::Error>> {
+async fn fetch_single_fixture(client: &wreq::Client, url: &str, name: &str) -> Result<String> {
     let url_display = &url[0..url.len().min(100)];
     println!("Fetching '{}': {}", name, url_display);
 
@@ -449,15 +425,15 @@ async fn fetch_single_fixture(
         .map_err(|e| anyhow::anyhow!(e))?;
     let status = resp.status();
     if !status.is_success() {
-        return Err(anyhow::anyhow!("HTTP {}: {}", status, url_display).into());
+        anyhow::bail!("HTTP {}: {}", status, url_display);
     }
     let text = resp.text().await.map_err(|e| anyhow::anyhow!(e))?;
 
     if text.to_lowercase().contains("consent") {
-        return Err(anyhow::anyhow!("Blocked by consent cookie").into());
+        anyhow::bail!("Blocked by consent cookie");
     }
     if text.len() < 1000 {
-        return Err(anyhow::anyhow!("Response too short ({} bytes)", text.len()).into());
+        anyhow::bail!("Response too short ({} bytes)", text.len());
     }
 
     Ok(text)
@@ -483,7 +459,7 @@ async fn fetch_fixture_tokyo_standard() {
     let ts = params.generate_ts().expect("encode ts");
     let url = format!("https://www.google.com/travel/search?q=Tokyo&ts={}", ts);
 
-    match rate_limited_fetch(&client, &url, FIXTURE_RATE_LIMIT_SECS, "tokyo-standard").await {
+    match rate_limited_fetch(&client, &url, "tokyo-standard").await {
         Ok(text) => compress_and_save(&text, "tokyo-standard"),
         Err(e) => panic!("Failed: {}", e),
     }
@@ -512,7 +488,7 @@ async fn fetch_fixture_paris_budget() {
     let ts = params.generate_ts().expect("encode ts");
     let url = format!("https://www.google.com/travel/search?q=Paris&ts={}", ts);
 
-    match rate_limited_fetch(&client, &url, FIXTURE_RATE_LIMIT_SECS, "paris-budget").await {
+    match rate_limited_fetch(&client, &url, "paris-budget").await {
         Ok(text) => compress_and_save(&text, "paris-budget"),
         Err(e) => panic!("Failed: {}", e),
     }
@@ -540,7 +516,7 @@ async fn fetch_fixture_tokyo_5star() {
     let ts = params.generate_ts().expect("encode ts");
     let url = format!("https://www.google.com/travel/search?q=Tokyo&ts={}", ts);
 
-    match rate_limited_fetch(&client, &url, FIXTURE_RATE_LIMIT_SECS, "tokyo-5star").await {
+    match rate_limited_fetch(&client, &url, "tokyo-5star").await {
         Ok(text) => compress_and_save(&text, "tokyo-5star"),
         Err(e) => panic!("Failed: {}", e),
     }
@@ -570,7 +546,7 @@ async fn fetch_fixture_nyc_families() {
     let ts = params.generate_ts().expect("encode ts");
     let url = format!("https://www.google.com/travel/search?q=New+York&ts={}", ts);
 
-    match rate_limited_fetch(&client, &url, FIXTURE_RATE_LIMIT_SECS, "nyc-families").await {
+    match rate_limited_fetch(&client, &url, "nyc-families").await {
         Ok(text) => compress_and_save(&text, "nyc-families"),
         Err(e) => panic!("Failed: {}", e),
     }
@@ -599,7 +575,7 @@ async fn fetch_fixture_london_long_stay() {
     let ts = params.generate_ts().expect("encode ts");
     let url = format!("https://www.google.com/travel/search?q=London&ts={}", ts);
 
-    match rate_limited_fetch(&client, &url, FIXTURE_RATE_LIMIT_SECS, "london-long-stay").await {
+    match rate_limited_fetch(&client, &url, "london-long-stay").await {
         Ok(text) => compress_and_save(&text, "london-long-stay"),
         Err(e) => panic!("Failed: {}", e),
     }
@@ -17,7 +17,7 @@
 
 use std::path::Path;
 
-use delulu_travel_agent::HotelSearchResult;
+use delulu_travel_agent::{Amenity, Hotel, HotelSearchResult};
 
 fn decompress_zst(compressed: &[u8]) -> String {
     let decoder = zstd::stream::Decoder::new(compressed).expect("create zstd decoder");
@@ -133,3 +133,188 @@ fn test_parse_london_long_stay() {
         result.hotels.len()
     );
 }
+
+/// `no-results` is `paris-budget` with every hotel card removed: a results
+/// page for a search nothing matches.
+#[test]
+fn test_parse_no_results() {
+    let html = load_fixture("no-results");
+    let result = HotelSearchResult::from_html(&html).expect("empty results are not an error");
+    assert!(result.hotels.is_empty());
+}
+
+#[test]
+fn test_selector_match_counts() {
+    // Some selectors target optional content (e.g. location_rating is absent from
+    // paris-budget), so a selector is only stale if it matches in no fixture at all.
+    let mut totals: Vec<(&'static str, usize)> = Vec::new();
+
+    for name in [
+        "paris-budget",
+        "tokyo-standard",
+        "tokyo-5star",
+        "nyc-families",
+        "london-long-stay",
+    ] {
+        let html = load_fixture(name);
+        println!("{}:", name);
+        let counts = HotelSearchResult::selector_match_counts(&html).unwrap();
+        for &(selector, count) in &counts {
+            println!("  {:20} {}", selector, count);
+        }
+        if totals.is_empty() {
+            totals = counts;
+        } else {
+            for (total, (_, count)) in totals.iter_mut().zip(counts) {
+                total.1 += count;
+            }
+        }
+    }
+
+    let stale: Vec<_> = totals
+        .iter()
+        .filter(|(_, count)| *count == 0)
+        .map(|(selector, _)| *selector)
+        .collect();
+    assert!(
+        stale.is_empty(),
+        "Selectors matched nothing in any fixture: {:?}",
+        stale
+    );
+}
+
+#[test]
+fn test_amenity_chips() {
+    let html = load_fixture("tokyo-5star");
+    let result = HotelSearchResult::from_html(&html).expect("parse fixture");
+
+    assert!(
+        result.hotels.iter().any(|h| !h.amenities.is_empty()),
+        "Expected at least one hotel with amenities"
+    );
+
+    // Known chips are normalized to the Amenity filter names...
+    assert!(
+        result
+            .hotels
+            .iter()
+            .any(|h| h.known_amenities().contains(&Amenity::IndoorPool)),
+        "Expected an 'Indoor pool' chip normalized to Amenity::IndoorPool"
+    );
+    assert!(
+        result
+            .hotels
+            .iter()
+            .flat_map(|h| &h.amenities)
+            .all(|a| a != "Indoor pool" && a != "Kid-friendly")
+    );
+
+    // ...while unknown chips are kept as Google labels
+    assert!(
+        result
+            .hotels
+            .iter()
+            .any(|h| h.amenities.iter().any(|a| a == "Free Wi-Fi"))
+    );
+}
+
+#[test]
+fn test_require_chips() {
+    let mut result =
+        HotelSearchResult::from_html(&load_fixture("nyc-families")).expect("parse fixture");
+    let total = result.hotels.len();
+
+    result.require_chips(&["Pet-friendly".to_string()]);
+    assert!(!result.hotels.is_empty());
+    assert!(result.hotels.len() < total);
+    assert!(
+        result
+            .hotels
+            .iter()
+            .all(|h| h.amenities.iter().any(|a| a == "Pet-friendly"))
+    );
+}
+
+#[test]
+fn test_taxes_annotation() {
+    // Cards carry a "2 nights with taxes + fees" tooltip next to the price.
+    let result =
+        HotelSearchResult::from_html(&load_fixture("paris-budget")).expect("parse fixture");
+    assert!(!result.hotels.is_empty());
+    for hotel in &result.hotels {
+        assert_eq!(hotel.taxes_included, Some(true), "{}", hotel.name);
+    }
+}
+
+#[test]
+fn test_distance_from_search_center() {
+    // Distances come from the embedded map data: every card has a record with
+    // its coordinates, and the viewport of the searched place gives the center.
+    let mut result =
+        HotelSearchResult::from_html(&load_fixture("paris-budget")).expect("parse fixture");
+    assert!(result.hotels.iter().all(|h| h.distance_km.is_some()));
+    let distance = |result: &HotelSearchResult, name: &str| {
+        result
+            .hotels
+            .iter()
+            .find(|h| h.name == name)
+            .and_then(|h| h.distance_km)
+    };
+    assert_eq!(distance(&result, "Au Royal Mad"), Some(0.9));
+    assert_eq!(
+        distance(&result, "hotelF1 Paris Porte de Châtillon"),
+        Some(6.7)
+    );
+
+    let total = result.hotels.len();
+    result.within_km(3.0);
+    assert!(!result.hotels.is_empty());
+    assert!(result.hotels.len() < total);
+    assert!(result.hotels.iter().all(|h| h.distance_km.unwrap() <= 3.0));
+    assert!(distance(&result, "hotelF1 Paris Porte de Châtillon").is_none());
+}
+
+/// `Hotel` round-trips through JSON for caching; what `search_hotels`
+/// actually sends is the MCP response built from it, which must match the
+/// committed hotels response schema.
+#[test]
+fn test_hotel_serde_and_mcp_response_schema() {
+    let schema_path =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("src/schemas/hotels-response.json");
+    let schema: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(schema_path).unwrap()).unwrap();
+    let validator = jsonschema::Validator::new(&schema).expect("valid hotels schema");
+
+    let result = HotelSearchResult::from_html(&load_fixture("tokyo-5star")).expect("parse fixture");
+    for hotel in &result.hotels {
+        let json = serde_json::to_value(hotel).unwrap();
+        let decoded: Hotel = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&decoded).unwrap(), json);
+    }
+
+    let cached = serde_json::to_string(&result).unwrap();
+    let restored: HotelSearchResult = serde_json::from_str(&cached).unwrap();
+    assert_eq!(restored.hotels.len(), result.hotels.len());
+
+    let response = serde_json::to_value(result.to_mcp_api_response(
+        "Tokyo".to_string(),
+        "2025-06-01".to_string(),
+        "2025-06-05".to_string(),
+        "USD".to_string(),
+        "https://www.google.com/travel/search".to_string(),
+        Vec::new(),
+    ))
+    .unwrap();
+    let errors: Vec<String> = validator
+        .iter_errors(&response)
+        .map(|e| e.to_string())
+        .collect();
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(
+        response["search_hotels"]["results"]
+            .as_array()
+            .unwrap()
+            .len(),
+        result.hotels.len()
+    );
+}
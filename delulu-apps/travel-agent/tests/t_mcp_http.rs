@@ -35,7 +35,7 @@ use tracing_subscriber;
 use tracing_subscriber::EnvFilter;
 
 mod mcp_helpers;
-use mcp_helpers::{find_binary, stream_stderr_to_console};
+use mcp_helpers::{find_binary, parse_sse_response, stream_stderr_to_console};
 
 // MCP http never quits so seems like we need rely on timeout
 // if we want to read stdout AND stderr since we can't send it a kill signal.
@@ -254,104 +254,6 @@ async fn mcp_http_send_notification(
     Ok(())
 }
 
-fn parse_chunked_http_sse(body: &str) -> Result<String> {
-    let second_response_start = body
-        .find("\r\n\r\nHTTP/1.1 2")
-        .ok_or_else(|| anyhow::anyhow!("No second HTTP response found"))?;
-
-    let second_response = &body[second_response_start + 4..];
-
-    let body_start = second_response
-        .find("\r\n\r\n")
-        .map(|p| &second_response[p + 4..])
-        .ok_or_else(|| anyhow::anyhow!("No HTTP body in second response"))?;
-
-    let body_len = body_start.len();
-    debug!("body_start length: {}", body_len);
-
-    let mut current_event = String::new();
-    let mut pos = 0;
-    let mut iterations = 0;
-
-    while pos < body_len {
-        iterations += 1;
-        let line_end_crlf = body_start[pos..].find("\r\n");
-        let line_end = match line_end_crlf {
-            Some(i) => pos + i,
-            None => {
-                debug!("No CRLF at pos {}", pos);
-                break;
-            }
-        };
-
-        let line = &body_start[pos..line_end];
-        debug!(
-            "Iter {}: pos={}, line='{}' (len={})",
-            iterations,
-            pos,
-            line.escape_debug(),
-            line.len()
-        );
-
-        if let Ok(chunk_size) = usize::from_str_radix(line, 16) {
-            debug!("  -> hex chunk size {} at pos {}", chunk_size, pos);
-            if chunk_size == 0 {
-                debug!("  -> chunk size 0, breaking");
-                break;
-            }
-            let data_start = line_end + 2;
-            let data_end = data_start + chunk_size;
-            debug!(
-                "  -> data_start={}, data_end={}, chunk_size={}",
-                data_start, data_end, chunk_size
-            );
-            if data_end <= body_len {
-                let data = &body_start[data_start..data_end];
-                debug!(
-                    "  -> read {} bytes: '{}'...",
-                    data.len(),
-                    &data[..data.len().min(50)]
-                );
-                current_event.push_str(data);
-            } else {
-                debug!("  -> data_end {} > body_len {}", data_end, body_len);
-            }
-            pos = data_end + 2;
-            continue;
-        }
-
-        pos = line_end + 2;
-    }
-
-    debug!(
-        "Finished parsing: current_event.len()={}",
-        current_event.len()
-    );
-    debug!(
-        "current_event preview: '{}'",
-        &current_event[..current_event.len().min(200)]
-    );
-
-    let sse_events: Vec<&str> = current_event.split("\n\n").collect();
-    debug!("SSE events: {}", sse_events.len());
-
-    let json_event = sse_events
-        .iter()
-        .find(|e| e.contains("{\"jsonrpc"))
-        .ok_or_else(|| anyhow::anyhow!("No JSON event found in SSE response"))?;
-
-    debug!(
-        "Found JSON event: '{}'...",
-        &json_event[..json_event.len().min(100)]
-    );
-
-    if let Some(data_line) = json_event.lines().find(|l| l.starts_with("data: ")) {
-        return Ok(data_line[6..].to_string());
-    }
-
-    anyhow::bail!("No data: line found in SSE event");
-}
-
 #[tokio::test]
 async fn test_mcp_help_output() -> Result<()> {
     init_tracing();
@@ -505,18 +407,13 @@ async fn test_mcp_flights_http() -> Result<()> {
         .await
         .context("Failed to send tool call")?;
 
-    let sse_data = parse_chunked_http_sse(&response_body).context("Failed to parse SSE data")?;
-
-    debug!(
-        "SSE data ({} bytes): {:?}",
-        sse_data.len(),
-        &sse_data[..sse_data.len().min(500)]
-    );
+    let messages = parse_sse_response(&response_body).context("Failed to parse SSE response")?;
+    debug!("SSE messages: {:?}", messages);
 
-    let response: Value = serde_json::from_str(&sse_data).context(format!(
-        "Failed to parse JSON response: {}",
-        &sse_data[..sse_data.len().min(200)]
-    ))?;
+    let response = messages
+        .into_iter()
+        .find(|message| message["id"] == 2)
+        .context("No response to the tool call in the SSE stream")?;
 
     assert!(response.is_object(), "Response should be an object");
     let obj = response.as_object().unwrap();
@@ -751,18 +648,13 @@ async fn test_mcp_hotels_http() -> Result<()> {
         &response_body[..response_body.len().min(500)]
     );
 
-    let sse_data = parse_chunked_http_sse(&response_body).context("Failed to parse SSE data")?;
+    let messages = parse_sse_response(&response_body).context("Failed to parse SSE response")?;
+    debug!("SSE messages: {:?}", messages);
 
-    debug!(
-        "SSE data ({} bytes): {:?}",
-        sse_data.len(),
-        &sse_data[..sse_data.len().min(500)]
-    );
-
-    let response: Value = serde_json::from_str(&sse_data).context(format!(
-        "Failed to parse JSON response: {}",
-        &sse_data[..sse_data.len().min(200)]
-    ))?;
+    let response = messages
+        .into_iter()
+        .find(|message| message["id"] == 2)
+        .context("No response to the tool call in the SSE stream")?;
 
     assert!(response.is_object(), "Response should be an object");
     let obj = response.as_object().unwrap();
@@ -901,3 +793,88 @@ async fn test_mcp_hotels_http() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_mcp_http_request_id_in_logs() -> Result<()> {
+    init_tracing();
+    let path = find_binary()?;
+    let port = get_free_port();
+
+    let mut child = Command::new(&path)
+        .arg("http")
+        .arg("--port")
+        .arg(port.to_string())
+        .env("RUST_LOG", "info")
+        .env("NO_COLOR", "1")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let mut stderr = child.stderr.take().unwrap();
+    let stderr_task = tokio::spawn(async move {
+        let mut logs = String::new();
+        let _ = stderr.read_to_string(&mut logs).await;
+        logs
+    });
+
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    // A past departure date fails inside the flights client before any network
+    // access, which is enough to exercise the full tool call span.
+    async fn call_with_id(port: u16, request_id: u64) -> Result<String> {
+        let mut stream = TcpStream::connect(format!("127.0.0.1:{}", port))
+            .await
+            .context("Failed to connect")?;
+        let session_id = mcp_http_initialize(&mut stream, port)
+            .await
+            .context("Initialize failed")?;
+        let initialized_notification = r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#;
+        mcp_http_send_notification(&mut stream, &session_id, initialized_notification).await?;
+
+        let call_request = json!({
+            "jsonrpc": "2.0",
+            "id": request_id,
+            "method": "tools/call",
+            "params": {
+                "name": "search_flights",
+                "arguments": {"from": "SFO", "to": "JFK", "date": "2020-01-15", "adults": 1}
+            }
+        })
+        .to_string();
+        mcp_http_send(&mut stream, &session_id, &call_request).await
+    }
+
+    let (first, second) = tokio::join!(call_with_id(port, 101), call_with_id(port, 202));
+    first.context("First tool call failed")?;
+    second.context("Second tool call failed")?;
+
+    let _ = child.kill().await;
+    let _ = child.wait().await;
+    let logs = stderr_task.await?;
+    debug!("Server logs:\n{}", logs);
+
+    for request_id in [101, 202] {
+        let tag = format!("request_id={}", request_id);
+        assert!(
+            logs.lines()
+                .any(|l| l.contains(&tag) && l.contains("Tool call started")),
+            "Missing tool call start for {}",
+            tag
+        );
+        assert!(
+            logs.lines()
+                .any(|l| l.contains(&tag) && l.contains("flights_client")),
+            "Client logs should be correlated with {}",
+            tag
+        );
+    }
+    assert!(
+        !logs
+            .lines()
+            .any(|l| l.contains("request_id=101") && l.contains("request_id=202")),
+        "Concurrent calls must not share a span"
+    );
+
+    Ok(())
+}
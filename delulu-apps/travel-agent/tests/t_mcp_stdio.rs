@@ -22,7 +22,7 @@
 
 use anyhow::{Context, Result};
 use chrono::{Months, NaiveDate};
-use delulu_travel_agent::{FlightSearchParams, HotelSearchParams};
+use delulu_travel_agent::{FlightSearchParams, HotelSearchParams, Trip};
 use serde_json::Value;
 use serde_json::json;
 use std::path::PathBuf;
@@ -237,7 +237,7 @@ async fn test_mcp_server_starts_stdio() -> Result<()> {
     let path = find_binary()?;
 
     let mut child = Command::new(&path)
-        .arg("stdio")
+        .args(["stdio", "--strict-schema"])
         .stdout(Stdio::piped())
         .stdin(Stdio::piped())
         .stderr(Stdio::piped())
@@ -298,7 +298,7 @@ async fn test_mcp_flights_stdio() -> Result<()> {
     let path = find_binary()?;
 
     let mut child = Command::new(&path)
-        .arg("stdio")
+        .args(["stdio", "--strict-schema"])
         .stdout(Stdio::piped())
         .stdin(Stdio::piped())
         .stderr(Stdio::piped())
@@ -484,6 +484,102 @@ async fn test_mcp_flights_stdio() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+#[ignore]
+async fn test_mcp_flights_multi_city_stdio() -> Result<()> {
+    init_tracing();
+    let path = find_binary()?;
+
+    let mut child = Command::new(&path)
+        .args(["stdio", "--strict-schema"])
+        .stdout(Stdio::piped())
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let mut stdout = child.stdout.take().unwrap();
+    let stderr = child.stderr.take().unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+
+    mcp_initialize(&mut stdin, &mut stdout)
+        .await
+        .context("MCP initialize failed")?;
+
+    let first = today() + Months::new(2);
+    let dates: Vec<String> = [0, 4, 9]
+        .iter()
+        .map(|days| {
+            (first + chrono::Days::new(*days))
+                .format("%Y-%m-%d")
+                .to_string()
+        })
+        .collect();
+    let legs = [("SFO", "JFK"), ("JFK", "LHR"), ("LHR", "SFO")];
+    let args = json!({
+        // Ignored in favor of the legs
+        "from": "AAA",
+        "to": "BBB",
+        "date": dates[0],
+        "adults": 1,
+        "legs": legs
+            .iter()
+            .zip(&dates)
+            .map(|((from, to), date)| json!({"from": from, "to": to, "date": date}))
+            .collect::<Vec<_>>(),
+    });
+
+    send_tool_call(&mut stdin, "search_flights", args)
+        .await
+        .context("Failed to send flight search tool call")?;
+
+    let response = read_json_response_with_timeout(&mut stdout, TIMEOUT)
+        .await
+        .context("Failed to read flight search response")?;
+
+    drop(stdin);
+    let stderr_task = tokio::spawn(stream_stderr_to_console(stderr));
+    let _ = stderr_task.await;
+    drop(child);
+
+    if let Some(error) = response.get("error") {
+        anyhow::bail!("API error: {}", error);
+    }
+    let text = response["result"]["content"][0]["text"]
+        .as_str()
+        .context("Response should have text content")?;
+    let inner: Value = serde_json::from_str(text).context("Failed to parse inner flight JSON")?;
+    validate_json_schema(&inner, &get_flights_response_schema()?, "flights_response")?;
+
+    let search_url = inner["search_flights"]["query"]["search_url"]
+        .as_str()
+        .context("search_url should be present")?;
+    let decoded = FlightSearchParams::from_url(search_url)
+        .context("Failed to decode the search URL")?
+        .params;
+
+    assert_eq!(decoded.trip_type, Trip::MultiCity);
+    let mut decoded_legs = vec![(
+        decoded.from_airport.clone(),
+        decoded.to_airport.clone(),
+        decoded.depart_date.clone(),
+    )];
+    decoded_legs.extend(
+        decoded
+            .extra_legs
+            .iter()
+            .map(|l| (l.from_airport.clone(), l.to_airport.clone(), l.date.clone())),
+    );
+    let expected: Vec<_> = legs
+        .iter()
+        .zip(&dates)
+        .map(|((from, to), date)| (from.to_string(), to.to_string(), date.clone()))
+        .collect();
+    assert_eq!(decoded_legs, expected, "every leg should be in the TFS");
+
+    Ok(())
+}
+
 #[tokio::test]
 #[ignore]
 async fn test_mcp_hotels_stdio() -> Result<()> {
@@ -491,7 +587,7 @@ async fn test_mcp_hotels_stdio() -> Result<()> {
     let path = find_binary()?;
 
     let mut child = Command::new(&path)
-        .arg("stdio")
+        .args(["stdio", "--strict-schema"])
         .stdout(Stdio::piped())
         .stdin(Stdio::piped())
         .stderr(Stdio::piped())
@@ -690,7 +786,7 @@ async fn test_mcp_hotels_with_unknown_amenity_warning() -> Result<()> {
     let path = find_binary()?;
 
     let mut child = Command::new(&path)
-        .arg("stdio")
+        .args(["stdio", "--strict-schema"])
         .stdout(Stdio::piped())
         .stdin(Stdio::piped())
         .stderr(Stdio::piped())
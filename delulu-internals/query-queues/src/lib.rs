@@ -64,6 +64,91 @@ impl AsyncSemaphore {
     }
 }
 
+/// Exponential backoff schedule, as used by [`QueryQueue::with_retry`]
+///
+/// Yields `initial_delay`, then doubles it after each step up to `max_delay`.
+/// Each value gets a random jitter of up to `jitter_factor` times itself, without
+/// exceeding `max_delay`. The iterator never ends: bound it with `take` or a
+/// retry counter.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut delays = Backoff::default().max_delay(Duration::from_secs(5)).take(10);
+/// while !job.is_done().await {
+///     let Some(delay) = delays.next() else { break };
+///     tokio::time::sleep(delay).await;
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Backoff {
+    delay: Duration,
+    max_delay: Duration,
+    jitter_factor: f64,
+    exponential: bool,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(30000),
+            jitter_factor: 0.5,
+            exponential: true,
+        }
+    }
+}
+
+impl Backoff {
+    /// First delay of the schedule.
+    pub fn initial_delay(mut self, initial_delay: Duration) -> Self {
+        self.delay = initial_delay;
+        self
+    }
+
+    /// Cap every delay at `max_delay`, jitter included.
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Random extra delay, as a fraction of the delay (0.0 disables jitter).
+    pub fn jitter_factor(mut self, jitter_factor: f64) -> Self {
+        self.jitter_factor = jitter_factor;
+        self
+    }
+
+    /// Double the delay after each step (the default), or keep it constant.
+    pub fn exponential(mut self, exponential: bool) -> Self {
+        self.exponential = exponential;
+        self
+    }
+
+    /// Apply jitter to the delay, without exceeding `max_delay`
+    fn apply_jitter(&self, delay: Duration) -> Duration {
+        if self.jitter_factor == 0.0 {
+            return delay;
+        }
+
+        let jitter_ms = (delay.as_millis() as f64 * self.jitter_factor) as u64;
+        let rand_jitter = rand::thread_rng().gen_range(0..=jitter_ms);
+
+        Duration::from_millis(delay.as_millis() as u64 + rand_jitter).min(self.max_delay)
+    }
+}
+
+impl Iterator for Backoff {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        let delay = self.apply_jitter(self.delay);
+        if self.exponential {
+            self.delay = self.delay.saturating_mul(2).min(self.max_delay);
+        }
+        Some(delay)
+    }
+}
+
 /// A simple work queue that limits concurrent requests to an external service
 /// and uses exponential backoff with jitter for retries
 ///
@@ -87,6 +172,7 @@ pub struct QueryQueue {
     max_retries: u32,
     exponential: bool,
     rate_limit: RateLimit,
+    attempt_timeout: Option<Duration>,
 }
 
 impl Default for QueryQueue {
@@ -99,6 +185,7 @@ impl Default for QueryQueue {
             max_retries: 3,
             exponential: true,
             rate_limit: RateLimit::ConcurrencyOnly,
+            attempt_timeout: None,
         }
     }
 }
@@ -129,6 +216,88 @@ impl QueryQueue {
         }
     }
 
+    /// Cap concurrent requests at `max_concurrent` (at least 1), keeping any QPS limit.
+    pub fn max_concurrent(mut self, max_concurrent: u64) -> Self {
+        self.semaphore = AsyncSemaphore::new(max_concurrent.max(1) as usize);
+        self
+    }
+
+    /// Cap the delay between retries at `max_delay`, jitter included.
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Bound each attempt inside [`Self::with_retry`] to `timeout`.
+    ///
+    /// An attempt that does not complete in time is dropped and counts as a
+    /// retryable failure. `None` (the default) lets attempts run indefinitely.
+    pub fn attempt_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.attempt_timeout = timeout;
+        self
+    }
+
+    /// The retry schedule of [`Self::with_retry`], for callers driving their
+    /// own retry or polling loop with the same settings.
+    pub fn backoff(&self) -> Backoff {
+        Backoff {
+            delay: self.initial_delay,
+            max_delay: self.max_delay,
+            jitter_factor: self.jitter_factor,
+            exponential: self.exponential,
+        }
+    }
+
+    /// Number of requests that could start right now without waiting for a
+    /// concurrency permit. A snapshot: it may change as soon as it is read.
+    pub fn available_permits(&self) -> usize {
+        self.semaphore.inner.available_permits()
+    }
+
+    /// Tokens left in the QPS bucket, as of its last refill. A snapshot: it
+    /// may change as soon as it is read.
+    ///
+    /// Returns `u64::MAX` for a concurrency-only queue, which has no bucket.
+    pub fn available_tokens(&self) -> u64 {
+        match &self.rate_limit {
+            RateLimit::ConcurrencyOnly => u64::MAX,
+            RateLimit::Qps { tokens, .. } => tokens.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Fill the QPS bucket back to its limit and wake tasks waiting for a
+    /// token, without waiting for the refill interval. For tests and
+    /// controlled bursts; a no-op for a concurrency-only queue.
+    pub async fn reset_tokens(&self) {
+        if let RateLimit::Qps {
+            limit,
+            tokens,
+            last_refill,
+            notify,
+            ..
+        } = &self.rate_limit
+        {
+            *last_refill.lock().await = Instant::now();
+            tokens.store(*limit, Ordering::SeqCst);
+            notify.notify_waiters();
+        }
+    }
+
+    /// Empty the QPS bucket, as if the limit had just been used up: requests
+    /// wait for the next refill (or [`Self::reset_tokens`]). A no-op for a
+    /// concurrency-only queue.
+    pub async fn drain_tokens(&self) {
+        if let RateLimit::Qps {
+            tokens,
+            last_refill,
+            ..
+        } = &self.rate_limit
+        {
+            *last_refill.lock().await = Instant::now();
+            tokens.store(0, Ordering::SeqCst);
+        }
+    }
+
     /// Refill tokens based on elapsed time
     async fn refill_tokens(&self) {
         match &self.rate_limit {
@@ -189,7 +358,21 @@ impl QueryQueue {
     ///
     /// The function `f` should return `Result<T, E>` where `E` implements `std::error::Error`.
     /// If the function returns `Err`, it will be retried with exponential backoff and jitter.
-    pub async fn with_retry<T, F, Fut>(&self, mut f: F) -> Result<T, QueryQueueError>
+    /// With an [`attempt_timeout`](Self::attempt_timeout), an attempt exceeding it is retried too.
+    pub async fn with_retry<T, F, Fut>(&self, f: F) -> Result<T, QueryQueueError>
+    where
+        F: FnMut() -> Fut + Send,
+        Fut: std::future::Future<Output = Result<T, anyhow::Error>> + Send,
+    {
+        self.with_retry_observed(f).await.map(|(result, _)| result)
+    }
+
+    /// Like [`with_retry`](Self::with_retry), but also returns how many times `f`
+    /// was retried before succeeding (0 when the first attempt did).
+    pub async fn with_retry_observed<T, F, Fut>(
+        &self,
+        mut f: F,
+    ) -> Result<(T, u32), QueryQueueError>
     where
         F: FnMut() -> Fut + Send,
         Fut: std::future::Future<Output = Result<T, anyhow::Error>> + Send,
@@ -206,39 +389,192 @@ impl QueryQueue {
 
         // Execute with backoff
         let mut retry_count = 0;
-        let mut delay = self.initial_delay;
+        let mut delays = self.backoff();
 
         loop {
-            match f().await {
-                Ok(result) => return Ok(result),
+            let attempt = match self.attempt_timeout {
+                Some(limit) => match time::timeout(limit, f()).await {
+                    Ok(result) => result,
+                    Err(_) => Err(anyhow::anyhow!("attempt timed out after {:?}", limit)),
+                },
+                None => f().await,
+            };
+            match attempt {
+                Ok(result) => return Ok((result, retry_count)),
                 Err(e) => {
                     retry_count += 1;
                     if retry_count > self.max_retries {
                         return Err(QueryQueueError::MaxRetriesExceeded(e));
                     }
 
-                    // Apply jitter to the delay
-                    let jittered_delay = self.apply_jitter(delay);
-                    time::sleep(jittered_delay).await;
+                    let delay = delays.next().unwrap_or(self.max_delay);
+                    time::sleep(delay).await;
+                }
+            }
+        }
+    }
+}
 
-                    // Increase delay for next retry if exponential is enabled
-                    if self.exponential {
-                        delay = std::cmp::min(delay * 2, self.max_delay);
-                    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+
+    #[tokio::test]
+    async fn test_attempt_timeout_is_retried() {
+        let queue = QueryQueue {
+            initial_delay: Duration::from_millis(1),
+            jitter_factor: 0.0,
+            max_retries: 2,
+            ..Default::default()
+        }
+        .attempt_timeout(Some(Duration::from_millis(20)));
+
+        let attempts = AtomicU32::new(0);
+        let result = queue
+            .with_retry(|| {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                async {
+                    time::sleep(Duration::from_secs(5)).await;
+                    Ok(())
                 }
+            })
+            .await;
+
+        match result {
+            Err(QueryQueueError::MaxRetriesExceeded(e)) => {
+                assert!(e.to_string().contains("timed out"))
             }
+            other => panic!("expected MaxRetriesExceeded, got {:?}", other),
         }
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
     }
 
-    /// Apply jitter to the delay
-    fn apply_jitter(&self, delay: Duration) -> Duration {
-        if self.jitter_factor == 0.0 {
-            return delay;
+    #[tokio::test]
+    async fn test_with_retry_observed_counts_retries() {
+        let queue = QueryQueue {
+            initial_delay: Duration::from_millis(1),
+            jitter_factor: 0.0,
+            ..Default::default()
+        };
+
+        let attempts = AtomicU32::new(0);
+        let (value, retries) = queue
+            .with_retry_observed(|| {
+                let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if attempt < 2 {
+                        anyhow::bail!("flaky");
+                    }
+                    Ok(attempt)
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!((value, retries), (2, 2));
+
+        let (_, retries) = queue
+            .with_retry_observed(|| async { Ok(()) })
+            .await
+            .unwrap();
+        assert_eq!(retries, 0);
+    }
+
+    #[test]
+    fn test_jitter_never_exceeds_max_delay() {
+        let queue = QueryQueue {
+            jitter_factor: 2.0,
+            ..Default::default()
         }
+        .max_delay(Duration::from_secs(1));
 
-        let jitter_ms = (delay.as_millis() as f64 * self.jitter_factor) as u64;
-        let rand_jitter = rand::thread_rng().gen_range(0..=jitter_ms);
+        let backoff = queue.backoff();
+        for _ in 0..100 {
+            assert!(backoff.apply_jitter(Duration::from_secs(1)) <= Duration::from_secs(1));
+        }
+        assert!(backoff.apply_jitter(Duration::from_millis(100)) <= Duration::from_millis(300));
+    }
+
+    #[test]
+    fn test_backoff_doubles_then_caps() {
+        let delays: Vec<_> = Backoff::default()
+            .initial_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_secs(1))
+            .jitter_factor(0.0)
+            .take(6)
+            .map(|d| d.as_millis())
+            .collect();
+        assert_eq!(delays, [100, 200, 400, 800, 1000, 1000]);
+
+        let constant: Vec<_> = Backoff::default()
+            .jitter_factor(0.0)
+            .exponential(false)
+            .take(3)
+            .collect();
+        assert_eq!(constant, [Duration::from_millis(100); 3]);
+    }
+
+    #[test]
+    fn test_backoff_jitter_within_bounds() {
+        let max_delay = Duration::from_millis(1000);
+        for _ in 0..20 {
+            let backoff = Backoff::default()
+                .initial_delay(Duration::from_millis(100))
+                .max_delay(max_delay)
+                .jitter_factor(0.5);
+            let mut base = Duration::from_millis(100);
+            for delay in backoff.take(8) {
+                assert!(delay >= base, "{:?} below {:?}", delay, base);
+                assert!(
+                    delay <= (base * 3 / 2).min(max_delay),
+                    "{:?} above bound",
+                    delay
+                );
+                base = (base * 2).min(max_delay);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_drain_then_reset_tokens() {
+        let queue = QueryQueue::with_qps_limit(2);
+
+        queue.drain_tokens().await;
+        assert_eq!(queue.available_tokens(), 0);
+        let blocked = time::timeout(Duration::from_millis(200), queue.acquire_token()).await;
+        assert!(blocked.is_err(), "acquire_token should wait after a drain");
+
+        let waiter = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.acquire_token().await }
+        });
+        time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiter.is_finished());
+
+        queue.reset_tokens().await;
+        time::timeout(Duration::from_millis(200), waiter)
+            .await
+            .expect("acquire_token should proceed after a reset")
+            .unwrap();
+        assert_eq!(queue.available_tokens(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_available_permits_and_tokens() {
+        let queue = QueryQueue::with_qps_limit(5).max_concurrent(3);
+        assert_eq!(queue.available_permits(), 3);
+        assert_eq!(queue.available_tokens(), 5);
+
+        let first = queue.semaphore.acquire().await.unwrap();
+        let _second = queue.semaphore.acquire().await.unwrap();
+        queue.acquire_token().await;
+        assert_eq!(queue.available_permits(), 1);
+        assert_eq!(queue.available_tokens(), 4);
+
+        drop(first);
+        assert_eq!(queue.available_permits(), 2);
 
-        Duration::from_millis(delay.as_millis() as u64 + rand_jitter)
+        let unlimited = QueryQueue::with_concurrency_limit(2);
+        assert_eq!(unlimited.available_tokens(), u64::MAX);
     }
 }